@@ -1,12 +1,38 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_initial;
+mod m20221205_000002_soft_delete;
+mod m20221212_000003_search;
+mod m20221219_000004_saved_view;
+mod m20221226_000005_event_id_sequence;
+mod m20230102_000006_webhook;
+mod m20230109_000007_git_integration;
+mod m20230116_000008_task_key;
+mod m20230123_000009_calendar;
+mod m20230130_000010_change_sequence;
+mod m20230206_000011_row_version;
+mod m20230213_000012_idempotency_key;
+mod m20230220_000013_key_prefix_resync;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_initial::Migration)]
+        vec![
+            Box::new(m20220101_000001_initial::Migration),
+            Box::new(m20221205_000002_soft_delete::Migration),
+            Box::new(m20221212_000003_search::Migration),
+            Box::new(m20221219_000004_saved_view::Migration),
+            Box::new(m20221226_000005_event_id_sequence::Migration),
+            Box::new(m20230102_000006_webhook::Migration),
+            Box::new(m20230109_000007_git_integration::Migration),
+            Box::new(m20230116_000008_task_key::Migration),
+            Box::new(m20230123_000009_calendar::Migration),
+            Box::new(m20230130_000010_change_sequence::Migration),
+            Box::new(m20230206_000011_row_version::Migration),
+            Box::new(m20230213_000012_idempotency_key::Migration),
+            Box::new(m20230220_000013_key_prefix_resync::Migration),
+        ]
     }
 }
@@ -0,0 +1,75 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tables searchable by `GET /search` with expressions their search vectors are generated from
+const SEARCHABLE_TABLES: [(&str, &str); 3] = [
+    (
+        "workspace",
+        r#"setweight(to_tsvector('english', "title"), 'A') || setweight(to_tsvector('english', coalesce("description", '')), 'B')"#,
+    ),
+    (
+        "task_group",
+        r#"setweight(to_tsvector('english', "title"), 'A')"#,
+    ),
+    (
+        "task",
+        r#"setweight(to_tsvector('english', "title"), 'A') || setweight(to_tsvector('english', "description"), 'B')"#,
+    ),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, search_vector) in SEARCHABLE_TABLES {
+            db.execute(Statement::from_string(
+                manager.get_database_backend(),
+                format!(
+                    r#"ALTER TABLE "{}" ADD COLUMN "search_vector" tsvector GENERATED ALWAYS AS ({}) STORED"#,
+                    table, search_vector
+                ),
+            ))
+            .await?;
+
+            db.execute(Statement::from_string(
+                manager.get_database_backend(),
+                format!(
+                    r#"CREATE INDEX "idx_{}_search_vector" ON "{}" USING GIN ("search_vector")"#,
+                    table, table
+                ),
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, _) in SEARCHABLE_TABLES {
+            db.execute(Statement::from_string(
+                manager.get_database_backend(),
+                format!(r#"DROP INDEX IF EXISTS "idx_{}_search_vector""#, table),
+            ))
+            .await?;
+
+            db.execute(Statement::from_string(
+                manager.get_database_backend(),
+                format!(
+                    r#"ALTER TABLE "{}" DROP COLUMN IF EXISTS "search_vector""#,
+                    table
+                ),
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
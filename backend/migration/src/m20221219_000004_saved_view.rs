@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_initial::table_with_id;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_with_id(
+                    SavedView::Id,
+                    Table::create()
+                        .table(SavedView::Table)
+                        .col(ColumnDef::new(SavedView::WorkspaceId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_saved_view_id_workspace_id")
+                                .from(SavedView::Table, SavedView::WorkspaceId)
+                                .to(Workspace::Table, Workspace::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(ColumnDef::new(SavedView::UserId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_saved_view_id_user_id")
+                                .from(SavedView::Table, SavedView::UserId)
+                                .to(User::Table, User::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(SavedView::Title)
+                                .string()
+                                .string_len(50)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(SavedView::Filter)
+                                .string()
+                                .string_len(255)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(SavedView::SortBy)
+                                .string()
+                                .string_len(20)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(SavedView::SortOrder)
+                                .string()
+                                .string_len(4)
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(SavedView::GroupBy).string().string_len(20))
+                        .col(
+                            ColumnDef::new(SavedView::VisibleFields)
+                                .array(ColumnType::String(Some(20)))
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(SavedView::Shared)
+                                .boolean()
+                                .not_null()
+                                .default(false),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().if_exists().table(SavedView::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Workspace {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum SavedView {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+
+    Title,
+    Filter,
+    SortBy,
+    SortOrder,
+    GroupBy,
+    VisibleFields,
+    Shared,
+}
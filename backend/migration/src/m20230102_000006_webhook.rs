@@ -0,0 +1,195 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_initial::table_with_id;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_with_id(
+                    Webhook::Id,
+                    Table::create()
+                        .table(Webhook::Table)
+                        .col(ColumnDef::new(Webhook::WorkspaceId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_webhook_id_workspace_id")
+                                .from(Webhook::Table, Webhook::WorkspaceId)
+                                .to(Workspace::Table, Workspace::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(ColumnDef::new(Webhook::UserId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_webhook_id_user_id")
+                                .from(Webhook::Table, Webhook::UserId)
+                                .to(User::Table, User::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(Webhook::Url)
+                                .string()
+                                .string_len(255)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(Webhook::Secret)
+                                .string()
+                                .string_len(255)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(Webhook::EventTypes)
+                                .array(ColumnType::String(Some(30)))
+                                .not_null(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_with_id(
+                    WebhookDelivery::Id,
+                    Table::create()
+                        .table(WebhookDelivery::Table)
+                        .col(
+                            ColumnDef::new(WebhookDelivery::WebhookId)
+                                .integer()
+                                .not_null(),
+                        )
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_webhook_delivery_id_webhook_id")
+                                .from(WebhookDelivery::Table, WebhookDelivery::WebhookId)
+                                .to(Webhook::Table, Webhook::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(WebhookDelivery::EventId)
+                                .big_integer()
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(WebhookDelivery::EventType)
+                                .string()
+                                .string_len(30)
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(WebhookDelivery::Payload).text().not_null())
+                        .col(
+                            ColumnDef::new(WebhookDelivery::Status)
+                                .string()
+                                .string_len(10)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(WebhookDelivery::Attempts)
+                                .integer()
+                                .not_null()
+                                .default(0),
+                        )
+                        .col(
+                            ColumnDef::new(WebhookDelivery::NextAttemptAt)
+                                .timestamp_with_time_zone()
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(WebhookDelivery::ResponseStatus).integer())
+                        .col(ColumnDef::new(WebhookDelivery::ResponseTimeMs).integer())
+                        .col(
+                            ColumnDef::new(WebhookDelivery::Error)
+                                .string()
+                                .string_len(255),
+                        )
+                        .col(
+                            ColumnDef::new(WebhookDelivery::CreatedAt)
+                                .timestamp_with_time_zone()
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(WebhookDelivery::DeliveredAt).timestamp_with_time_zone(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        // Delivery job looks for pending deliveries which are due
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_status_next_attempt_at")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::Status)
+                    .col(WebhookDelivery::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(WebhookDelivery::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().if_exists().table(Webhook::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Workspace {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Webhook {
+    Table,
+    Id,
+    WorkspaceId,
+    UserId,
+
+    Url,
+    Secret,
+    EventTypes,
+}
+
+#[derive(Iden)]
+enum WebhookDelivery {
+    Table,
+    Id,
+    WebhookId,
+
+    EventId,
+    EventType,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    ResponseStatus,
+    ResponseTimeMs,
+    Error,
+    CreatedAt,
+    DeliveredAt,
+}
@@ -0,0 +1,217 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_initial::table_with_id;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_with_id(
+                    GitIntegration::Id,
+                    Table::create()
+                        .table(GitIntegration::Table)
+                        .col(
+                            ColumnDef::new(GitIntegration::WorkspaceId)
+                                .integer()
+                                .not_null()
+                                .unique_key(),
+                        )
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_git_integration_id_workspace_id")
+                                .from(GitIntegration::Table, GitIntegration::WorkspaceId)
+                                .to(Workspace::Table, Workspace::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(GitIntegration::Secret)
+                                .string()
+                                .string_len(255)
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(GitIntegration::DoneTaskGroupId).integer())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_git_integration_id_done_task_group_id")
+                                .from(GitIntegration::Table, GitIntegration::DoneTaskGroupId)
+                                .to(TaskGroup::Table, TaskGroup::Id)
+                                .on_delete(ForeignKeyAction::SetNull),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_with_id(
+                    TaskCommit::Id,
+                    Table::create()
+                        .table(TaskCommit::Table)
+                        .col(ColumnDef::new(TaskCommit::TaskId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_task_commit_id_task_id")
+                                .from(TaskCommit::Table, TaskCommit::TaskId)
+                                .to(Task::Table, Task::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(TaskCommit::Sha)
+                                .string()
+                                .string_len(64)
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(TaskCommit::Message).text().not_null())
+                        .col(ColumnDef::new(TaskCommit::Url).string().string_len(255))
+                        .col(ColumnDef::new(TaskCommit::Author).string().string_len(255))
+                        .col(
+                            ColumnDef::new(TaskCommit::CreatedAt)
+                                .timestamp_with_time_zone()
+                                .not_null(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        // Commit is linked to a task once even if the push is delivered again
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_commit_task_id_sha")
+                    .table(TaskCommit::Table)
+                    .col(TaskCommit::TaskId)
+                    .col(TaskCommit::Sha)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_with_id(
+                    TaskHistory::Id,
+                    Table::create()
+                        .table(TaskHistory::Table)
+                        .col(ColumnDef::new(TaskHistory::TaskId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_task_history_id_task_id")
+                                .from(TaskHistory::Table, TaskHistory::TaskId)
+                                .to(Task::Table, Task::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(TaskHistory::Action)
+                                .string()
+                                .string_len(30)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(TaskHistory::CommitSha)
+                                .string()
+                                .string_len(64),
+                        )
+                        .col(ColumnDef::new(TaskHistory::FromTaskGroupId).integer())
+                        .col(ColumnDef::new(TaskHistory::ToTaskGroupId).integer())
+                        .col(
+                            ColumnDef::new(TaskHistory::CreatedAt)
+                                .timestamp_with_time_zone()
+                                .not_null(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(TaskHistory::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(TaskCommit::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(GitIntegration::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Workspace {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum TaskGroup {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Task {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum GitIntegration {
+    Table,
+    Id,
+    WorkspaceId,
+
+    Secret,
+    DoneTaskGroupId,
+}
+
+#[derive(Iden)]
+enum TaskCommit {
+    Table,
+    Id,
+    TaskId,
+
+    Sha,
+    Message,
+    Url,
+    Author,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum TaskHistory {
+    Table,
+    Id,
+    TaskId,
+
+    Action,
+    CommitSha,
+    FromTaskGroupId,
+    ToTaskGroupId,
+    CreatedAt,
+}
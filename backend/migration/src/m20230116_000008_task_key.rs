@@ -0,0 +1,147 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workspace::Table)
+                    .add_column(ColumnDef::new(Workspace::KeyPrefix).string().string_len(10))
+                    .add_column(
+                        ColumnDef::new(Workspace::NextTaskNumber)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::Number).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing workspaces get prefix from their id, e.g. W12, and their tasks are numbered
+        // in order of creation
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"UPDATE "workspace" SET "key_prefix" = 'W' || "id""#.to_owned(),
+        ))
+        .await?;
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"UPDATE "task" SET "number" = "numbered"."number" FROM (
+                SELECT "task"."id", row_number() OVER (
+                    PARTITION BY "task_group"."workspace_id" ORDER BY "task"."id"
+                ) AS "number"
+                FROM "task" JOIN "task_group" ON "task_group"."id" = "task"."task_group_id"
+            ) AS "numbered" WHERE "numbered"."id" = "task"."id""#
+                .to_owned(),
+        ))
+        .await?;
+        db.execute(Statement::from_string(
+            manager.get_database_backend(),
+            r#"UPDATE "workspace" SET "next_task_number" = coalesce((
+                SELECT max("task"."number") + 1
+                FROM "task" JOIN "task_group" ON "task_group"."id" = "task"."task_group_id"
+                WHERE "task_group"."workspace_id" = "workspace"."id"
+            ), 1)"#
+                .to_owned(),
+        ))
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workspace::Table)
+                    .modify_column(
+                        ColumnDef::new(Workspace::KeyPrefix)
+                            .string()
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .modify_column(ColumnDef::new(Task::Number).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Task keys are resolved among workspaces of the user
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_workspace_user_id_key_prefix")
+                    .table(Workspace::Table)
+                    .col(Workspace::UserId)
+                    .col(Workspace::KeyPrefix)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_workspace_user_id_key_prefix")
+                    .table(Workspace::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::Number)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workspace::Table)
+                    .drop_column(Workspace::KeyPrefix)
+                    .drop_column(Workspace::NextTaskNumber)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Workspace {
+    Table,
+    UserId,
+    KeyPrefix,
+    NextTaskNumber,
+}
+
+#[derive(Iden)]
+enum Task {
+    Table,
+    Number,
+}
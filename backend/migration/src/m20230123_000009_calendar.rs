@@ -0,0 +1,103 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_initial::table_with_id;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(ColumnDef::new(Task::DueAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                table_with_id(
+                    CalendarFeed::Id,
+                    Table::create()
+                        .table(CalendarFeed::Table)
+                        .col(
+                            ColumnDef::new(CalendarFeed::UserId)
+                                .integer()
+                                .not_null()
+                                .unique_key(),
+                        )
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_calendar_feed_id_user_id")
+                                .from(CalendarFeed::Table, CalendarFeed::UserId)
+                                .to(User::Table, User::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(CalendarFeed::Token)
+                                .string()
+                                .string_len(64)
+                                .not_null()
+                                .unique_key(),
+                        )
+                        .col(ColumnDef::new(CalendarFeed::Etag).string().string_len(64))
+                        .col(
+                            ColumnDef::new(CalendarFeed::LastModified)
+                                .timestamp_with_time_zone()
+                                .not_null(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(CalendarFeed::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_column(Task::DueAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Task {
+    Table,
+    DueAt,
+}
+
+#[derive(Iden)]
+enum CalendarFeed {
+    Table,
+    Id,
+    UserId,
+
+    Token,
+    Etag,
+    LastModified,
+}
@@ -0,0 +1,224 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+use super::m20220101_000001_initial::table_with_id;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Namespace of the advisory locks of users' changes, same as `CHANGES_LOCK_NAMESPACE`
+/// of the backend
+const CHANGES_LOCK_NAMESPACE: i32 = 1_937_337_955;
+
+/// Tables synced by `GET /sync` with the updates which change them for clients,
+/// task counter of workspaces is updated on every created task and is not synced
+const SYNCED_TABLES: [(&str, &str); 4] = [
+    (
+        "workspace",
+        r#"UPDATE OF "title", "description", "key_prefix", "deleted_at""#,
+    ),
+    ("task_group", "UPDATE"),
+    ("task", "UPDATE"),
+    ("label", "UPDATE"),
+];
+
+/// Every inserted or updated row of the synced tables takes the next value of `change_seq`,
+/// deleted rows are recorded as tombstones with it
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        db.execute(Statement::from_string(
+            backend,
+            r#"CREATE SEQUENCE "change_seq""#.to_owned(),
+        ))
+        .await?;
+
+        // Tombstones of deleted users stay, the user is deleted before their rows cascade
+        manager
+            .create_table(
+                table_with_id(
+                    Tombstone::Id,
+                    Table::create()
+                        .table(Tombstone::Table)
+                        .col(ColumnDef::new(Tombstone::UserId).integer().not_null())
+                        .col(
+                            ColumnDef::new(Tombstone::Entity)
+                                .string()
+                                .string_len(20)
+                                .not_null(),
+                        )
+                        .col(ColumnDef::new(Tombstone::EntityId).integer().not_null())
+                        .col(
+                            ColumnDef::new(Tombstone::ChangeSeq)
+                                .big_integer()
+                                .not_null(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tombstone_user_id_change_seq")
+                    .table(Tombstone::Table)
+                    .col(Tombstone::UserId)
+                    .col(Tombstone::ChangeSeq)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Writers hold the shared lock of the user until they commit,
+        // `GET /sync` takes the exclusive one before it reads the last value of the sequence
+        db.execute(Statement::from_string(
+            backend,
+            format!(
+                r#"CREATE FUNCTION "record_change"() RETURNS trigger AS $$
+                BEGIN
+                    PERFORM pg_advisory_xact_lock_shared({}, NEW."user_id");
+                    NEW."change_seq" := nextval('change_seq');
+                    RETURN NEW;
+                END
+                $$ LANGUAGE plpgsql"#,
+                CHANGES_LOCK_NAMESPACE
+            ),
+        ))
+        .await?;
+        db.execute(Statement::from_string(
+            backend,
+            format!(
+                r#"CREATE FUNCTION "record_deletion"() RETURNS trigger AS $$
+                BEGIN
+                    PERFORM pg_advisory_xact_lock_shared({}, OLD."user_id");
+                    INSERT INTO "tombstone" ("user_id", "entity", "entity_id", "change_seq")
+                    VALUES (OLD."user_id", TG_TABLE_NAME, OLD."id", nextval('change_seq'));
+                    RETURN OLD;
+                END
+                $$ LANGUAGE plpgsql"#,
+                CHANGES_LOCK_NAMESPACE
+            ),
+        ))
+        .await?;
+
+        for (table, update) in SYNCED_TABLES {
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"ALTER TABLE "{}" ADD COLUMN "change_seq" bigint NOT NULL DEFAULT nextval('change_seq')"#,
+                    table
+                ),
+            ))
+            .await?;
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"CREATE INDEX "idx_{}_user_id_change_seq" ON "{}" ("user_id", "change_seq")"#,
+                    table, table
+                ),
+            ))
+            .await?;
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"CREATE TRIGGER "record_change" BEFORE INSERT OR {} ON "{}" FOR EACH ROW EXECUTE FUNCTION "record_change"()"#,
+                    update, table
+                ),
+            ))
+            .await?;
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"CREATE TRIGGER "record_deletion" AFTER DELETE ON "{}" FOR EACH ROW EXECUTE FUNCTION "record_deletion"()"#,
+                    table
+                ),
+            ))
+            .await?;
+        }
+
+        // Clients drop tasks of deleted task groups, they are synced again on restore
+        db.execute(Statement::from_string(
+            backend,
+            r#"CREATE FUNCTION "resync_restored_tasks"() RETURNS trigger AS $$
+            BEGIN
+                UPDATE "task" SET "change_seq" = 0 WHERE "task_group_id" = NEW."id";
+                RETURN NEW;
+            END
+            $$ LANGUAGE plpgsql"#
+                .to_owned(),
+        ))
+        .await?;
+        db.execute(Statement::from_string(
+            backend,
+            r#"CREATE TRIGGER "resync_restored_tasks" AFTER UPDATE OF "deleted_at" ON "task_group" FOR EACH ROW
+            WHEN (OLD."deleted_at" IS NOT NULL AND NEW."deleted_at" IS NULL) EXECUTE FUNCTION "resync_restored_tasks"()"#
+                .to_owned(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        db.execute(Statement::from_string(
+            backend,
+            r#"DROP TRIGGER IF EXISTS "resync_restored_tasks" ON "task_group""#.to_owned(),
+        ))
+        .await?;
+        for (table, _) in SYNCED_TABLES {
+            db.execute(Statement::from_string(
+                backend,
+                format!(r#"DROP TRIGGER IF EXISTS "record_deletion" ON "{}""#, table),
+            ))
+            .await?;
+            db.execute(Statement::from_string(
+                backend,
+                format!(r#"DROP TRIGGER IF EXISTS "record_change" ON "{}""#, table),
+            ))
+            .await?;
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"ALTER TABLE "{}" DROP COLUMN IF EXISTS "change_seq""#,
+                    table
+                ),
+            ))
+            .await?;
+        }
+        db.execute(Statement::from_string(
+            backend,
+            r#"DROP FUNCTION IF EXISTS "resync_restored_tasks", "record_deletion", "record_change""#
+                .to_owned(),
+        ))
+        .await?;
+
+        manager
+            .drop_table(Table::drop().if_exists().table(Tombstone::Table).to_owned())
+            .await?;
+        db.execute(Statement::from_string(
+            backend,
+            r#"DROP SEQUENCE IF EXISTS "change_seq""#.to_owned(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Tombstone {
+    Table,
+    Id,
+    UserId,
+
+    Entity,
+    EntityId,
+    ChangeSeq,
+}
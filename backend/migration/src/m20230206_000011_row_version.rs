@@ -0,0 +1,86 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Namespace of the advisory locks of users' changes, same as `CHANGES_LOCK_NAMESPACE`
+/// of the backend
+const CHANGES_LOCK_NAMESPACE: i32 = 1_937_337_955;
+
+/// Tables with `record_change` trigger, their rows are versioned
+const VERSIONED_TABLES: [&str; 4] = ["workspace", "task_group", "task", "label"];
+
+/// Every update of a row which takes the next value of `change_seq` increments its version,
+/// clients send the version they have changed and conflicting writes are rejected
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        for table in VERSIONED_TABLES {
+            db.execute(Statement::from_string(
+                backend,
+                format!(
+                    r#"ALTER TABLE "{}" ADD COLUMN "version" integer NOT NULL DEFAULT 1"#,
+                    table
+                ),
+            ))
+            .await?;
+        }
+
+        // Same updates change the row for clients of `GET /sync` and of the other routes
+        db.execute(Statement::from_string(
+            backend,
+            format!(
+                r#"CREATE OR REPLACE FUNCTION "record_change"() RETURNS trigger AS $$
+                BEGIN
+                    PERFORM pg_advisory_xact_lock_shared({}, NEW."user_id");
+                    NEW."change_seq" := nextval('change_seq');
+                    IF TG_OP = 'UPDATE' THEN
+                        NEW."version" := OLD."version" + 1;
+                    END IF;
+                    RETURN NEW;
+                END
+                $$ LANGUAGE plpgsql"#,
+                CHANGES_LOCK_NAMESPACE
+            ),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        db.execute(Statement::from_string(
+            backend,
+            format!(
+                r#"CREATE OR REPLACE FUNCTION "record_change"() RETURNS trigger AS $$
+                BEGIN
+                    PERFORM pg_advisory_xact_lock_shared({}, NEW."user_id");
+                    NEW."change_seq" := nextval('change_seq');
+                    RETURN NEW;
+                END
+                $$ LANGUAGE plpgsql"#,
+                CHANGES_LOCK_NAMESPACE
+            ),
+        ))
+        .await?;
+
+        for table in VERSIONED_TABLES {
+            db.execute(Statement::from_string(
+                backend,
+                format!(r#"ALTER TABLE "{}" DROP COLUMN IF EXISTS "version""#, table),
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20220101_000001_initial::table_with_id;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Keys of creating requests with the responses which are replayed for their retries
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                table_with_id(
+                    IdempotencyKey::Id,
+                    Table::create()
+                        .table(IdempotencyKey::Table)
+                        .col(ColumnDef::new(IdempotencyKey::UserId).integer().not_null())
+                        .foreign_key(
+                            ForeignKey::create()
+                                .name("fk_idempotency_key_id_user_id")
+                                .from(IdempotencyKey::Table, IdempotencyKey::UserId)
+                                .to(User::Table, User::Id)
+                                .on_delete(ForeignKeyAction::Cascade),
+                        )
+                        .col(
+                            ColumnDef::new(IdempotencyKey::Key)
+                                .string()
+                                .string_len(255)
+                                .not_null(),
+                        )
+                        .col(
+                            ColumnDef::new(IdempotencyKey::RequestHash)
+                                .string()
+                                .string_len(64)
+                                .not_null(),
+                        )
+                        // Response is missing until the first request is handled
+                        .col(ColumnDef::new(IdempotencyKey::ResponseStatus).small_integer())
+                        .col(
+                            ColumnDef::new(IdempotencyKey::ResponseEtag)
+                                .string()
+                                .string_len(64),
+                        )
+                        .col(ColumnDef::new(IdempotencyKey::ResponseBody).text())
+                        .col(
+                            ColumnDef::new(IdempotencyKey::CreatedAt)
+                                .timestamp_with_time_zone()
+                                .not_null(),
+                        ),
+                )
+                .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_idempotency_key_user_id_key")
+                    .table(IdempotencyKey::Table)
+                    .col(IdempotencyKey::UserId)
+                    .col(IdempotencyKey::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_idempotency_key_created_at")
+                    .table(IdempotencyKey::Table)
+                    .col(IdempotencyKey::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .if_exists()
+                    .table(IdempotencyKey::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum IdempotencyKey {
+    Table,
+    Id,
+    UserId,
+
+    Key,
+    RequestHash,
+    ResponseStatus,
+    ResponseEtag,
+    ResponseBody,
+    CreatedAt,
+}
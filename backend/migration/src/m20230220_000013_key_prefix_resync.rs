@@ -0,0 +1,58 @@
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{ConnectionTrait, Statement},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Keys of tasks are made of the key prefix of their workspace, tasks of a workspace whose
+/// key prefix changes take the next `change_seq` so that clients sync their new keys
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        db.execute(Statement::from_string(
+            backend,
+            r#"CREATE FUNCTION "resync_task_keys"() RETURNS trigger AS $$
+            BEGIN
+                UPDATE "task" SET "change_seq" = 0 WHERE "task_group_id" IN (
+                    SELECT "id" FROM "task_group" WHERE "workspace_id" = NEW."id"
+                );
+                RETURN NEW;
+            END
+            $$ LANGUAGE plpgsql"#
+                .to_owned(),
+        ))
+        .await?;
+        db.execute(Statement::from_string(
+            backend,
+            r#"CREATE TRIGGER "resync_task_keys" AFTER UPDATE OF "key_prefix" ON "workspace" FOR EACH ROW
+            WHEN (OLD."key_prefix" IS DISTINCT FROM NEW."key_prefix") EXECUTE FUNCTION "resync_task_keys"()"#
+                .to_owned(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+
+        db.execute(Statement::from_string(
+            backend,
+            r#"DROP TRIGGER IF EXISTS "resync_task_keys" ON "workspace""#.to_owned(),
+        ))
+        .await?;
+        db.execute(Statement::from_string(
+            backend,
+            r#"DROP FUNCTION IF EXISTS "resync_task_keys""#.to_owned(),
+        ))
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! CORS of the API for the client and other allowed origins.
+//!
+//! Allowed origins are read from `CORS_ALLOWED_ORIGINS` (comma separated), only the origin
+//! of `CLIENT_URL` is allowed without it. Responses to allowed origins may be read with
+//! credentials, so that the client can use the `id` cookie. `*` allows any other origin
+//! without credentials. Preflight requests are answered with 204 before they reach the router.
+
+use reqwest::Url;
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{uri::Origin, Method, Status},
+    Data, Request, Response,
+};
+use std::io::Cursor;
+
+use backend::{get_env_var, get_optional_env_var};
+
+/// Path without routes which preflight requests are routed to
+const PREFLIGHT_PATH: &str = "/__preflight";
+const ALLOWED_METHODS: &str = "GET, POST, PATCH, PUT, DELETE";
+const ALLOWED_HEADERS: &str =
+    "Authorization, Content-Type, If-Match, Idempotency-Key, Last-Event-ID, X-CSRF-Token, X-Request-Id";
+/// Headers which the client can read besides the simple ones
+const EXPOSED_HEADERS: &str = "ETag, Idempotent-Replayed, Retry-After, RateLimit-Limit, RateLimit-Remaining, RateLimit-Reset, RateLimit-Policy, X-Request-Id";
+/// Browsers cache preflight responses for this many seconds
+const PREFLIGHT_MAX_AGE_SECS: u32 = 60 * 60;
+
+/// Request is a preflight request, its response has no body
+struct Preflight;
+
+pub struct Cors {
+    allowed_origins: Vec<String>,
+}
+
+impl Cors {
+    /// Reads allowed origins, panics on invalid configuration
+    pub fn new() -> Cors {
+        let allowed_origins = match get_optional_env_var("CORS_ALLOWED_ORIGINS")
+            .filter(|origins| !origins.trim().is_empty())
+        {
+            Some(origins) => origins
+                .split(',')
+                .map(|origin| origin.trim())
+                .filter(|origin| !origin.is_empty())
+                .map(|origin| origin.trim_end_matches('/').to_string())
+                .collect(),
+            None => {
+                let client_url = get_env_var("CLIENT_URL");
+                let client_origin = Url::parse(&client_url)
+                    .expect("[ENV]: CLIENT_URL has to be an absolute URL")
+                    .origin()
+                    .ascii_serialization();
+                vec![client_origin]
+            }
+        };
+
+        Cors { allowed_origins }
+    }
+
+    /// Value of `Access-Control-Allow-Origin` for the origin and whether it is allowed
+    /// to use credentials, only listed origins are
+    fn allow_origin<'a>(&self, origin: &'a str) -> Option<(&'a str, bool)> {
+        let is_allowed = |allowed: &str| {
+            self.allowed_origins
+                .iter()
+                .any(|allowed_origin| allowed_origin == allowed)
+        };
+        if is_allowed(origin) {
+            Some((origin, true))
+        } else if is_allowed("*") {
+            Some(("*", false))
+        } else {
+            None
+        }
+    }
+
+    /// Only `*` allows all origins with the same headers
+    fn varies_by_origin(&self) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed_origin| allowed_origin != "*")
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let is_preflight = request.method() == Method::Options
+            && request.headers().contains("Origin")
+            && request.headers().contains("Access-Control-Request-Method");
+        if is_preflight {
+            request.local_cache(|| Some(Preflight));
+            request.set_uri(Origin::parse(PREFLIGHT_PATH).unwrap());
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let is_preflight = request.local_cache(|| None::<Preflight>).is_some();
+        if is_preflight {
+            response.set_status(Status::NoContent);
+            response.remove_header("Content-Type");
+            response.set_sized_body(0, Cursor::new(""));
+        }
+
+        // Responses differ by origin unless every origin is allowed the same way,
+        // so caches must not reuse a response without CORS headers for an allowed origin
+        if self.varies_by_origin() {
+            response.adjoin_raw_header("Vary", "Origin");
+        }
+
+        let (allowed_origin, allows_credentials) = match request
+            .headers()
+            .get_one("Origin")
+            .and_then(|origin| self.allow_origin(origin))
+        {
+            Some(allowed) => allowed,
+            None => return,
+        };
+
+        response.set_raw_header("Access-Control-Allow-Origin", allowed_origin.to_string());
+        if allows_credentials {
+            response.set_raw_header("Access-Control-Allow-Credentials", "true");
+        }
+        if is_preflight {
+            response.set_raw_header("Access-Control-Allow-Methods", ALLOWED_METHODS);
+            response.set_raw_header("Access-Control-Allow-Headers", ALLOWED_HEADERS);
+            response.set_raw_header("Access-Control-Max-Age", PREFLIGHT_MAX_AGE_SECS.to_string());
+        } else {
+            response.set_raw_header("Access-Control-Expose-Headers", EXPOSED_HEADERS);
+        }
+    }
+}
@@ -11,6 +11,9 @@ pub struct Model {
     pub description: Option<String>,
     #[sea_orm(primary_key)]
     pub id: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub change_seq: i64,
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,10 +2,19 @@
 
 pub mod prelude;
 
+pub mod calendar_feed;
+pub mod git_integration;
+pub mod idempotency_key;
 pub mod label;
+pub mod saved_view;
 pub mod sea_orm_active_enums;
 pub mod social_profile;
 pub mod task;
+pub mod task_commit;
 pub mod task_group;
+pub mod task_history;
+pub mod tombstone;
 pub mod user;
+pub mod webhook;
+pub mod webhook_delivery;
 pub mod workspace;
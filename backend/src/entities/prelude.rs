@@ -1,8 +1,17 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.3
 
+pub use super::calendar_feed::Entity as CalendarFeed;
+pub use super::git_integration::Entity as GitIntegration;
+pub use super::idempotency_key::Entity as IdempotencyKey;
 pub use super::label::Entity as Label;
+pub use super::saved_view::Entity as SavedView;
 pub use super::social_profile::Entity as SocialProfile;
 pub use super::task::Entity as Task;
+pub use super::task_commit::Entity as TaskCommit;
 pub use super::task_group::Entity as TaskGroup;
+pub use super::task_history::Entity as TaskHistory;
+pub use super::tombstone::Entity as Tombstone;
 pub use super::user::Entity as User;
+pub use super::webhook::Entity as Webhook;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
 pub use super::workspace::Entity as Workspace;
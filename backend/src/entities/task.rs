@@ -12,6 +12,10 @@ pub struct Model {
     pub labels_ids: Option<Vec<i32>>,
     #[sea_orm(primary_key)]
     pub id: i32,
+    pub number: i32,
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub change_seq: i64,
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +36,10 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     User,
+    #[sea_orm(has_many = "super::task_commit::Entity")]
+    TaskCommit,
+    #[sea_orm(has_many = "super::task_history::Entity")]
+    TaskHistory,
 }
 
 impl Related<super::task_group::Entity> for Entity {
@@ -46,4 +54,16 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::task_commit::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskCommit.def()
+    }
+}
+
+impl Related<super::task_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TaskHistory.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -10,6 +10,9 @@ pub struct Model {
     pub title: String,
     #[sea_orm(primary_key)]
     pub id: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub change_seq: i64,
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +35,8 @@ pub enum Relation {
     Workspace,
     #[sea_orm(has_many = "super::task::Entity")]
     Task,
+    #[sea_orm(has_many = "super::git_integration::Entity")]
+    GitIntegration,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -52,4 +57,10 @@ impl Related<super::task::Entity> for Entity {
     }
 }
 
+impl Related<super::git_integration::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GitIntegration.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
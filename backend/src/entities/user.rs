@@ -21,8 +21,16 @@ pub enum Relation {
     SocialProfile,
     #[sea_orm(has_many = "super::label::Entity")]
     Label,
+    #[sea_orm(has_many = "super::saved_view::Entity")]
+    SavedView,
+    #[sea_orm(has_many = "super::webhook::Entity")]
+    Webhook,
     #[sea_orm(has_many = "super::task::Entity")]
     Task,
+    #[sea_orm(has_one = "super::calendar_feed::Entity")]
+    CalendarFeed,
+    #[sea_orm(has_many = "super::idempotency_key::Entity")]
+    IdempotencyKey,
 }
 
 impl Related<super::workspace::Entity> for Entity {
@@ -49,10 +57,34 @@ impl Related<super::label::Entity> for Entity {
     }
 }
 
+impl Related<super::saved_view::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SavedView.def()
+    }
+}
+
+impl Related<super::webhook::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Webhook.def()
+    }
+}
+
 impl Related<super::task::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Task.def()
     }
 }
 
+impl Related<super::calendar_feed::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CalendarFeed.def()
+    }
+}
+
+impl Related<super::idempotency_key::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IdempotencyKey.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
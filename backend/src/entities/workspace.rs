@@ -10,6 +10,11 @@ pub struct Model {
     pub user_id: i32,
     pub title: String,
     pub description: Option<String>,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub key_prefix: String,
+    pub next_task_number: i32,
+    pub change_seq: i64,
+    pub version: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -26,6 +31,12 @@ pub enum Relation {
     TaskGroup,
     #[sea_orm(has_many = "super::label::Entity")]
     Label,
+    #[sea_orm(has_many = "super::saved_view::Entity")]
+    SavedView,
+    #[sea_orm(has_many = "super::webhook::Entity")]
+    Webhook,
+    #[sea_orm(has_one = "super::git_integration::Entity")]
+    GitIntegration,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -46,4 +57,22 @@ impl Related<super::label::Entity> for Entity {
     }
 }
 
+impl Related<super::saved_view::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SavedView.def()
+    }
+}
+
+impl Related<super::webhook::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Webhook.def()
+    }
+}
+
+impl Related<super::git_integration::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GitIntegration.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
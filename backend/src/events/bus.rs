@@ -0,0 +1,321 @@
+//! Bus which delivers changes made by mutation routes to event streams of workspaces.
+//!
+//! Events are published with Postgres `NOTIFY` so that they reach every backend instance,
+//! they are sent in the transaction of the change and delivered only when it commits,
+//! together with the queued webhook deliveries. Each instance listens on `EVENTS_CHANNEL`
+//! (see `jobs::event_listener`) and delivers the events to its streams. Event ids come from
+//! a database sequence and delivered events are kept in a bounded replay buffer, so that
+//! a client which reconnects with `Last-Event-ID` to any instance receives the events
+//! it has missed.
+
+use rocket::{
+    serde::{Deserialize, Serialize},
+    tokio::sync::broadcast::{self, Receiver, Sender},
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, ConnectionTrait, DbBackend, DbErr, Statement};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use backend::{
+    entities::{label, task},
+    services::{task_keys::task_key, webhooks::enqueue_deliveries},
+};
+
+pub const EVENTS_CHANNEL: &str = "workspace_events";
+
+/// Values of `type` of the events, webhooks subscribe to these
+pub const EVENT_TYPES: [&str; 14] = [
+    "workspace_updated",
+    "workspace_deleted",
+    "task_group_created",
+    "task_group_updated",
+    "task_group_deleted",
+    "task_group_restored",
+    "task_created",
+    "task_updated",
+    "task_moved",
+    "task_deleted",
+    "label_created",
+    "label_updated",
+    "label_deleted",
+    "label_restored",
+];
+
+const CHANNEL_CAPACITY: usize = 256;
+const REPLAY_BUFFER_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct WorkspaceEvent {
+    pub id: u64,
+    pub workspace_id: i32,
+    #[serde(flatten)]
+    pub kind: WorkspaceEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(
+    crate = "rocket::serde",
+    tag = "type",
+    content = "data",
+    rename_all = "snake_case"
+)]
+pub enum WorkspaceEventKind {
+    WorkspaceUpdated {
+        title: String,
+        description: Option<String>,
+        key_prefix: String,
+    },
+    WorkspaceDeleted,
+    TaskGroupCreated {
+        id: i32,
+        title: String,
+    },
+    TaskGroupUpdated {
+        id: i32,
+        title: String,
+    },
+    TaskGroupDeleted {
+        id: i32,
+    },
+    TaskGroupRestored {
+        id: i32,
+    },
+    TaskCreated(EventTask),
+    TaskUpdated(EventTask),
+    TaskMoved {
+        id: i32,
+        from_task_group_id: i32,
+        to_task_group_id: i32,
+    },
+    TaskDeleted {
+        id: i32,
+        task_group_id: i32,
+    },
+    LabelCreated(EventLabel),
+    LabelUpdated(EventLabel),
+    LabelDeleted {
+        id: i32,
+    },
+    LabelRestored {
+        id: i32,
+    },
+}
+
+impl WorkspaceEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorkspaceEventKind::WorkspaceUpdated { .. } => "workspace_updated",
+            WorkspaceEventKind::WorkspaceDeleted => "workspace_deleted",
+            WorkspaceEventKind::TaskGroupCreated { .. } => "task_group_created",
+            WorkspaceEventKind::TaskGroupUpdated { .. } => "task_group_updated",
+            WorkspaceEventKind::TaskGroupDeleted { .. } => "task_group_deleted",
+            WorkspaceEventKind::TaskGroupRestored { .. } => "task_group_restored",
+            WorkspaceEventKind::TaskCreated(_) => "task_created",
+            WorkspaceEventKind::TaskUpdated(_) => "task_updated",
+            WorkspaceEventKind::TaskMoved { .. } => "task_moved",
+            WorkspaceEventKind::TaskDeleted { .. } => "task_deleted",
+            WorkspaceEventKind::LabelCreated(_) => "label_created",
+            WorkspaceEventKind::LabelUpdated(_) => "label_updated",
+            WorkspaceEventKind::LabelDeleted { .. } => "label_deleted",
+            WorkspaceEventKind::LabelRestored { .. } => "label_restored",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct EventTask {
+    pub id: i32,
+    pub key: String,
+    pub task_group_id: i32,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Vec<i32>,
+    pub due_at: Option<DateTimeWithTimeZone>,
+}
+
+impl EventTask {
+    pub fn new(task_model: task::Model, key_prefix: &str) -> EventTask {
+        EventTask {
+            id: task_model.id,
+            key: task_key(key_prefix, task_model.number),
+            task_group_id: task_model.task_group_id,
+            title: task_model.title,
+            description: task_model.description,
+            labels_ids: task_model.labels_ids.unwrap_or_default(),
+            due_at: task_model.due_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct EventLabel {
+    pub id: i32,
+    pub color: String,
+    pub description: Option<String>,
+}
+
+impl EventLabel {
+    pub fn new(label_model: label::Model) -> EventLabel {
+        EventLabel {
+            id: label_model.id,
+            color: label_model.color,
+            description: label_model.description,
+        }
+    }
+}
+
+/// Events of all workspaces, streams pick the ones of their workspace.
+///
+/// Clones share the same channel and replay buffer.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: Sender<BusMessage>,
+    replay_buffer: Arc<Mutex<VecDeque<WorkspaceEvent>>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum BusMessage {
+    Event(WorkspaceEvent),
+    /// Events may have been lost, streams have to tell their clients to reload
+    Resync,
+}
+
+pub struct Subscription {
+    /// Id of the last event delivered before subscribing
+    pub last_event_id: Option<u64>,
+    /// Events of the workspace delivered after the requested event
+    pub missed_events: Vec<WorkspaceEvent>,
+    /// Requested event is no longer in the replay buffer, the client has to reload the workspace
+    pub resync_required: bool,
+    pub receiver: Receiver<BusMessage>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        EventBus {
+            sender,
+            replay_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(REPLAY_BUFFER_SIZE))),
+        }
+    }
+
+    /// Notifies all instances about the change and queues its webhook deliveries, the event
+    /// reaches streams once it comes back through `LISTEN`.
+    /// `db` is the transaction which makes the change, `NOTIFY` is sent and the deliveries
+    /// are stored only when it commits, so the event is published if and only if the change is.
+    pub async fn publish<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        workspace_id: i32,
+        kind: WorkspaceEventKind,
+    ) -> Result<(), DbErr> {
+        publish_event(db, workspace_id, kind).await
+    }
+
+    /// Delivers event received from `LISTEN` to the streams of this instance
+    pub fn deliver(&self, event: WorkspaceEvent) {
+        // Event is sent while the buffer is locked so that an event is either replayed
+        // to a new subscriber or received by it, never both
+        let mut replay_buffer = self.replay_buffer.lock().unwrap();
+        if replay_buffer.len() == REPLAY_BUFFER_SIZE {
+            replay_buffer.pop_front();
+        }
+        replay_buffer.push_back(event.clone());
+
+        // Sending fails only when nobody is subscribed
+        let _ = self.sender.send(BusMessage::Event(event));
+    }
+
+    /// Tells all streams that events may have been lost, e.g. while the listener was
+    /// reconnecting, the replay buffer can not be trusted afterwards either
+    pub fn resync(&self) {
+        let mut replay_buffer = self.replay_buffer.lock().unwrap();
+        replay_buffer.clear();
+        let _ = self.sender.send(BusMessage::Resync);
+    }
+
+    /// Subscribes to events of the workspace, events delivered after the one with
+    /// `last_event_id` are replayed from the buffer
+    pub fn subscribe(&self, workspace_id: i32, last_event_id: Option<u64>) -> Subscription {
+        let replay_buffer = self.replay_buffer.lock().unwrap();
+        let receiver = self.sender.subscribe();
+
+        // Ids come from concurrent transactions and may arrive slightly out of order,
+        // so the missed events are the ones delivered after the requested one
+        let (missed_events, resync_required) = match last_event_id {
+            Some(requested_id) => {
+                match replay_buffer
+                    .iter()
+                    .position(|event| event.id == requested_id)
+                {
+                    Some(position) => (
+                        replay_buffer
+                            .iter()
+                            .skip(position + 1)
+                            .filter(|event| event.workspace_id == workspace_id)
+                            .cloned()
+                            .collect(),
+                        false,
+                    ),
+                    None => (vec![], true),
+                }
+            }
+            None => (vec![], false),
+        };
+
+        Subscription {
+            last_event_id: replay_buffer.back().map(|event| event.id),
+            missed_events,
+            resync_required,
+            receiver,
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> EventBus {
+        EventBus::new()
+    }
+}
+
+async fn publish_event<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    kind: WorkspaceEventKind,
+) -> Result<(), DbErr> {
+    // Id comes from the sequence so that it is unique across instances
+    let next_id_res = db
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            r#"SELECT nextval('workspace_event_id_seq') AS "id""#.to_owned(),
+        ))
+        .await?;
+    let event_id: i64 = match next_id_res {
+        Some(row) => row.try_get("", "id")?,
+        None => return Err(DbErr::Custom("Could not get next event id".into())),
+    };
+
+    let event_type = kind.name();
+    let payload = serde_json::to_string(&WorkspaceEvent {
+        id: event_id as u64,
+        workspace_id,
+        kind,
+    })
+    .map_err(|err| DbErr::Custom(err.to_string()))?;
+
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_notify($1, $2)",
+        vec![EVENTS_CHANNEL.into(), payload.clone().into()],
+    ))
+    .await?;
+
+    enqueue_deliveries(db, workspace_id, event_id, event_type, &payload).await?;
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use rocket::{fairing::AdHoc, tokio::time};
+use sqlx::postgres::PgListener;
+use std::time::Duration;
+
+use backend::get_env_var;
+
+use crate::events::bus::{EventBus, WorkspaceEvent, EVENTS_CHANNEL};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Listens for events published by any instance and delivers them to the event streams
+/// of this instance
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Event listener", |rocket| {
+        Box::pin(async move {
+            let event_bus = rocket
+                .state::<EventBus>()
+                .expect("[EVENTS]: EventBus has to be managed")
+                .clone();
+            rocket::tokio::spawn(run(event_bus));
+        })
+    })
+}
+
+async fn run(event_bus: EventBus) {
+    let database_url = get_env_var("DATABASE_URL");
+
+    loop {
+        let listen_res = async {
+            let mut listener = PgListener::connect(&database_url).await?;
+            listener.listen(EVENTS_CHANNEL).await?;
+            listen(&mut listener, &event_bus).await
+        }
+        .await;
+
+        if let Err(err) = listen_res {
+            error!("[EVENTS]: Could not listen for events: {}", err);
+        }
+        // Events published while not listening are lost
+        event_bus.resync();
+        time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn listen(listener: &mut PgListener, event_bus: &EventBus) -> Result<(), sqlx::Error> {
+    loop {
+        // `None` means that the connection was lost and notifications may have been missed,
+        // listener reconnects on the next call
+        match listener.try_recv().await? {
+            Some(notification) => {
+                match serde_json::from_str::<WorkspaceEvent>(notification.payload()) {
+                    Ok(event) => event_bus.deliver(event),
+                    Err(err) => error!("[EVENTS]: Could not parse event: {}", err),
+                }
+            }
+            None => event_bus.resync(),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod event_listener;
+pub mod purge_idempotency_keys;
+pub mod purge_trash;
+pub mod webhook_delivery;
@@ -0,0 +1,36 @@
+use rocket::{fairing::AdHoc, tokio::time};
+use std::time::Duration;
+
+use backend::{establish_db_connection, services::idempotency::purge_expired_keys};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically removes idempotency keys which can not be replayed anymore,
+/// see `services::idempotency`
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Idempotency keys purge", |_| {
+        Box::pin(async move {
+            rocket::tokio::spawn(run());
+        })
+    })
+}
+
+async fn run() {
+    let mut interval = time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let purge_res = async {
+            let db = establish_db_connection().await?;
+            purge_expired_keys(&db).await
+        }
+        .await;
+
+        if let Err(err) = purge_res {
+            error!(
+                "[IDEMPOTENCY]: Could not purge expired idempotency keys: {}",
+                err
+            );
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use rocket::{fairing::AdHoc, tokio::time};
+use std::time::Duration;
+
+use backend::{
+    current_timestamp, establish_db_connection, get_optional_env_var,
+    services::deletion::purge_trash,
+};
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically removes items which have been in trash for longer than
+/// `TRASH_RETENTION_DAYS` (30 days by default)
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Trash purge", |_| {
+        Box::pin(async move {
+            rocket::tokio::spawn(run());
+        })
+    })
+}
+
+async fn run() {
+    let retention_days = match get_optional_env_var("TRASH_RETENTION_DAYS") {
+        Some(days) => days
+            .parse::<i64>()
+            .expect("[ENV]: TRASH_RETENTION_DAYS has to be a number of days"),
+        None => DEFAULT_RETENTION_DAYS,
+    };
+
+    let mut interval = time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let purge_before = current_timestamp() - chrono::Duration::days(retention_days);
+        let purge_res = async move {
+            let db = establish_db_connection().await?;
+            purge_trash(&db, purge_before).await
+        }
+        .await;
+
+        if let Err(err) = purge_res {
+            error!("[TRASH]: Could not purge trash: {}", err);
+        }
+    }
+}
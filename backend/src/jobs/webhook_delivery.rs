@@ -0,0 +1,37 @@
+use rocket::{fairing::AdHoc, tokio::time};
+use std::time::Duration;
+
+use backend::{
+    establish_db_connection,
+    services::webhooks::{deliver_due, BATCH_SIZE},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Sends queued webhook deliveries, see `services::webhooks`
+pub fn fairing() -> AdHoc {
+    AdHoc::on_liftoff("Webhook delivery", |_| {
+        Box::pin(async move {
+            rocket::tokio::spawn(run());
+        })
+    })
+}
+
+async fn run() {
+    loop {
+        let deliver_res = async {
+            let db = establish_db_connection().await?;
+            deliver_due(&db).await
+        }
+        .await;
+
+        match deliver_res {
+            // More deliveries may be due, continue right away
+            Ok(delivered) if delivered == BATCH_SIZE => continue,
+            Ok(_) => (),
+            Err(err) => error!("[WEBHOOKS]: Could not send webhook deliveries: {}", err),
+        }
+
+        time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -1,14 +1,38 @@
-use sea_orm::{Database, DatabaseConnection, DbErr};
+use chrono::Utc;
+use lazy_static::lazy_static;
+use rocket::tokio::sync::OnceCell;
+use sea_orm::{prelude::DateTimeWithTimeZone, Database, DatabaseConnection, DbErr};
 use std::env;
 
 pub mod entities;
+pub mod services;
 
+lazy_static! {
+    static ref DB_CONNECTION: OnceCell<DatabaseConnection> = OnceCell::new();
+}
+
+/// Connection pool shared by all requests and jobs, it is opened by the first call
 pub async fn establish_db_connection() -> Result<DatabaseConnection, DbErr> {
-    let database_url = get_env_var("DATABASE_URL");
-    Database::connect(database_url).await
+    DB_CONNECTION
+        .get_or_try_init(|| async {
+            let database_url = get_env_var("DATABASE_URL");
+            let mut db = Database::connect(database_url).await?;
+            db.set_metric_callback(services::metrics::observe_query);
+            Ok(db)
+        })
+        .await
+        .cloned()
 }
 
 pub fn get_env_var<S: Into<String>>(key: S) -> String {
     let k: String = key.into();
     env::var(&k).expect(&format!("[ENV]: Could not find {}", &k))
 }
+
+pub fn get_optional_env_var<S: Into<String>>(key: S) -> Option<String> {
+    env::var(key.into()).ok()
+}
+
+pub fn current_timestamp() -> DateTimeWithTimeZone {
+    Utc::now().into()
+}
@@ -5,6 +5,11 @@ use dotenvy::dotenv;
 use rocket::serde::json::Json;
 use routes::lib::SuccessResponse;
 
+mod cors;
+mod events;
+mod jobs;
+mod rate_limit;
+mod request_log;
 mod routes;
 
 #[get("/")]
@@ -15,9 +20,22 @@ fn index() -> Json<SuccessResponse<&'static str>> {
 #[launch]
 fn rocket() -> _ {
     dotenv().ok();
+    request_log::init_subscriber();
 
     rocket::build()
-        .mount("/", routes![index])
+        .manage(events::bus::EventBus::new())
+        .manage(routes::graphql::lib::build_schema())
+        .attach(cors::Cors::new())
+        .attach(rate_limit::RateLimiter::new())
+        .attach(routes::lib::csrf_fairing())
+        .attach(routes::lib::idempotency_fairing())
+        // Last, so that responses are logged as they are sent
+        .attach(request_log::RequestLogger)
+        .attach(jobs::event_listener::fairing())
+        .attach(jobs::purge_trash::fairing())
+        .attach(jobs::purge_idempotency_keys::fairing())
+        .attach(jobs::webhook_delivery::fairing())
+        .mount("/", routes![index, routes::metrics::get::handler])
         .mount(
             "/api/v1/auth/signin",
             routes![routes::auth::sign_in::handler],
@@ -33,6 +51,7 @@ fn rocket() -> _ {
             "/api/v1/auth/signout",
             routes![routes::auth::sign_out::handler],
         )
+        .mount("/api", routes![routes::graphql::execute::handler])
         .mount(
             "/api/v1",
             routes![
@@ -41,14 +60,50 @@ fn rocket() -> _ {
                 routes::workspace::delete::handler,
                 routes::workspace::select_all::handler,
                 routes::workspace::select_one::handler,
+                routes::workspace::events::handler,
+                routes::workspace::export::handler,
+                routes::workspace::import::handler,
                 routes::user::get::handler,
                 routes::task_group::insert::handler,
                 routes::task_group::update::handler,
                 routes::task_group::delete::handler,
+                routes::task_group::select_tasks::handler,
                 routes::task::insert::handler,
+                routes::task::select_all::handler,
+                routes::task::select_by_key::handler,
+                routes::task::export_csv::handler,
+                routes::task::import_csv::handler,
+                routes::task::update::handler,
+                routes::task::delete::handler,
+                routes::task::select_history::handler,
                 routes::label::insert::handler,
                 routes::label::update::handler,
                 routes::label::delete::handler,
+                routes::batch::execute::handler,
+                routes::trash::select_all::handler,
+                routes::trash::restore::handler,
+                routes::search::get::handler,
+                routes::sync::get::handler,
+                routes::view::insert::handler,
+                routes::view::select_all::handler,
+                routes::view::update::handler,
+                routes::view::delete::handler,
+                routes::view::select_tasks::handler,
+                routes::webhook::insert::handler,
+                routes::webhook::select_all::handler,
+                routes::webhook::update::handler,
+                routes::webhook::delete::handler,
+                routes::webhook::select_deliveries::handler,
+                routes::git::update_integration::handler,
+                routes::git::select_integration::handler,
+                routes::git::delete_integration::handler,
+                routes::git::push::handler,
+                routes::calendar::feed::handler,
+                routes::calendar::rotate_token::handler,
+                routes::calendar::delete_token::handler,
+                routes::docs::openapi::handler,
+                routes::docs::ui::handler,
+                routes::docs::ui::asset_handler,
             ],
         )
 }
@@ -0,0 +1,281 @@
+//! Token bucket rate limits of the API.
+//!
+//! Every route group has its own limit, `<requests>/<seconds>` read from `RATE_LIMIT_<GROUP>`.
+//! Buckets of authenticated requests are kept per user, anonymous requests share the bucket
+//! of their client IP. The IP is the address of the connection, `X-Real-IP` (Rocket's
+//! `ip_header`) is only used when the connection comes from one of `TRUSTED_PROXIES`,
+//! otherwise any client could pick its own bucket. A bucket holds up to `requests` tokens
+//! and is refilled evenly over `seconds`, every request takes one token.
+//!
+//! Requests without a token are routed to a path without routes before they reach a handler,
+//! their response is replaced by 429. Responses of all grouped routes have `RateLimit-*` headers.
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::{uri::Origin, ContentType, Method, Status},
+    Data, Request, Response,
+};
+use serde_json::json as serde_json;
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use backend::get_optional_env_var;
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponseBody};
+
+/// Path without routes which limited requests are routed to
+const LIMITED_PATH: &str = "/__rate_limited";
+/// Full buckets are removed after this long, they are the same as new ones
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Groups are matched by prefix of the path in this order
+const ROUTE_GROUPS: [RouteGroup; 3] = [
+    RouteGroup {
+        name: "AUTH",
+        path_prefix: "/api/v1/auth",
+        default_limit: (20, 60),
+    },
+    RouteGroup {
+        name: "GRAPHQL",
+        path_prefix: "/api/graphql",
+        default_limit: (120, 60),
+    },
+    RouteGroup {
+        name: "API",
+        path_prefix: "/api",
+        default_limit: (300, 60),
+    },
+];
+
+struct RouteGroup {
+    name: &'static str,
+    path_prefix: &'static str,
+    /// Requests per seconds
+    default_limit: (u32, u64),
+}
+
+#[derive(Clone, Copy)]
+struct Limit {
+    requests: u32,
+    per: Duration,
+}
+
+impl Limit {
+    fn from_env(group: &RouteGroup) -> Limit {
+        let env_var = format!("RATE_LIMIT_{}", group.name);
+        let (requests, seconds) = match get_optional_env_var(&env_var) {
+            Some(limit) => limit
+                .split_once('/')
+                .and_then(|(requests, seconds)| {
+                    Some((requests.parse::<u32>().ok()?, seconds.parse::<u64>().ok()?))
+                })
+                .filter(|(requests, seconds)| *requests > 0 && *seconds > 0)
+                .expect(&format!(
+                    "[ENV]: {} has to be <requests>/<seconds>, e.g. 300/60",
+                    env_var
+                )),
+            None => group.default_limit,
+        };
+
+        Limit {
+            requests,
+            per: Duration::from_secs(seconds),
+        }
+    }
+
+    /// Tokens added to a bucket per second
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.requests) / self.per.as_secs_f64()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Client {
+    User(i32),
+    Ip(IpAddr),
+    /// Requests without a known client IP, they share a single bucket
+    Unknown,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// State of a bucket after a request, sent in `RateLimit-*` headers
+struct Decision {
+    limit: Limit,
+    allowed: bool,
+    remaining: u32,
+    /// Seconds until the bucket is full
+    reset_secs: u64,
+    /// Seconds until the next token, for limited requests
+    retry_after_secs: u64,
+}
+
+struct Buckets {
+    buckets: HashMap<(usize, Client), Bucket>,
+    swept_at: Instant,
+}
+
+pub struct RateLimiter {
+    limits: Vec<Limit>,
+    /// Addresses of proxies which set the client IP header
+    trusted_proxies: Vec<IpAddr>,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    /// Reads limits of the route groups, panics on invalid configuration
+    pub fn new() -> RateLimiter {
+        RateLimiter {
+            limits: ROUTE_GROUPS.iter().map(Limit::from_env).collect(),
+            trusted_proxies: trusted_proxies_from_env(),
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                swept_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Address of the connection, or the forwarded address if the connection is from a proxy
+    fn client_ip(&self, request: &Request<'_>) -> Option<IpAddr> {
+        let remote_ip = request.remote().map(|remote| remote.ip());
+        match remote_ip {
+            Some(ip) if self.trusted_proxies.contains(&ip) => request.real_ip().or(remote_ip),
+            _ => remote_ip,
+        }
+    }
+
+    fn take_token(&self, group_index: usize, client: Client) -> Decision {
+        let limit = self.limits[group_index];
+        let refill_rate = limit.refill_rate();
+        let capacity = f64::from(limit.requests);
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if now.duration_since(buckets.swept_at) >= SWEEP_INTERVAL {
+            let limits = &self.limits;
+            buckets.buckets.retain(|(group_index, _), bucket| {
+                let limit = limits[*group_index];
+                let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+                bucket.tokens + elapsed * limit.refill_rate() < f64::from(limit.requests)
+            });
+            buckets.swept_at = now;
+        }
+
+        let bucket = buckets
+            .buckets
+            .entry((group_index, client))
+            .or_insert(Bucket {
+                tokens: capacity,
+                refilled_at: now,
+            });
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_rate).min(capacity);
+        bucket.refilled_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        Decision {
+            limit,
+            allowed,
+            remaining: bucket.tokens.floor() as u32,
+            reset_secs: ((capacity - bucket.tokens) / refill_rate).ceil() as u64,
+            retry_after_secs: ((1.0 - bucket.tokens) / refill_rate).ceil().max(1.0) as u64,
+        }
+    }
+}
+
+/// Comma separated IPs of `TRUSTED_PROXIES`, panics on invalid configuration
+fn trusted_proxies_from_env() -> Vec<IpAddr> {
+    match get_optional_env_var("TRUSTED_PROXIES") {
+        Some(proxies) => proxies
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .map(|proxy| {
+                proxy.parse::<IpAddr>().expect(&format!(
+                    "[ENV]: TRUSTED_PROXIES has to be a comma separated list of IPs, {} is not an IP",
+                    proxy
+                ))
+            })
+            .collect(),
+        None => vec![],
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for RateLimiter {
+    fn info(&self) -> Info {
+        Info {
+            name: "Rate limits",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let path = request.uri().path().to_string();
+        let group_index = match ROUTE_GROUPS
+            .iter()
+            .position(|group| path.starts_with(group.path_prefix))
+        {
+            Some(group_index) => group_index,
+            None => return,
+        };
+
+        let client = match request.guard::<AuthenticatedUser>().await.succeeded() {
+            Some(user) => Client::User(user.user_id),
+            None => match self.client_ip(request) {
+                Some(ip) => Client::Ip(ip),
+                None => Client::Unknown,
+            },
+        };
+
+        let decision = self.take_token(group_index, client);
+        if !decision.allowed {
+            request.set_method(Method::Get);
+            request.set_uri(Origin::parse(LIMITED_PATH).unwrap());
+        }
+        request.local_cache(|| Some(decision));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let decision = match request.local_cache(|| None::<Decision>) {
+            Some(decision) => decision,
+            None => return,
+        };
+
+        if !decision.allowed {
+            let body = serde_json!(ErrorResponseBody::new(format!(
+                "Too many requests, retry in {} seconds",
+                decision.retry_after_secs
+            )))
+            .to_string();
+            response.set_status(Status::TooManyRequests);
+            response.set_header(ContentType::JSON);
+            response.set_raw_header("Retry-After", decision.retry_after_secs.to_string());
+            response.set_sized_body(body.len(), Cursor::new(body));
+        }
+
+        response.set_raw_header("RateLimit-Limit", decision.limit.requests.to_string());
+        response.set_raw_header("RateLimit-Remaining", decision.remaining.to_string());
+        response.set_raw_header("RateLimit-Reset", decision.reset_secs.to_string());
+        response.set_raw_header(
+            "RateLimit-Policy",
+            format!(
+                "{};w={}",
+                decision.limit.requests,
+                decision.limit.per.as_secs()
+            ),
+        );
+    }
+}
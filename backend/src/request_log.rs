@@ -0,0 +1,138 @@
+//! Structured logs of requests.
+//!
+//! Logs are written as JSON lines, levels are filtered with `RUST_LOG` (`info` by default).
+//! Every request has an id which is sent back in `X-Request-Id`, ids of incoming requests
+//! are kept so that the logs of a proxy can be matched. Logs of a request are written within
+//! its span, errors which caused an error response are logged with `ErrorResponse::with_source`.
+
+use rand::RngCore;
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    request::{FromRequest, Outcome},
+    Data, Request, Response,
+};
+use std::time::Instant;
+use tracing::Span;
+use tracing_subscriber::EnvFilter;
+
+use backend::services::metrics::observe_request;
+
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Sets up the JSON subscriber, records of the `log` crate (Rocket, SeaORM) are logged by it too
+pub fn init_subscriber() {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_env_filter(env_filter)
+        .init();
+}
+
+/// Id of the request, the same for all its logs and its response
+#[derive(Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Id of the request which is assigned on its first use
+    pub fn of<'r>(request: &'r Request<'_>) -> &'r RequestId {
+        request.local_cache(|| {
+            let incoming_id = request
+                .headers()
+                .get_one(REQUEST_ID_HEADER)
+                .filter(|id| is_valid_request_id(id));
+            match incoming_id {
+                Some(id) => RequestId(id.to_string()),
+                None => RequestId(generate_request_id()),
+            }
+        })
+    }
+
+    /// Span of the request, logs within it have the request id. It is enabled with any
+    /// filter, so that errors always have the id
+    pub fn span(&self) -> Span {
+        tracing::error_span!("request", request_id = %self.0)
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RequestId::of(request).clone())
+    }
+}
+
+/// Ids are sent back in a header and written to logs, so only printable ASCII is kept
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+/// 128 random bits, hex encoded
+fn generate_request_id() -> String {
+    let mut id = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut id);
+    hex::encode(id)
+}
+
+/// Time the request was received at
+struct ReceivedAt(Instant);
+
+/// Assigns ids to requests, logs every handled request and records it in the metrics
+pub struct RequestLogger;
+
+#[rocket::async_trait]
+impl Fairing for RequestLogger {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request logs",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| ReceivedAt(Instant::now()));
+        RequestId::of(request);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let request_id = RequestId::of(request);
+        let duration = request
+            .local_cache(|| ReceivedAt(Instant::now()))
+            .0
+            .elapsed();
+        let route = request.route().and_then(|route| route.name.as_deref());
+        let route_uri = match request.route() {
+            Some(route) => route.uri.as_str(),
+            None => "unmatched",
+        };
+        observe_request(
+            request.method().as_str(),
+            route_uri,
+            response.status().code,
+            duration,
+        );
+
+        let _span = request_id.span().entered();
+        tracing::info!(
+            method = %request.method(),
+            path = %request.uri().path(),
+            route,
+            status = response.status().code,
+            duration_ms = duration.as_secs_f64() * 1000.0,
+            "Request handled"
+        );
+        response.set_raw_header(REQUEST_ID_HEADER, request_id.0.clone());
+    }
+}
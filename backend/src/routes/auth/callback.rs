@@ -3,7 +3,7 @@ use rocket::{response::Redirect, serde::Deserialize};
 use sea_orm::{
     ActiveModelTrait, ActiveValue, ColumnTrait, DbErr, EntityTrait, QueryFilter, TransactionTrait,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
 use super::lib::{get_fail_redirect, AuthRoute, AuthSuccessRedirect, FailReason, ProviderData};
 use backend::{
@@ -13,17 +13,49 @@ use backend::{
         social_profile, user,
     },
     establish_db_connection, get_env_var,
+    services::metrics::observe_oauth_callback,
 };
 
-use crate::routes::lib::create_signature;
+use crate::{request_log::RequestId, routes::lib::create_signature};
 
+/// Callback of the provider, signs the user in by setting the signed `id` cookie
+#[utoipa::path(
+    context_path = "/api/v1/auth/callback",
+    tag = "auth",
+    responses(
+        (status = 308, description = "Redirect to the client, with the `id` cookie set or with `error_msg` on failure")
+    ),
+    security(())
+)]
 #[get("/<provider_type>?<code>")]
 pub async fn success_handler(
     provider_type: &str,
     code: &str,
+    request_id: RequestId,
 ) -> Result<AuthSuccessRedirect, Redirect> {
+    let sign_in_res = sign_in(provider_type, code, &request_id).await;
+
+    // Provider is only a label for known providers, so that requests can not add series
+    let provider_label = match provider_type {
+        "discord" | "google" => provider_type,
+        _ => "unknown",
+    };
+    match &sign_in_res {
+        Ok(_) => observe_oauth_callback(provider_label, "success"),
+        Err(fail_reason) => observe_oauth_callback(provider_label, fail_reason.label()),
+    }
+
+    sign_in_res.map_err(|fail_reason| get_fail_redirect(&fail_reason))
+}
+
+/// Exchanges the code for the profile of the user, creates the user on the first sign in
+async fn sign_in(
+    provider_type: &str,
+    code: &str,
+    request_id: &RequestId,
+) -> Result<AuthSuccessRedirect, FailReason> {
     if provider_type != "discord" && provider_type != "google" {
-        return Err(get_fail_redirect(&FailReason::UnknownProvider));
+        return Err(FailReason::UnknownProvider);
     }
 
     let provider_data = ProviderData::new(provider_type);
@@ -41,7 +73,7 @@ pub async fn success_handler(
         .form(&token_form_body)
         .send()
         .await;
-    let token_body = handle_res_body::<TokenResponse>(token_res).await;
+    let token_body = handle_res_body::<TokenResponse>(token_res, request_id).await;
 
     if let Err(err) = token_body {
         return Err(err);
@@ -57,7 +89,7 @@ pub async fn success_handler(
         .await;
     let profile_body = match provider_data.provider {
         SocialProviderType::Discord => {
-            let body_res = handle_res_body::<DiscordProfileResponse>(profile_res).await;
+            let body_res = handle_res_body::<DiscordProfileResponse>(profile_res, request_id).await;
             if let Err(err) = body_res {
                 return Err(err);
             }
@@ -68,7 +100,7 @@ pub async fn success_handler(
             }
         }
         SocialProviderType::Google => {
-            let body_res = handle_res_body::<GoogleProfileResponse>(profile_res).await;
+            let body_res = handle_res_body::<GoogleProfileResponse>(profile_res, request_id).await;
             if let Err(err) = body_res {
                 return Err(err);
             }
@@ -81,8 +113,8 @@ pub async fn success_handler(
     };
 
     let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(get_fail_redirect(&FailReason::Internal));
+    if let Err(err) = &db_res {
+        return Err(internal_failure(request_id, err));
     }
     let db = db_res.unwrap();
 
@@ -90,8 +122,8 @@ pub async fn success_handler(
         .filter(social_profile::Column::ProviderId.eq(profile_body.provider_id.clone()))
         .one(&db)
         .await;
-    if let Err(_) = existing_social_profile_res {
-        return Err(get_fail_redirect(&FailReason::Internal));
+    if let Err(err) = &existing_social_profile_res {
+        return Err(internal_failure(request_id, err));
     }
 
     match existing_social_profile_res.unwrap() {
@@ -100,7 +132,10 @@ pub async fn success_handler(
             match existing_user_res {
                 Ok(existing_user) => {
                     if existing_user == None {
-                        return Err(get_fail_redirect(&FailReason::Internal));
+                        return Err(internal_failure(
+                            request_id,
+                            "User of the social profile does not exist",
+                        ));
                     }
 
                     let existing_user_id = existing_user.unwrap().id.to_string();
@@ -118,7 +153,7 @@ pub async fn success_handler(
                         }
                     )
                 }
-                Err(_) => Err(get_fail_redirect(&FailReason::Internal)),
+                Err(err) => Err(internal_failure(request_id, err)),
             }
         }
         None => {
@@ -152,8 +187,8 @@ pub async fn success_handler(
                 })
                 .await;
 
-            if let Err(_) = user_id_res {
-                return Err(get_fail_redirect(&FailReason::Internal));
+            if let Err(err) = &user_id_res {
+                return Err(internal_failure(request_id, err));
             }
 
             let user_id = user_id_res.unwrap();
@@ -202,7 +237,10 @@ struct UserSocialProfileData {
     provider_username: String,
 }
 
-pub async fn handle_res_body<T>(res: Result<ReqResponse, Error>) -> Result<T, Redirect>
+pub async fn handle_res_body<T>(
+    res: Result<ReqResponse, Error>,
+    request_id: &RequestId,
+) -> Result<T, FailReason>
 where
     T: for<'a> Deserialize<'a>,
 {
@@ -211,9 +249,16 @@ where
             let parsed = r.json::<T>().await;
             match parsed {
                 Ok(p) => Ok(p),
-                Err(_) => Err(get_fail_redirect(&FailReason::Internal)),
+                Err(err) => Err(internal_failure(request_id, err)),
             }
         }
-        Err(_) => Err(get_fail_redirect(&FailReason::Internal)),
+        Err(err) => Err(internal_failure(request_id, err)),
     }
 }
+
+/// Logs the error which failed the sign in, the client only gets `FailReason::Internal`
+fn internal_failure<E: Display>(request_id: &RequestId, err: E) -> FailReason {
+    let _span = request_id.span().entered();
+    tracing::error!(error = %err, "Sign in failed");
+    FailReason::Internal
+}
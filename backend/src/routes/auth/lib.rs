@@ -28,6 +28,14 @@ impl FailReason {
             FailReason::UnknownProvider => "Unknown provider",
         }
     }
+
+    /// Value of the `result` label of the callback metrics
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailReason::Internal => "internal",
+            FailReason::UnknownProvider => "unknown_provider",
+        }
+    }
 }
 
 pub struct AuthSuccessRedirect {
@@ -2,6 +2,16 @@ use rocket::response::Redirect;
 
 use super::lib::{get_fail_redirect, FailReason, ProviderData};
 
+/// Redirects to the sign in page of `discord` or `google`
+#[utoipa::path(
+    context_path = "/api/v1/auth/signin",
+    tag = "auth",
+    responses(
+        (status = 307, description = "Redirect to the provider"),
+        (status = 308, description = "Redirect to the client with `error_msg` for unknown providers")
+    ),
+    security(())
+)]
 #[get("/<provider_type>")]
 pub fn handler(provider_type: &str) -> Redirect {
     if provider_type != "discord" && provider_type != "google" {
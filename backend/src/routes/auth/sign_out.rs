@@ -1,5 +1,14 @@
 use super::lib::{AuthRoute, AuthSuccessRedirect};
 
+/// Removes the `id` cookie
+#[utoipa::path(
+    context_path = "/api/v1/auth/signout",
+    tag = "auth",
+    responses(
+        (status = 308, description = "Redirect to the client with the `id` cookie removed")
+    ),
+    security(())
+)]
 #[get("/")]
 pub fn handler() -> AuthSuccessRedirect {
     AuthSuccessRedirect {
@@ -0,0 +1,92 @@
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::TransactionTrait;
+
+use backend::establish_db_connection;
+
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, SuccessResponse},
+};
+
+use super::{
+    lib::{BatchError, BatchRequest, BatchResult, MAX_BATCH_OPERATIONS},
+    operations::BatchExecutor,
+};
+
+/// Executes operations in their order in a single transaction, either all of them
+/// are saved or none. Events are published only when the whole batch is saved.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "batch",
+    request_body = BatchRequest,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Results of the operations in their order", body = BatchResultsResponse),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = BatchErrorBody),
+        (status = "4XX", description = "Batch was rolled back, `failed_index` is the position of the failed operation", body = BatchErrorBody),
+        (status = 500, description = "Internal server error", body = BatchErrorBody)
+    )
+)]
+#[post("/batch", data = "<data>")]
+pub async fn handler(
+    data: Json<BatchRequest>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey<'_>,
+    event_bus: &State<EventBus>,
+) -> Result<Idempotent<Json<SuccessResponse<Vec<BatchResult>>>>, BatchError> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    let operations = data.into_inner().operations;
+    if operations.is_empty() || operations.len() > MAX_BATCH_OPERATIONS {
+        return Err(ErrorResponse::new(
+            Some(format!(
+                "Batch must have between 1 and {} operations",
+                MAX_BATCH_OPERATIONS
+            )),
+            Status::BadRequest,
+        )
+        .into());
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err).into());
+    }
+    let db = db_res.unwrap();
+
+    // Failed operations return responses instead of `DbErr`, so the transaction
+    // is committed or rolled back here instead of using `TransactionTrait::transaction`
+    let tx = match db.begin().await {
+        Ok(tx) => tx,
+        Err(err) => return Err(server_err_response.with_source(err).into()),
+    };
+    let execute_res = async {
+        // Events are published in the transaction, so they are sent only if the whole batch commits
+        let mut executor = BatchExecutor::new(&tx, event_bus, user.user_id);
+        let mut results: Vec<BatchResult> = vec![];
+        for (index, operation) in operations.into_iter().enumerate() {
+            match executor.execute(operation).await {
+                Ok(result) => results.push(result),
+                Err(err) => return Err(BatchError::new(index, err)),
+            }
+        }
+        Ok(results)
+    }
+    .await;
+
+    let results = match execute_res {
+        Ok(results) => results,
+        Err(err) => {
+            let _ = tx.rollback().await;
+            return Err(err);
+        }
+    };
+    if let Err(err) = tx.commit().await {
+        return Err(server_err_response.with_source(err).into());
+    }
+
+    Ok(Idempotent::Created(Json(SuccessResponse::new(results))))
+}
@@ -0,0 +1,286 @@
+use rocket::{
+    http::Status,
+    response,
+    serde::{Deserialize, Serialize},
+    Request, Response,
+};
+use serde_json::{json as serde_json, Value};
+use std::{collections::HashMap, io::Cursor};
+use utoipa::ToSchema;
+
+use crate::request_log::RequestId;
+use crate::routes::{
+    label::update::LabelDataToUpdate, lib::ErrorResponse,
+    task_group::update::ModifiedTaskGroupData, workspace::insert::NewWorkspace,
+    workspace::update::ModifiedWorkspaceData,
+};
+
+/// Operations of a single batch, offline clients send their queue in chunks of this size
+pub const MAX_BATCH_OPERATIONS: usize = 100;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchRequest {
+    pub operations: Vec<BatchOperation>,
+}
+
+/// Operation of a batch, same as the request to the route of the entity.
+///
+/// Rows created by the batch get `temp_id` given by the client, later operations
+/// can use it in place of the id of the row. Operations with `version` fail when the row
+/// is at another version.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde", tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    CreateWorkspace {
+        temp_id: Option<String>,
+        data: NewWorkspace,
+    },
+    UpdateWorkspace {
+        id: BatchId,
+        data: ModifiedWorkspaceData,
+    },
+    DeleteWorkspace {
+        id: BatchId,
+        version: Option<i32>,
+    },
+    CreateTaskGroup {
+        temp_id: Option<String>,
+        data: BatchNewTaskGroup,
+    },
+    UpdateTaskGroup {
+        id: BatchId,
+        data: ModifiedTaskGroupData,
+    },
+    DeleteTaskGroup {
+        id: BatchId,
+        version: Option<i32>,
+    },
+    CreateTask {
+        temp_id: Option<String>,
+        data: BatchNewTask,
+    },
+    UpdateTask {
+        id: BatchId,
+        data: BatchTaskDataToUpdate,
+    },
+    DeleteTask {
+        id: BatchId,
+        version: Option<i32>,
+    },
+    CreateLabel {
+        temp_id: Option<String>,
+        data: BatchNewLabel,
+    },
+    UpdateLabel {
+        id: BatchId,
+        data: LabelDataToUpdate,
+    },
+    DeleteLabel {
+        id: BatchId,
+        version: Option<i32>,
+    },
+}
+
+/// Either id of an existing row or temporary id of a row created earlier in the batch
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde", untagged)]
+pub enum BatchId {
+    Id(i32),
+    TempId(String),
+}
+
+/// `NewTaskGroup` which can refer to a workspace of the batch
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchNewTaskGroup {
+    pub workspace_id: BatchId,
+    pub title: String,
+}
+
+/// `NewTask` which can refer to a task group and labels of the batch
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchNewTask {
+    pub task_group_id: BatchId,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Option<Vec<BatchId>>,
+    /// Date (2026-11-01) or RFC 3339 date and time
+    pub due_at: Option<String>,
+}
+
+/// `TaskDataToUpdate` which can refer to a task group and labels of the batch
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchTaskDataToUpdate {
+    pub task_group_id: Option<BatchId>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub labels_ids: Option<Vec<BatchId>>,
+    /// Date (2026-11-01) or RFC 3339 date and time, empty string removes the due date
+    pub due_at: Option<String>,
+    pub version: Option<i32>,
+}
+
+/// `NewLabel` which can refer to a workspace of the batch
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchNewLabel {
+    pub workspace_id: BatchId,
+    pub color: String,
+    pub description: Option<String>,
+}
+
+/// Result of an operation, results are in the order of the operations
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchResult {
+    /// Id of the created, updated or deleted row
+    pub id: i32,
+    /// Temporary id of the created row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_id: Option<String>,
+    /// Key of the created or updated task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Version of the created or updated row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BatchEntity {
+    Workspace,
+    TaskGroup,
+    Task,
+    Label,
+}
+
+impl BatchEntity {
+    fn name(&self) -> &'static str {
+        match self {
+            BatchEntity::Workspace => "workspace",
+            BatchEntity::TaskGroup => "task group",
+            BatchEntity::Task => "task",
+            BatchEntity::Label => "label",
+        }
+    }
+}
+
+/// Ids of the rows created by the batch under temporary ids
+#[derive(Default)]
+pub struct TempIds(HashMap<String, (BatchEntity, i32)>);
+
+impl TempIds {
+    pub fn insert(
+        &mut self,
+        temp_id: Option<String>,
+        entity: BatchEntity,
+        id: i32,
+    ) -> Result<(), ErrorResponse> {
+        let temp_id = match temp_id {
+            Some(temp_id) => temp_id,
+            None => return Ok(()),
+        };
+        if self.0.contains_key(&temp_id) {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Temporary id {} is already used by another operation",
+                    temp_id
+                )),
+                Status::UnprocessableEntity,
+            ));
+        }
+        self.0.insert(temp_id, (entity, id));
+        Ok(())
+    }
+
+    /// Id of the row, temporary id has to be created earlier in the batch for the same entity
+    pub fn resolve(&self, batch_id: &BatchId, entity: BatchEntity) -> Result<i32, ErrorResponse> {
+        let temp_id = match batch_id {
+            BatchId::Id(id) => return Ok(*id),
+            BatchId::TempId(temp_id) => temp_id,
+        };
+        match self.0.get(temp_id) {
+            Some((temp_entity, id)) if *temp_entity == entity => Ok(*id),
+            Some((temp_entity, _)) => Err(ErrorResponse::new(
+                Some(format!(
+                    "Temporary id {} is a {}, not a {}",
+                    temp_id,
+                    temp_entity.name(),
+                    entity.name()
+                )),
+                Status::UnprocessableEntity,
+            )),
+            None => Err(ErrorResponse::new(
+                Some(format!(
+                    "Temporary id {} is not created by an earlier operation",
+                    temp_id
+                )),
+                Status::UnprocessableEntity,
+            )),
+        }
+    }
+}
+
+/// Batch is rolled back as a whole, `failed_index` is the position of the operation
+/// which failed and is missing when the batch could not be executed at all
+pub struct BatchError {
+    failed_index: Option<usize>,
+    error: ErrorResponse,
+}
+
+impl BatchError {
+    pub fn new(failed_index: usize, error: ErrorResponse) -> BatchError {
+        BatchError {
+            failed_index: Some(failed_index),
+            error,
+        }
+    }
+}
+
+impl From<ErrorResponse> for BatchError {
+    fn from(error: ErrorResponse) -> BatchError {
+        BatchError {
+            failed_index: None,
+            error,
+        }
+    }
+}
+
+impl<'r> response::Responder<'r, 'static> for BatchError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let request_id = RequestId::of(request);
+        self.error.log(request_id);
+
+        let res_body = serde_json!(BatchErrorBody {
+            success: false,
+            error: self.error.message().into(),
+            failed_index: self.failed_index,
+            current: self.error.current().cloned(),
+            request_id: request_id.0.clone(),
+        })
+        .to_string();
+        let build = &mut Response::build();
+        build
+            .status(self.error.status())
+            .sized_body(res_body.len(), Cursor::new(res_body))
+            .ok()
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BatchErrorBody {
+    success: bool,
+    error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failed_index: Option<usize>,
+    /// Current row when the operation changed another version of it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    current: Option<Value>,
+    /// Same as the `X-Request-Id` header
+    request_id: String,
+}
@@ -0,0 +1,611 @@
+use rocket::http::Status;
+use sea_orm::{ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter};
+
+use backend::{
+    entities::{
+        label,
+        prelude::{Label, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+    services::task_keys::task_key,
+};
+
+use crate::{
+    events::bus::EventBus,
+    routes::{
+        label::{
+            lib::{delete_label, insert_label, update_label},
+            update::{LabelDataToUpdate, UpdatedLabel},
+        },
+        lib::{ErrorResponse, ExpectedVersion, IfMatch},
+        task::{
+            lib::{
+                delete_task, find_missing_labels_ids, insert_task, update_task, TaskChanges,
+                TaskToInsert,
+            },
+            update::UpdatedTask,
+        },
+        task_group::{
+            lib::{delete_task_group, insert_task_group, update_task_group},
+            update::{ModifiedTaskGroupData, UpdatedTaskGroup},
+        },
+        workspace::{
+            insert::NewWorkspace,
+            lib::{delete_workspace, insert_workspace, update_workspace},
+            update::{ModifiedWorkspaceData, SavedModifiedWorkspace},
+        },
+    },
+};
+
+use super::lib::{
+    BatchEntity, BatchId, BatchNewLabel, BatchNewTask, BatchNewTaskGroup, BatchOperation,
+    BatchResult, BatchTaskDataToUpdate, TempIds,
+};
+
+/// Executes operations of a batch in its transaction.
+///
+/// Operations are written by the same functions as the routes of their entities,
+/// their events are published in the transaction and sent only if the batch commits.
+pub struct BatchExecutor<'a> {
+    tx: &'a DatabaseTransaction,
+    event_bus: &'a EventBus,
+    user_id: i32,
+    temp_ids: TempIds,
+}
+
+impl<'a> BatchExecutor<'a> {
+    pub fn new(
+        tx: &'a DatabaseTransaction,
+        event_bus: &'a EventBus,
+        user_id: i32,
+    ) -> BatchExecutor<'a> {
+        BatchExecutor {
+            tx,
+            event_bus,
+            user_id,
+            temp_ids: TempIds::default(),
+        }
+    }
+
+    pub async fn execute(
+        &mut self,
+        operation: BatchOperation,
+    ) -> Result<BatchResult, ErrorResponse> {
+        match operation {
+            BatchOperation::CreateWorkspace { temp_id, data } => {
+                self.create_workspace(temp_id, data).await
+            }
+            BatchOperation::UpdateWorkspace { id, data } => self.update_workspace(id, data).await,
+            BatchOperation::DeleteWorkspace { id, version } => {
+                self.delete_workspace(id, version).await
+            }
+            BatchOperation::CreateTaskGroup { temp_id, data } => {
+                self.create_task_group(temp_id, data).await
+            }
+            BatchOperation::UpdateTaskGroup { id, data } => self.update_task_group(id, data).await,
+            BatchOperation::DeleteTaskGroup { id, version } => {
+                self.delete_task_group(id, version).await
+            }
+            BatchOperation::CreateTask { temp_id, data } => self.create_task(temp_id, data).await,
+            BatchOperation::UpdateTask { id, data } => self.update_task(id, data).await,
+            BatchOperation::DeleteTask { id, version } => self.delete_task(id, version).await,
+            BatchOperation::CreateLabel { temp_id, data } => self.create_label(temp_id, data).await,
+            BatchOperation::UpdateLabel { id, data } => self.update_label(id, data).await,
+            BatchOperation::DeleteLabel { id, version } => self.delete_label(id, version).await,
+        }
+    }
+
+    async fn create_workspace(
+        &mut self,
+        temp_id: Option<String>,
+        data: NewWorkspace,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let inserted_workspace = insert_workspace(self.tx, self.user_id, &data).await?;
+
+        self.temp_ids.insert(
+            temp_id.clone(),
+            BatchEntity::Workspace,
+            inserted_workspace.id,
+        )?;
+        Ok(BatchResult {
+            id: inserted_workspace.id,
+            temp_id,
+            key: None,
+            version: Some(inserted_workspace.version),
+        })
+    }
+
+    async fn update_workspace(
+        &mut self,
+        id: BatchId,
+        data: ModifiedWorkspaceData,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let workspace_id = self.temp_ids.resolve(&id, BatchEntity::Workspace)?;
+        let workspace_model = self.find_workspace(workspace_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), data.version);
+        if !expected_version.matches(workspace_model.version) {
+            return Err(expected_version.conflict_response(
+                &SavedModifiedWorkspace::new(workspace_model.clone()),
+                workspace_model.version,
+            ));
+        }
+        let updated_workspace = update_workspace(
+            self.tx,
+            self.event_bus,
+            workspace_model,
+            &data,
+            &expected_version,
+        )
+        .await?
+        .ok_or_else(changed_error)?;
+
+        Ok(BatchResult {
+            id: workspace_id,
+            temp_id: None,
+            key: None,
+            version: Some(updated_workspace.version),
+        })
+    }
+
+    async fn delete_workspace(
+        &mut self,
+        id: BatchId,
+        version: Option<i32>,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let workspace_id = self.temp_ids.resolve(&id, BatchEntity::Workspace)?;
+        let workspace_model = self.find_workspace(workspace_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), version);
+        if !expected_version.matches(workspace_model.version) {
+            return Err(expected_version.conflict_response(
+                &SavedModifiedWorkspace::new(workspace_model.clone()),
+                workspace_model.version,
+            ));
+        }
+        delete_workspace(self.tx, self.event_bus, &workspace_model, &expected_version)
+            .await?
+            .ok_or_else(changed_error)?;
+
+        Ok(BatchResult {
+            id: workspace_id,
+            temp_id: None,
+            key: None,
+            version: None,
+        })
+    }
+
+    async fn create_task_group(
+        &mut self,
+        temp_id: Option<String>,
+        data: BatchNewTaskGroup,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let workspace_id = self
+            .temp_ids
+            .resolve(&data.workspace_id, BatchEntity::Workspace)?;
+        self.find_workspace(workspace_id).await?;
+        let inserted_task_group = insert_task_group(
+            self.tx,
+            self.event_bus,
+            self.user_id,
+            workspace_id,
+            data.title,
+        )
+        .await?;
+
+        self.temp_ids.insert(
+            temp_id.clone(),
+            BatchEntity::TaskGroup,
+            inserted_task_group.id,
+        )?;
+        Ok(BatchResult {
+            id: inserted_task_group.id,
+            temp_id,
+            key: None,
+            version: Some(inserted_task_group.version),
+        })
+    }
+
+    async fn update_task_group(
+        &mut self,
+        id: BatchId,
+        data: ModifiedTaskGroupData,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let task_group_id = self.temp_ids.resolve(&id, BatchEntity::TaskGroup)?;
+        let task_group_model = self.find_task_group(task_group_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), data.version);
+        if !expected_version.matches(task_group_model.version) {
+            return Err(expected_version.conflict_response(
+                &UpdatedTaskGroup::new(task_group_model.clone()),
+                task_group_model.version,
+            ));
+        }
+        let updated_task_group = update_task_group(
+            self.tx,
+            self.event_bus,
+            task_group_model,
+            data.title,
+            &expected_version,
+        )
+        .await?
+        .ok_or_else(changed_error)?;
+
+        Ok(BatchResult {
+            id: task_group_id,
+            temp_id: None,
+            key: None,
+            version: Some(updated_task_group.version),
+        })
+    }
+
+    async fn delete_task_group(
+        &mut self,
+        id: BatchId,
+        version: Option<i32>,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let task_group_id = self.temp_ids.resolve(&id, BatchEntity::TaskGroup)?;
+        let task_group_model = self.find_task_group(task_group_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), version);
+        if !expected_version.matches(task_group_model.version) {
+            return Err(expected_version.conflict_response(
+                &UpdatedTaskGroup::new(task_group_model.clone()),
+                task_group_model.version,
+            ));
+        }
+        delete_task_group(
+            self.tx,
+            self.event_bus,
+            &task_group_model,
+            &expected_version,
+        )
+        .await?
+        .ok_or_else(changed_error)?;
+
+        Ok(BatchResult {
+            id: task_group_id,
+            temp_id: None,
+            key: None,
+            version: None,
+        })
+    }
+
+    async fn create_task(
+        &mut self,
+        temp_id: Option<String>,
+        data: BatchNewTask,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let task_group_id = self
+            .temp_ids
+            .resolve(&data.task_group_id, BatchEntity::TaskGroup)?;
+        let workspace_id = self.find_task_group(task_group_id).await?.workspace_id;
+        let labels_ids = match &data.labels_ids {
+            Some(labels_ids) => self.resolve_labels_ids(workspace_id, labels_ids).await?,
+            None => vec![],
+        };
+
+        let workspace_model = self.find_workspace(workspace_id).await?;
+        let inserted_task = insert_task(
+            self.tx,
+            self.event_bus,
+            self.user_id,
+            &workspace_model,
+            TaskToInsert {
+                task_group_id,
+                title: data.title,
+                description: data.description,
+                labels_ids,
+                due_at: data.due_at,
+            },
+        )
+        .await?;
+
+        self.temp_ids
+            .insert(temp_id.clone(), BatchEntity::Task, inserted_task.id)?;
+        Ok(BatchResult {
+            id: inserted_task.id,
+            temp_id,
+            key: Some(task_key(&workspace_model.key_prefix, inserted_task.number)),
+            version: Some(inserted_task.version),
+        })
+    }
+
+    async fn update_task(
+        &mut self,
+        id: BatchId,
+        data: BatchTaskDataToUpdate,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let task_id = self.temp_ids.resolve(&id, BatchEntity::Task)?;
+        let (task_model, current_task_group) = self.find_task(task_id).await?;
+        let workspace_id = current_task_group.workspace_id;
+        let expected_version = ExpectedVersion::new(IfMatch(None), data.version);
+        if !expected_version.matches(task_model.version) {
+            return Err(self
+                .task_conflict(&expected_version, task_model, workspace_id)
+                .await);
+        }
+
+        let task_group_id = match &data.task_group_id {
+            Some(task_group_id) => Some(
+                self.temp_ids
+                    .resolve(task_group_id, BatchEntity::TaskGroup)?,
+            ),
+            None => None,
+        };
+        let labels_ids = match &data.labels_ids {
+            Some(labels_ids) => Some(self.resolve_labels_ids(workspace_id, labels_ids).await?),
+            None => None,
+        };
+        let workspace_model = self.find_workspace(workspace_id).await?;
+        let changes = TaskChanges {
+            task_group_id,
+            title: data.title,
+            description: data.description,
+            labels_ids,
+            due_at: data.due_at,
+        };
+        let updated_task = update_task(
+            self.tx,
+            self.event_bus,
+            task_model,
+            &workspace_model,
+            changes,
+            &expected_version,
+        )
+        .await?
+        .ok_or_else(changed_error)?;
+
+        Ok(BatchResult {
+            id: task_id,
+            temp_id: None,
+            key: Some(task_key(&workspace_model.key_prefix, updated_task.number)),
+            version: Some(updated_task.version),
+        })
+    }
+
+    async fn delete_task(
+        &mut self,
+        id: BatchId,
+        version: Option<i32>,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let task_id = self.temp_ids.resolve(&id, BatchEntity::Task)?;
+        let (task_model, task_group_model) = self.find_task(task_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), version);
+        if !expected_version.matches(task_model.version) {
+            return Err(self
+                .task_conflict(&expected_version, task_model, task_group_model.workspace_id)
+                .await);
+        }
+        let is_deleted = delete_task(
+            self.tx,
+            self.event_bus,
+            &task_model,
+            task_group_model.workspace_id,
+            &expected_version,
+        )
+        .await?;
+        if !is_deleted {
+            return Err(changed_error());
+        }
+
+        Ok(BatchResult {
+            id: task_id,
+            temp_id: None,
+            key: None,
+            version: None,
+        })
+    }
+
+    async fn create_label(
+        &mut self,
+        temp_id: Option<String>,
+        data: BatchNewLabel,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let workspace_id = self
+            .temp_ids
+            .resolve(&data.workspace_id, BatchEntity::Workspace)?;
+        self.find_workspace(workspace_id).await?;
+        let inserted_label = insert_label(
+            self.tx,
+            self.event_bus,
+            self.user_id,
+            workspace_id,
+            &data.color,
+            data.description,
+        )
+        .await?;
+
+        self.temp_ids
+            .insert(temp_id.clone(), BatchEntity::Label, inserted_label.id)?;
+        Ok(BatchResult {
+            id: inserted_label.id,
+            temp_id,
+            key: None,
+            version: Some(inserted_label.version),
+        })
+    }
+
+    async fn update_label(
+        &mut self,
+        id: BatchId,
+        data: LabelDataToUpdate,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let label_id = self.temp_ids.resolve(&id, BatchEntity::Label)?;
+        let label_model = self.find_label(label_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), data.version);
+        if !expected_version.matches(label_model.version) {
+            return Err(expected_version
+                .conflict_response(&UpdatedLabel::new(label_model.clone()), label_model.version));
+        }
+        let updated_label = update_label(
+            self.tx,
+            self.event_bus,
+            label_model,
+            &data,
+            &expected_version,
+        )
+        .await?
+        .ok_or_else(changed_error)?;
+
+        Ok(BatchResult {
+            id: label_id,
+            temp_id: None,
+            key: None,
+            version: Some(updated_label.version),
+        })
+    }
+
+    /// Moves label to trash, tasks keep its id the same way as on `DELETE /label/<id>`
+    async fn delete_label(
+        &mut self,
+        id: BatchId,
+        version: Option<i32>,
+    ) -> Result<BatchResult, ErrorResponse> {
+        let label_id = self.temp_ids.resolve(&id, BatchEntity::Label)?;
+        let label_model = self.find_label(label_id).await?;
+        let expected_version = ExpectedVersion::new(IfMatch(None), version);
+        if !expected_version.matches(label_model.version) {
+            return Err(expected_version
+                .conflict_response(&UpdatedLabel::new(label_model.clone()), label_model.version));
+        }
+        let is_trashed =
+            delete_label(self.tx, self.event_bus, &label_model, &expected_version).await?;
+        if !is_trashed {
+            return Err(changed_error());
+        }
+
+        Ok(BatchResult {
+            id: label_id,
+            temp_id: None,
+            key: None,
+            version: None,
+        })
+    }
+
+    async fn find_workspace(&self, workspace_id: i32) -> Result<workspace::Model, ErrorResponse> {
+        let found_workspace = Workspace::find_by_id(workspace_id)
+            .filter(workspace::Column::UserId.eq(self.user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .one(self.tx)
+            .await
+            .map_err(ErrorResponse::server_error)?;
+        found_workspace.ok_or_else(|| {
+            ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            )
+        })
+    }
+
+    async fn find_task_group(
+        &self,
+        task_group_id: i32,
+    ) -> Result<task_group::Model, ErrorResponse> {
+        let found_task_group = TaskGroup::find_by_id(task_group_id)
+            .filter(task_group::Column::UserId.eq(self.user_id))
+            .filter(task_group::Column::DeletedAt.is_null())
+            .one(self.tx)
+            .await
+            .map_err(ErrorResponse::server_error)?;
+        found_task_group.ok_or_else(|| {
+            ErrorResponse::new(
+                Some(format!(
+                    "Task group with id {} does not exist",
+                    task_group_id
+                )),
+                Status::NotFound,
+            )
+        })
+    }
+
+    /// Tasks of trashed task groups are not accessible
+    async fn find_task(
+        &self,
+        task_id: i32,
+    ) -> Result<(task::Model, task_group::Model), ErrorResponse> {
+        let found_task = Task::find_by_id(task_id)
+            .filter(task::Column::UserId.eq(self.user_id))
+            .find_also_related(TaskGroup)
+            .filter(task_group::Column::DeletedAt.is_null())
+            .one(self.tx)
+            .await
+            .map_err(ErrorResponse::server_error)?;
+        match found_task {
+            Some((task_model, Some(task_group_model))) => Ok((task_model, task_group_model)),
+            _ => Err(ErrorResponse::new(
+                Some(format!("Task with id {} does not exist", task_id)),
+                Status::NotFound,
+            )),
+        }
+    }
+
+    async fn find_label(&self, label_id: i32) -> Result<label::Model, ErrorResponse> {
+        let found_label = Label::find_by_id(label_id)
+            .filter(label::Column::UserId.eq(self.user_id))
+            .filter(label::Column::DeletedAt.is_null())
+            .one(self.tx)
+            .await
+            .map_err(ErrorResponse::server_error)?;
+        found_label.ok_or_else(|| {
+            ErrorResponse::new(
+                Some(format!("Label with id {} does not exist", label_id)),
+                Status::NotFound,
+            )
+        })
+    }
+
+    /// Conflict response with the current task, its key needs the prefix of the workspace
+    async fn task_conflict(
+        &self,
+        expected_version: &ExpectedVersion,
+        task_model: task::Model,
+        workspace_id: i32,
+    ) -> ErrorResponse {
+        match self.find_workspace(workspace_id).await {
+            Ok(workspace_model) => {
+                let version = task_model.version;
+                expected_version.conflict_response(
+                    &UpdatedTask::new(task_model, &workspace_model.key_prefix),
+                    version,
+                )
+            }
+            Err(err) => err,
+        }
+    }
+
+    /// Labels of the task have to be labels of its workspace which are not in trash
+    async fn resolve_labels_ids(
+        &self,
+        workspace_id: i32,
+        labels_ids: &[BatchId],
+    ) -> Result<Vec<i32>, ErrorResponse> {
+        let labels_ids = labels_ids
+            .iter()
+            .map(|label_id| self.temp_ids.resolve(label_id, BatchEntity::Label))
+            .collect::<Result<Vec<i32>, ErrorResponse>>()?;
+        let missing_labels_ids =
+            find_missing_labels_ids(self.tx, self.user_id, workspace_id, &labels_ids)
+                .await
+                .map_err(ErrorResponse::server_error)?;
+        if !missing_labels_ids.is_empty() {
+            let missing_labels_ids: Vec<String> = missing_labels_ids
+                .iter()
+                .map(|label_id| label_id.to_string())
+                .collect();
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Labels with ids {} do not exist",
+                    missing_labels_ids.join(", ")
+                )),
+                Status::Conflict,
+            ));
+        }
+        Ok(labels_ids)
+    }
+}
+
+/// Writes with `version` affect no rows when a concurrent request has changed the row
+/// since the operation selected it
+fn changed_error() -> ErrorResponse {
+    ErrorResponse::new(
+        Some("Row was changed by another request, batch can be retried".into()),
+        Status::Conflict,
+    )
+}
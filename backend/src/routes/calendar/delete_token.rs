@@ -0,0 +1,42 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use backend::{
+    entities::{calendar_feed, prelude::CalendarFeed},
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+/// Disables the calendar feed of the user, `POST /calendar/token` enables it with a new token
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "calendar",
+    responses(
+        (status = 200, description = "Disabled feed", body = EmptyResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/calendar/token")]
+pub async fn handler(user: AuthenticatedUser) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let delete_res = CalendarFeed::delete_many()
+        .filter(calendar_feed::Column::UserId.eq(user.user_id))
+        .exec(&db)
+        .await;
+
+    match delete_res {
+        Ok(delete_result) if delete_result.rows_affected > 0 => Ok(Json(SuccessResponse::new(()))),
+        Ok(_) => Err(ErrorResponse::new(
+            Some("Calendar feed is not enabled".into()),
+            Status::NotFound,
+        )),
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
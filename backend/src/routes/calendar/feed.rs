@@ -0,0 +1,247 @@
+use hmac_sha256::Hash;
+use rocket::http::{Header, Status};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue, ColumnTrait, DbConn, DbErr,
+    EntityTrait, QueryFilter,
+};
+
+use backend::{
+    current_timestamp,
+    entities::{
+        calendar_feed, git_integration,
+        prelude::{CalendarFeed, GitIntegration, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+    establish_db_connection,
+    services::task_keys::task_key,
+};
+
+use crate::routes::lib::ErrorResponse;
+
+use super::lib::{escape_text, format_date_time, push_line, CacheValidators, FEED_EXTENSION};
+
+/// Calendar with a VTODO for each task with a due date, in all workspaces of the user
+/// owning the token. Tasks in the done task group of a git integration are completed.
+///
+/// Feed is not authenticated by cookie so that calendar apps can subscribe to it,
+/// the token in the URL is the secret.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "calendar",
+    params(
+        ("file" = String, Path, description = "Token of the feed followed by `.ics`"),
+        ("If-None-Match" = Option<String>, Header),
+        ("If-Modified-Since" = Option<String>, Header)
+    ),
+    responses(
+        (status = 200, description = "iCalendar feed", body = String, content_type = "text/calendar"),
+        (status = 304, description = "Feed has not changed"),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    ),
+    security(())
+)]
+#[get("/calendar/<file>")]
+pub async fn handler(
+    file: String,
+    cache_validators: CacheValidators,
+) -> Result<CalendarResponse, ErrorResponse> {
+    let not_found_err_response = ErrorResponse::new(None, Status::NotFound);
+    let token = match file.strip_suffix(FEED_EXTENSION) {
+        Some(token) => token,
+        None => return Err(not_found_err_response),
+    };
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_feed_res = CalendarFeed::find()
+        .filter(calendar_feed::Column::Token.eq(token))
+        .one(&db)
+        .await;
+    let feed = match select_feed_res {
+        Ok(Some(feed_model)) => feed_model,
+        Ok(None) => return Err(not_found_err_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let todos = match find_todos(&db, feed.user_id).await {
+        Ok(todos) => todos,
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    // Last-Modified is the time the content was first served with a different ETag,
+    // so that it also changes when tasks are deleted or restored from trash.
+    // ETag is computed without DTSTAMP, which is the Last-Modified time.
+    let content = render_todos(&todos, None);
+    let etag = format!("\"{}\"", hex::encode(Hash::hash(content.as_bytes())));
+    let last_modified = match feed.etag.as_deref() == Some(etag.as_str()) {
+        true => feed.last_modified,
+        false => {
+            let last_modified = current_timestamp();
+            let mut feed_to_update: calendar_feed::ActiveModel = feed.into();
+            feed_to_update.etag = ActiveValue::Set(Some(etag.clone()));
+            feed_to_update.last_modified = ActiveValue::Set(last_modified);
+            if let Err(err) = feed_to_update.update(&db).await {
+                return Err(server_err_response.with_source(err));
+            }
+            last_modified
+        }
+    };
+
+    let etag_header = Header::new("ETag", etag.clone());
+    let last_modified_header = Header::new(
+        "Last-Modified",
+        last_modified
+            .naive_utc()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string(),
+    );
+    if cache_validators.is_not_modified(&etag, &last_modified) {
+        return Ok(CalendarResponse::NotModified(
+            (),
+            etag_header,
+            last_modified_header,
+        ));
+    }
+
+    let mut calendar = String::new();
+    push_line(&mut calendar, "BEGIN:VCALENDAR");
+    push_line(&mut calendar, "VERSION:2.0");
+    push_line(&mut calendar, "PRODID:-//Task-app//Tasks//EN");
+    push_line(&mut calendar, "X-WR-CALNAME:Tasks");
+    // Time stamps of the entries do not change between requests with the same content
+    calendar.push_str(&render_todos(&todos, Some(&last_modified)));
+    push_line(&mut calendar, "END:VCALENDAR");
+
+    Ok(CalendarResponse::Calendar(
+        calendar,
+        etag_header,
+        last_modified_header,
+    ))
+}
+
+#[derive(Responder)]
+pub enum CalendarResponse {
+    #[response(status = 200, content_type = "text/calendar")]
+    Calendar(String, Header<'static>, Header<'static>),
+    #[response(status = 304)]
+    NotModified((), Header<'static>, Header<'static>),
+}
+
+/// Task with a due date as it is shown in the feed
+struct Todo {
+    task_id: i32,
+    summary: String,
+    description: String,
+    categories: String,
+    due_at: DateTimeWithTimeZone,
+    is_completed: bool,
+}
+
+/// Tasks of the user with due dates, in workspaces and task groups which are not in trash
+async fn find_todos(db: &DbConn, user_id: i32) -> Result<Vec<Todo>, DbErr> {
+    let workspaces = Workspace::find()
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .all(db)
+        .await?;
+    if workspaces.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let task_groups = TaskGroup::find()
+        .filter(task_group::Column::WorkspaceId.is_in(workspaces.iter().map(|w| w.id)))
+        .filter(task_group::Column::DeletedAt.is_null())
+        .all(db)
+        .await?;
+    if task_groups.is_empty() {
+        return Ok(vec![]);
+    }
+    let done_task_groups_ids: Vec<i32> = GitIntegration::find()
+        .filter(git_integration::Column::WorkspaceId.is_in(workspaces.iter().map(|w| w.id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|integration| integration.done_task_group_id)
+        .collect();
+
+    let tasks = Task::find()
+        .filter(task::Column::UserId.eq(user_id))
+        .filter(task::Column::TaskGroupId.is_in(task_groups.iter().map(|tg| tg.id)))
+        .filter(task::Column::DueAt.is_not_null())
+        .all(db)
+        .await?;
+
+    let mut todos = vec![];
+    for task_model in tasks.into_iter() {
+        let workspace_model = task_groups
+            .iter()
+            .find(|tg| tg.id == task_model.task_group_id)
+            .and_then(|tg| workspaces.iter().find(|w| w.id == tg.workspace_id));
+        let (workspace_model, due_at) = match (workspace_model, task_model.due_at) {
+            (Some(workspace_model), Some(due_at)) => (workspace_model, due_at),
+            _ => continue,
+        };
+
+        todos.push(Todo {
+            task_id: task_model.id,
+            summary: format!(
+                "{} {}",
+                task_key(&workspace_model.key_prefix, task_model.number),
+                task_model.title
+            ),
+            description: task_model.description,
+            categories: workspace_model.title.clone(),
+            due_at,
+            is_completed: done_task_groups_ids.contains(&task_model.task_group_id),
+        });
+    }
+
+    Ok(todos)
+}
+
+/// VTODO entries of the tasks, DTSTAMP is left out when the last modification
+/// of the feed is not known yet
+fn render_todos(todos: &[Todo], dtstamp: Option<&DateTimeWithTimeZone>) -> String {
+    let mut content = String::new();
+    for todo in todos.iter() {
+        let status = match todo.is_completed {
+            true => "COMPLETED",
+            false => "NEEDS-ACTION",
+        };
+
+        push_line(&mut content, "BEGIN:VTODO");
+        push_line(&mut content, &format!("UID:task-{}@task-app", todo.task_id));
+        if let Some(dtstamp) = dtstamp {
+            push_line(
+                &mut content,
+                &format!("DTSTAMP:{}", format_date_time(dtstamp)),
+            );
+        }
+        push_line(
+            &mut content,
+            &format!("SUMMARY:{}", escape_text(&todo.summary)),
+        );
+        push_line(
+            &mut content,
+            &format!("DESCRIPTION:{}", escape_text(&todo.description)),
+        );
+        push_line(
+            &mut content,
+            &format!("CATEGORIES:{}", escape_text(&todo.categories)),
+        );
+        push_line(
+            &mut content,
+            &format!("DUE:{}", format_date_time(&todo.due_at)),
+        );
+        push_line(&mut content, &format!("STATUS:{}", status));
+        push_line(&mut content, "END:VTODO");
+    }
+
+    content
+}
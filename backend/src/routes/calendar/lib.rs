@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use rocket::{
+    request::{FromRequest, Outcome},
+    Request,
+};
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+/// Feed is served at `/api/v1/calendar/<token>.ics`
+pub const FEED_EXTENSION: &str = ".ics";
+
+/// 256 random bits, hex encoded
+pub fn generate_token() -> String {
+    let mut token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+pub fn feed_path(token: &str) -> String {
+    format!("/api/v1/calendar/{}{}", token, FEED_EXTENSION)
+}
+
+/// Escapes TEXT value of an iCalendar property (RFC 5545, section 3.3.11)
+pub fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// Date and time in UTC, e.g. `20261101T090000Z`
+pub fn format_date_time(date_time: &DateTimeWithTimeZone) -> String {
+    date_time
+        .with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Appends content line folded to lines of at most 75 octets, as required by RFC 5545
+pub fn push_line(calendar: &mut String, line: &str) {
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            calendar.push_str("\r\n ");
+            line_len = 1;
+        }
+        calendar.push(c);
+        line_len += c.len_utf8();
+    }
+    calendar.push_str("\r\n");
+}
+
+/// Conditional request headers of a client polling the feed
+pub struct CacheValidators {
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<DateTime<Utc>>,
+}
+
+impl CacheValidators {
+    /// `If-Modified-Since` is only used when the client did not send `If-None-Match`
+    pub fn is_not_modified(&self, etag: &str, last_modified: &DateTimeWithTimeZone) -> bool {
+        match (&self.if_none_match, &self.if_modified_since) {
+            (Some(if_none_match), _) => if_none_match
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == etag),
+            (None, Some(if_modified_since)) => {
+                last_modified.timestamp() <= if_modified_since.timestamp()
+            }
+            (None, None) => false,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CacheValidators {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        let if_modified_since = headers
+            .get_one("If-Modified-Since")
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&Utc));
+
+        Outcome::Success(CacheValidators {
+            if_none_match: headers.get_one("If-None-Match").map(String::from),
+            if_modified_since,
+        })
+    }
+}
@@ -0,0 +1,4 @@
+pub mod delete_token;
+pub mod feed;
+pub mod lib;
+pub mod rotate_token;
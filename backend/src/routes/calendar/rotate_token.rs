@@ -0,0 +1,82 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    current_timestamp,
+    entities::{calendar_feed, prelude::CalendarFeed},
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::{feed_path, generate_token};
+
+/// Creates the calendar feed of the user or replaces its token,
+/// calendars subscribed with the previous token stop receiving updates
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "calendar",
+    responses(
+        (status = 200, description = "New token of the feed", body = CalendarTokenResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[post("/calendar/token")]
+pub async fn handler(
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<CalendarToken>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_feed_res = CalendarFeed::find()
+        .filter(calendar_feed::Column::UserId.eq(user.user_id))
+        .one(&db)
+        .await;
+    let token = generate_token();
+    let save_res = match select_feed_res {
+        Ok(Some(feed_model)) => {
+            let mut feed_to_update: calendar_feed::ActiveModel = feed_model.into();
+            feed_to_update.token = ActiveValue::Set(token.clone());
+            feed_to_update.etag = ActiveValue::Set(None);
+            feed_to_update.last_modified = ActiveValue::Set(current_timestamp());
+            feed_to_update.update(&db).await
+        }
+        Ok(None) => {
+            calendar_feed::ActiveModel {
+                user_id: ActiveValue::Set(user.user_id),
+                token: ActiveValue::Set(token.clone()),
+                etag: ActiveValue::Set(None),
+                last_modified: ActiveValue::Set(current_timestamp()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+        }
+        Err(err) => Err(err),
+    };
+
+    match save_res {
+        Ok(_) => Ok(Json(SuccessResponse::new(CalendarToken {
+            path: feed_path(&token),
+            token,
+        }))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct CalendarToken {
+    pub token: String,
+    /// Path of the feed to subscribe to
+    pub path: String,
+}
@@ -0,0 +1,263 @@
+use utoipa::{
+    openapi::{
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        OpenApi as OpenApiDocument,
+    },
+    Modify, OpenApi, ToSchema,
+};
+
+use crate::routes::{
+    auth, batch, calendar, git, label, lib, search, sync, task, task_group, trash, user, view,
+    webhook, workspace,
+};
+
+/// Declares schema of `SuccessResponse` with each `data` type, under the given name
+macro_rules! success_responses {
+    ($($name:ident = $data:ty),* $(,)?) => {
+        $(
+            /// Body of a successful response, only describes it in the document
+            #[derive(ToSchema)]
+            #[allow(dead_code)]
+            pub struct $name {
+                success: bool,
+                data: $data,
+                /// Cursor of the next page of a listing, missing on the last page
+                next_cursor: Option<String>,
+            }
+        )*
+    };
+}
+
+success_responses!(
+    EmptyResponse = (),
+    BatchResultsResponse = Vec<batch::lib::BatchResult>,
+    CalendarTokenResponse = calendar::rotate_token::CalendarToken,
+    GitIntegrationResponse = git::lib::GitIntegration,
+    ProcessedPushResponse = git::push::ProcessedPush,
+    SavedLabelResponse = label::insert::SavedLabel,
+    UpdatedLabelResponse = label::update::UpdatedLabel,
+    SearchHitsResponse = Vec<search::get::SearchHit>,
+    SyncChangesResponse = sync::get::SyncChanges,
+    ImportedTasksResponse = task::import_csv::ImportedTasks,
+    InsertedTaskResponse = task::insert::InsertedTask,
+    FoundTaskResponse = task::select_all::FoundTask,
+    FoundTasksResponse = Vec<task::select_all::FoundTask>,
+    FoundTaskHistoryResponse = task::select_history::FoundTaskHistory,
+    UpdatedTaskResponse = task::update::UpdatedTask,
+    DeletedTaskGroupResponse = task_group::delete::DeletedTaskGroup,
+    InsertedTaskGroupResponse = task_group::insert::InsertedTaskGroup,
+    UpdatedTaskGroupResponse = task_group::update::UpdatedTaskGroup,
+    GroupTasksResponse = Vec<workspace::select_one::GroupTask>,
+    FoundTrashResponse = trash::select_all::FoundTrash,
+    FoundUserResponse = user::get::FoundUserData,
+    SavedViewResponse = view::lib::SavedView,
+    SavedViewsResponse = Vec<view::lib::SavedView>,
+    ViewTasksResponse = view::select_tasks::ViewTasks,
+    WebhookResponse = webhook::lib::Webhook,
+    WebhooksResponse = Vec<webhook::lib::Webhook>,
+    FoundDeliveriesResponse = Vec<webhook::select_deliveries::FoundDelivery>,
+    DeletedWorkspaceResponse = workspace::delete::DeletedWorkspace,
+    ImportedWorkspaceResponse = workspace::import::ImportedWorkspace,
+    InsertedWorkspaceResponse = workspace::insert::InsertedWorkspace,
+    ListedWorkspacesResponse = Vec<workspace::select_all::ListedWorkspace>,
+    FoundWorkspaceResponse = workspace::select_one::FoundWorkspace,
+    SavedModifiedWorkspaceResponse = workspace::update::SavedModifiedWorkspace,
+);
+
+/// OpenAPI document of all routes, every route requires one of the security schemes
+/// unless it overrides the requirement
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Task app API"),
+    paths(
+        auth::sign_in::handler,
+        auth::callback::success_handler,
+        auth::sign_out::handler,
+        workspace::insert::handler,
+        workspace::update::handler,
+        workspace::delete::handler,
+        workspace::select_all::handler,
+        workspace::select_one::handler,
+        workspace::events::handler,
+        workspace::export::handler,
+        workspace::import::handler,
+        user::get::handler,
+        task_group::insert::handler,
+        task_group::update::handler,
+        task_group::delete::handler,
+        task_group::select_tasks::handler,
+        task::insert::handler,
+        task::select_all::handler,
+        task::select_by_key::handler,
+        task::export_csv::handler,
+        task::import_csv::handler,
+        task::update::handler,
+        task::delete::handler,
+        task::select_history::handler,
+        label::insert::handler,
+        label::update::handler,
+        label::delete::handler,
+        batch::execute::handler,
+        trash::select_all::handler,
+        trash::restore::handler,
+        search::get::handler,
+        sync::get::handler,
+        view::insert::handler,
+        view::select_all::handler,
+        view::update::handler,
+        view::delete::handler,
+        view::select_tasks::handler,
+        webhook::insert::handler,
+        webhook::select_all::handler,
+        webhook::update::handler,
+        webhook::delete::handler,
+        webhook::select_deliveries::handler,
+        git::update_integration::handler,
+        git::select_integration::handler,
+        git::delete_integration::handler,
+        git::push::handler,
+        calendar::feed::handler,
+        calendar::rotate_token::handler,
+        calendar::delete_token::handler,
+    ),
+    components(schemas(
+        lib::ErrorResponseBody,
+        EmptyResponse,
+        BatchResultsResponse,
+        CalendarTokenResponse,
+        GitIntegrationResponse,
+        ProcessedPushResponse,
+        SavedLabelResponse,
+        UpdatedLabelResponse,
+        SearchHitsResponse,
+        SyncChangesResponse,
+        ImportedTasksResponse,
+        InsertedTaskResponse,
+        FoundTaskResponse,
+        FoundTasksResponse,
+        FoundTaskHistoryResponse,
+        UpdatedTaskResponse,
+        DeletedTaskGroupResponse,
+        InsertedTaskGroupResponse,
+        UpdatedTaskGroupResponse,
+        GroupTasksResponse,
+        FoundTrashResponse,
+        FoundUserResponse,
+        SavedViewResponse,
+        SavedViewsResponse,
+        ViewTasksResponse,
+        WebhookResponse,
+        WebhooksResponse,
+        FoundDeliveriesResponse,
+        DeletedWorkspaceResponse,
+        ImportedWorkspaceResponse,
+        InsertedWorkspaceResponse,
+        ListedWorkspacesResponse,
+        FoundWorkspaceResponse,
+        SavedModifiedWorkspaceResponse,
+        batch::lib::BatchRequest,
+        batch::lib::BatchOperation,
+        batch::lib::BatchId,
+        batch::lib::BatchNewTaskGroup,
+        batch::lib::BatchNewTask,
+        batch::lib::BatchTaskDataToUpdate,
+        batch::lib::BatchNewLabel,
+        batch::lib::BatchResult,
+        batch::lib::BatchErrorBody,
+        calendar::rotate_token::CalendarToken,
+        git::lib::GitIntegration,
+        git::lib::PushPayload,
+        git::lib::PushCommit,
+        git::lib::PushCommitAuthor,
+        git::push::ProcessedPush,
+        git::update_integration::GitIntegrationData,
+        label::insert::NewLabel,
+        label::insert::SavedLabel,
+        label::update::LabelDataToUpdate,
+        label::update::UpdatedLabel,
+        search::get::SearchHit,
+        search::get::HitWorkspace,
+        search::get::HitTaskGroup,
+        sync::get::SyncChanges,
+        sync::get::SyncWorkspace,
+        sync::get::SyncTaskGroup,
+        sync::get::SyncTask,
+        sync::get::SyncLabel,
+        sync::get::SyncDeleted,
+        task::import_csv::ImportedTasks,
+        task::import_csv::RowError,
+        task::insert::NewTask,
+        task::insert::InsertedTask,
+        task::select_all::FoundTask,
+        task::select_history::FoundTaskHistory,
+        task::select_history::HistoryEntry,
+        task::select_history::LinkedCommit,
+        task::update::TaskDataToUpdate,
+        task::update::UpdatedTask,
+        task_group::delete::DeletedTaskGroup,
+        task_group::insert::NewTaskGroup,
+        task_group::insert::InsertedTaskGroup,
+        task_group::update::ModifiedTaskGroupData,
+        task_group::update::UpdatedTaskGroup,
+        trash::select_all::FoundTrash,
+        trash::select_all::TrashedWorkspace,
+        trash::select_all::TrashedTaskGroup,
+        trash::select_all::TrashedLabel,
+        user::get::FoundUserData,
+        view::insert::NewView,
+        view::lib::SavedView,
+        view::select_tasks::ViewTasks,
+        view::select_tasks::ViewTasksGroup,
+        view::select_tasks::ViewTask,
+        view::update::ModifiedViewData,
+        webhook::insert::NewWebhook,
+        webhook::lib::Webhook,
+        webhook::select_deliveries::FoundDelivery,
+        webhook::update::WebhookDataToUpdate,
+        workspace::delete::DeletedWorkspace,
+        workspace::import::ImportedWorkspace,
+        workspace::insert::NewWorkspace,
+        workspace::insert::InsertedWorkspace,
+        workspace::lib::ExportDocument,
+        workspace::lib::ExportWorkspace,
+        workspace::lib::ExportLabel,
+        workspace::lib::ExportTaskGroup,
+        workspace::lib::ExportTask,
+        workspace::lib::SkippedItem,
+        workspace::select_all::ListedWorkspace,
+        workspace::select_one::FoundWorkspace,
+        workspace::select_one::FoundLabel,
+        workspace::select_one::FoundTaskGroup,
+        workspace::select_one::GroupTask,
+        workspace::update::ModifiedWorkspaceData,
+        workspace::update::SavedModifiedWorkspace,
+    )),
+    modifiers(&SecuritySchemes),
+    security(("cookie" = []), ("bearer" = []))
+)]
+pub struct ApiDoc;
+
+/// Signed user id of `AuthenticatedUser`, either in the cookie set on sign in or as bearer token
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::with_description(
+                "id",
+                "Set by `/api/v1/auth/callback` after signing in",
+            ))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("Value of the `id` cookie, `<user id>.<signature>`"))
+                    .build(),
+            ),
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use lazy_static::lazy_static;
+use rocket::http::ContentType;
+use utoipa::OpenApi;
+
+use super::lib::ApiDoc;
+
+/// OpenAPI 3 document of the API, generated once from the route annotations
+#[get("/openapi.json")]
+pub fn handler() -> (ContentType, &'static str) {
+    lazy_static! {
+        static ref DOCUMENT: String = ApiDoc::openapi()
+            .to_json()
+            .expect("[OPENAPI]: Could not serialize the document");
+    }
+
+    (ContentType::JSON, DOCUMENT.as_str())
+}
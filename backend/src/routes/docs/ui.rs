@@ -0,0 +1,45 @@
+use rocket::{http::ContentType, response::content::RawHtml};
+use std::sync::Arc;
+use utoipa_swagger_ui::Config;
+
+/// Swagger UI of `/api/v1/openapi.json`, assets are embedded in the binary by `utoipa-swagger-ui`
+/// and served by `asset_handler`, so that the page does not run scripts of a CDN
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <title>Task app API</title>
+    <link rel="stylesheet" href="/api/v1/docs/swagger-ui.css" />
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="/api/v1/docs/swagger-ui-bundle.js"></script>
+    <script>
+      window.ui = SwaggerUIBundle({
+        url: "/api/v1/openapi.json",
+        dom_id: "#swagger-ui",
+        withCredentials: true,
+      });
+    </script>
+  </body>
+</html>
+"##;
+
+/// Interactive documentation of the API
+#[get("/docs")]
+pub fn handler() -> RawHtml<&'static str> {
+    RawHtml(SWAGGER_UI_HTML)
+}
+
+/// Stylesheets and scripts of Swagger UI
+#[get("/docs/<file>")]
+pub fn asset_handler(file: &str) -> Option<(ContentType, Vec<u8>)> {
+    let config = Arc::new(Config::from("/api/v1/openapi.json"));
+    match utoipa_swagger_ui::serve(file, config) {
+        Ok(Some(swagger_file)) => Some((
+            ContentType::parse_flexible(&swagger_file.content_type).unwrap_or(ContentType::Binary),
+            swagger_file.bytes.into_owned(),
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,62 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter};
+
+use backend::{
+    entities::{
+        git_integration,
+        prelude::{GitIntegration, Workspace},
+        workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+/// Removes git integration of the workspace, linked commits and task history are kept
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "git",
+    responses(
+        (status = 200, description = "Removed git integration", body = EmptyResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/workspace/<workspace_id>/git-integration")]
+pub async fn handler(
+    workspace_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_integration_res = GitIntegration::find()
+        .filter(git_integration::Column::WorkspaceId.eq(workspace_id))
+        .find_also_related(Workspace)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let integration_model = match select_integration_res {
+        Ok(Some((integration_model, Some(_)))) => integration_model,
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Workspace with id {} has no git integration",
+                    workspace_id
+                )),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    match integration_model.delete(&db).await {
+        Ok(_) => Ok(Json(SuccessResponse::new(()))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
@@ -0,0 +1,127 @@
+use hmac_sha256::HMAC;
+use lazy_static::lazy_static;
+use regex::Regex;
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    serde::{json::Json, Deserialize, Serialize},
+    Request,
+};
+use utoipa::ToSchema;
+
+use backend::entities::git_integration;
+
+use crate::routes::lib::{constant_time_eq, ErrorResponseBody};
+
+/// Task referenced in a commit message, `fixes #12` closes the task, `refs #12` only links it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskReference {
+    pub task_id: i32,
+    pub closes: bool,
+}
+
+/// Finds task references in a commit message, every task is returned once
+/// and closes if any of its references does
+pub fn parse_references(message: &str) -> Vec<TaskReference> {
+    lazy_static! {
+        static ref REFERENCE_REGEX: Regex = Regex::new(
+            r"(?i)\b(fix|fixes|fixed|close|closes|closed|resolve|resolves|resolved|ref|refs|references)\s+#(\d+)\b"
+        )
+        .unwrap();
+    }
+
+    let mut references: Vec<TaskReference> = vec![];
+    for captures in REFERENCE_REGEX.captures_iter(message) {
+        let task_id = match captures[2].parse::<i32>() {
+            Ok(task_id) => task_id,
+            Err(_) => continue,
+        };
+        let closes = !captures[1].to_lowercase().starts_with("ref");
+
+        match references.iter_mut().find(|r| r.task_id == task_id) {
+            Some(reference) => reference.closes = reference.closes || closes,
+            None => references.push(TaskReference { task_id, closes }),
+        }
+    }
+
+    references
+}
+
+pub fn verify_push_signature(secret: &str, payload: &str, signature: &PushSignature) -> bool {
+    let expected_signature = hex::encode(HMAC::mac(payload.as_bytes(), secret.as_bytes()));
+    let provided_signature = signature.0.to_lowercase();
+
+    constant_time_eq(&expected_signature, &provided_signature)
+}
+
+/// Hex encoded HMAC-SHA256 of the push payload, sent by GitHub in `X-Hub-Signature-256`
+/// and by Gitea in `X-Gitea-Signature`
+pub struct PushSignature(String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for PushSignature {
+    type Error = Json<ErrorResponseBody>;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        let github_signature = headers
+            .get_one("X-Hub-Signature-256")
+            .and_then(|signature| signature.strip_prefix("sha256="));
+
+        match github_signature.or_else(|| headers.get_one("X-Gitea-Signature")) {
+            Some(signature) => Outcome::Success(PushSignature(signature.into())),
+            None => Outcome::Failure((
+                Status::Unauthorized,
+                Json(ErrorResponseBody::new("Missing push signature")),
+            )),
+        }
+    }
+}
+
+/// Push event payload, only the parts common to GitHub and Gitea
+#[derive(Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PushPayload {
+    /// Missing in ping events
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PushCommit {
+    pub id: String,
+    pub message: String,
+    pub url: Option<String>,
+    pub author: Option<PushCommitAuthor>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PushCommitAuthor {
+    pub name: Option<String>,
+}
+
+/// Git integration without its secret, the secret can only be replaced
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct GitIntegration {
+    pub workspace_id: i32,
+    /// Task group tasks are moved to when a commit fixes them
+    pub done_task_group_id: Option<i32>,
+    /// Path the push webhook of the repository has to be sent to
+    pub push_path: String,
+}
+
+impl GitIntegration {
+    pub fn new(git_integration_model: git_integration::Model) -> GitIntegration {
+        GitIntegration {
+            workspace_id: git_integration_model.workspace_id,
+            done_task_group_id: git_integration_model.done_task_group_id,
+            push_path: format!(
+                "/api/v1/workspace/{}/git/push",
+                git_integration_model.workspace_id
+            ),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod delete_integration;
+pub mod lib;
+pub mod push;
+pub mod select_integration;
+pub mod update_integration;
@@ -0,0 +1,290 @@
+use rocket::{
+    data::{Data, ToByteUnit},
+    http::Status,
+    serde::{json::Json, Serialize},
+    State,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, ModelTrait,
+    QueryFilter, TransactionTrait,
+};
+use utoipa::ToSchema;
+
+use backend::{
+    current_timestamp,
+    entities::{
+        git_integration,
+        prelude::{GitIntegration, Task, TaskCommit, TaskGroup, Workspace},
+        task, task_commit, task_group, task_history, workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::{
+    events::bus::{EventBus, WorkspaceEventKind},
+    routes::{
+        lib::{ErrorResponse, SuccessResponse},
+        task::lib::{HISTORY_COMMIT_LINKED, HISTORY_MOVED},
+    },
+};
+
+use super::lib::{
+    parse_references, verify_push_signature, PushCommit, PushPayload, PushSignature, TaskReference,
+};
+
+const PUSH_PAYLOAD_LIMIT_MIB: usize = 5;
+
+/// Receives push webhook of a GitHub or Gitea repository, signed with the secret
+/// of the git integration of the workspace.
+///
+/// Commits referencing tasks of the workspace (`fixes #12`, `refs #12`) are linked to them,
+/// fixed tasks are moved to the done task group if the integration has one.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "git",
+    request_body(content = PushPayload, description = "Push event of GitHub or Gitea"),
+    params(
+        ("X-Hub-Signature-256" = Option<String>, Header, description = "Signature sent by GitHub"),
+        ("X-Gitea-Signature" = Option<String>, Header, description = "Signature sent by Gitea")
+    ),
+    responses(
+        (status = 200, description = "Linked commits and moved tasks", body = ProcessedPushResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    ),
+    security(())
+)]
+#[post("/workspace/<workspace_id>/git/push", data = "<data>")]
+pub async fn handler(
+    workspace_id: i32,
+    signature: PushSignature,
+    data: Data<'_>,
+    event_bus: &State<EventBus>,
+) -> Result<Json<SuccessResponse<ProcessedPush>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+
+    let read_res = data
+        .open(PUSH_PAYLOAD_LIMIT_MIB.mebibytes())
+        .into_string()
+        .await;
+    let payload = match read_res {
+        Ok(payload) if payload.is_complete() => payload.into_inner(),
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Push payload can not be larger than {} MiB",
+                    PUSH_PAYLOAD_LIMIT_MIB
+                )),
+                Status::PayloadTooLarge,
+            ))
+        }
+        Err(err) => return Err(ErrorResponse::body_read_error(err)),
+    };
+
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_integration_res = GitIntegration::find()
+        .filter(git_integration::Column::WorkspaceId.eq(workspace_id))
+        .find_also_related(Workspace)
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let integration = match select_integration_res {
+        Ok(Some((integration_model, Some(_)))) => integration_model,
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Workspace with id {} has no git integration",
+                    workspace_id
+                )),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    if !verify_push_signature(&integration.secret, &payload, &signature) {
+        return Err(ErrorResponse::new(
+            Some("Invalid push signature".into()),
+            Status::Unauthorized,
+        ));
+    }
+
+    let push: PushPayload = match serde_json::from_str(&payload) {
+        Ok(push) => push,
+        Err(err) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Invalid push payload: {}", err)),
+                Status::BadRequest,
+            ))
+        }
+    };
+
+    let mut processed_push = ProcessedPush {
+        linked_commits: 0,
+        moved_tasks: 0,
+    };
+    // Whole push is processed in one transaction, so it is either processed completely
+    // or can be delivered again, and events are sent only when it commits
+    let process_res: Result<(), DbErr> = async {
+        let tx = db.begin().await?;
+        // Only tasks of task groups which are not in trash can be referenced
+        let live_task_groups_ids: Vec<i32> = TaskGroup::find()
+            .filter(task_group::Column::WorkspaceId.eq(workspace_id))
+            .filter(task_group::Column::DeletedAt.is_null())
+            .all(&tx)
+            .await?
+            .into_iter()
+            .map(|task_group_model| task_group_model.id)
+            .collect();
+        let done_task_group_id = integration
+            .done_task_group_id
+            .filter(|id| live_task_groups_ids.contains(id));
+
+        for commit in push.commits.iter() {
+            for reference in parse_references(&commit.message) {
+                let found_task = Task::find_by_id(reference.task_id)
+                    .filter(task::Column::TaskGroupId.is_in(live_task_groups_ids.clone()))
+                    .one(&tx)
+                    .await?;
+                let task_model = match found_task {
+                    Some(task_model) => task_model,
+                    None => continue,
+                };
+
+                // Push can be delivered again, each commit is processed once per task
+                let is_linked = task_model
+                    .find_related(TaskCommit)
+                    .filter(task_commit::Column::Sha.eq(commit.id.clone()))
+                    .one(&tx)
+                    .await?
+                    .is_some();
+                if is_linked {
+                    continue;
+                }
+
+                link_commit(&tx, task_model.id, commit).await?;
+                processed_push.linked_commits += 1;
+
+                let moved = move_fixed_task(
+                    &tx,
+                    event_bus,
+                    workspace_id,
+                    task_model,
+                    reference,
+                    done_task_group_id,
+                    commit,
+                )
+                .await?;
+                if moved {
+                    processed_push.moved_tasks += 1;
+                }
+            }
+        }
+
+        tx.commit().await
+    }
+    .await;
+
+    match process_res {
+        Ok(_) => Ok(Json(SuccessResponse::new(processed_push))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+async fn link_commit<C: ConnectionTrait>(
+    db: &C,
+    task_id: i32,
+    commit: &PushCommit,
+) -> Result<(), DbErr> {
+    let now = current_timestamp();
+    let truncate = |value: &str| value.chars().take(255).collect::<String>();
+
+    task_commit::ActiveModel {
+        task_id: ActiveValue::Set(task_id),
+        sha: ActiveValue::Set(commit.id.chars().take(64).collect()),
+        message: ActiveValue::Set(commit.message.clone()),
+        url: ActiveValue::Set(commit.url.as_deref().map(truncate)),
+        author: ActiveValue::Set(
+            commit
+                .author
+                .as_ref()
+                .and_then(|author| author.name.as_deref())
+                .map(truncate),
+        ),
+        created_at: ActiveValue::Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    task_history::ActiveModel {
+        task_id: ActiveValue::Set(task_id),
+        action: ActiveValue::Set(HISTORY_COMMIT_LINKED.into()),
+        commit_sha: ActiveValue::Set(Some(commit.id.chars().take(64).collect())),
+        created_at: ActiveValue::Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Moves task fixed by the commit to the done task group, returns whether the task was moved
+async fn move_fixed_task<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    workspace_id: i32,
+    task_model: task::Model,
+    reference: TaskReference,
+    done_task_group_id: Option<i32>,
+    commit: &PushCommit,
+) -> Result<bool, DbErr> {
+    let done_task_group_id = match done_task_group_id {
+        Some(id) if reference.closes && id != task_model.task_group_id => id,
+        _ => return Ok(false),
+    };
+
+    let from_task_group_id = task_model.task_group_id;
+    let mut task_to_update: task::ActiveModel = task_model.into();
+    task_to_update.task_group_id = ActiveValue::Set(done_task_group_id);
+    let updated_task = task_to_update.update(db).await?;
+
+    task_history::ActiveModel {
+        task_id: ActiveValue::Set(updated_task.id),
+        action: ActiveValue::Set(HISTORY_MOVED.into()),
+        commit_sha: ActiveValue::Set(Some(commit.id.chars().take(64).collect())),
+        from_task_group_id: ActiveValue::Set(Some(from_task_group_id)),
+        to_task_group_id: ActiveValue::Set(Some(done_task_group_id)),
+        created_at: ActiveValue::Set(current_timestamp()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    event_bus
+        .publish(
+            db,
+            workspace_id,
+            WorkspaceEventKind::TaskMoved {
+                id: updated_task.id,
+                from_task_group_id,
+                to_task_group_id: done_task_group_id,
+            },
+        )
+        .await?;
+
+    Ok(true)
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ProcessedPush {
+    pub linked_commits: u64,
+    pub moved_tasks: u64,
+}
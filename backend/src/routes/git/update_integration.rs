@@ -0,0 +1,125 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, ModelTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        git_integration,
+        prelude::{GitIntegration as GitIntegrationEntity, TaskGroup, Workspace},
+        task_group, workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::GitIntegration;
+
+/// Creates or replaces git integration of the workspace
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "git",
+    request_body = GitIntegrationData,
+    responses(
+        (status = 200, description = "Saved git integration", body = GitIntegrationResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[put("/workspace/<workspace_id>/git-integration", data = "<data>")]
+pub async fn handler(
+    workspace_id: i32,
+    data: Json<GitIntegrationData>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<GitIntegration>>, ErrorResponse> {
+    if let Some(err) = validate_len(&data.secret, 16, 255, "Secret") {
+        return Err(err);
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_workspace_res = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let found_workspace = match select_workspace_res {
+        Ok(Some(workspace_model)) => workspace_model,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    // Done task group has to be in the workspace
+    if let Some(done_task_group_id) = data.done_task_group_id {
+        let select_task_group_res = TaskGroup::find_by_id(done_task_group_id)
+            .filter(task_group::Column::WorkspaceId.eq(workspace_id))
+            .filter(task_group::Column::DeletedAt.is_null())
+            .one(&db)
+            .await;
+        match select_task_group_res {
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                return Err(ErrorResponse::new(
+                    Some(format!(
+                        "Task group with id {} does not exist",
+                        done_task_group_id
+                    )),
+                    Status::NotFound,
+                ))
+            }
+            Err(err) => return Err(server_err_response.with_source(err)),
+        }
+    }
+
+    let existing_integration_res = found_workspace
+        .find_related(GitIntegrationEntity)
+        .one(&db)
+        .await;
+    let save_res = match existing_integration_res {
+        Ok(Some(integration_model)) => {
+            let mut integration_to_update: git_integration::ActiveModel = integration_model.into();
+            integration_to_update.secret = ActiveValue::Set(data.secret.clone());
+            integration_to_update.done_task_group_id = ActiveValue::Set(data.done_task_group_id);
+            integration_to_update.update(&db).await
+        }
+        Ok(None) => {
+            git_integration::ActiveModel {
+                workspace_id: ActiveValue::Set(workspace_id),
+                secret: ActiveValue::Set(data.secret.clone()),
+                done_task_group_id: ActiveValue::Set(data.done_task_group_id),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    match save_res {
+        Ok(integration_model) => Ok(Json(SuccessResponse::new(GitIntegration::new(
+            integration_model,
+        )))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct GitIntegrationData {
+    /// Secret the push webhook of the repository is signed with
+    pub secret: String,
+    pub done_task_group_id: Option<i32>,
+}
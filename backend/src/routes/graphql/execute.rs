@@ -0,0 +1,60 @@
+use async_graphql::{dataloader::DataLoader, BatchRequest};
+use async_graphql_rocket::{GraphQLBatchRequest, GraphQLResponse};
+use rocket::{http::Status, State};
+use tracing::Instrument;
+
+use backend::establish_db_connection;
+
+use crate::{
+    events::bus::EventBus,
+    request_log::RequestId,
+    routes::lib::{AuthenticatedUser, ErrorResponse},
+};
+
+use super::{
+    lib::{GraphQLSchema, MAX_BATCH_QUERIES},
+    loader::DbLoader,
+};
+
+/// Executes GraphQL query or mutation, or a batch of them, as the authenticated user
+#[post("/graphql", data = "<request>")]
+pub async fn handler(
+    request: GraphQLBatchRequest,
+    user: AuthenticatedUser,
+    schema: &State<GraphQLSchema>,
+    event_bus: &State<EventBus>,
+    request_id: RequestId,
+) -> Result<GraphQLResponse, ErrorResponse> {
+    if let BatchRequest::Batch(requests) = &request.0 {
+        if requests.len() > MAX_BATCH_QUERIES {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Batch must have at most {} queries",
+                    MAX_BATCH_QUERIES
+                )),
+                Status::BadRequest,
+            ));
+        }
+    }
+
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Errors of resolvers and of loaders, which run in their own tasks, are logged in the span
+    let span = request_id.span();
+    let loader_span = span.clone();
+    let loader = DataLoader::new(DbLoader::new(db.clone(), user.user_id), move |load| {
+        rocket::tokio::spawn(load.instrument(loader_span.clone()))
+    });
+    Ok(request
+        .data(db)
+        .data(loader)
+        .data(event_bus.inner().clone())
+        .data(user)
+        .execute(schema.inner())
+        .instrument(span)
+        .await)
+}
@@ -0,0 +1,102 @@
+use async_graphql::{
+    dataloader::DataLoader, Context, EmptySubscription, Error, ErrorExtensions, Schema, Value,
+};
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::DbErr;
+
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse, VersionedResponse},
+};
+
+use super::{loader::DbLoader, mutation::MutationRoot, query::QueryRoot};
+
+/// Workspace > task groups > tasks > labels > workspace is 5 levels deep
+const MAX_QUERY_DEPTH: usize = 8;
+/// Every selected field costs 1, so that aliases can not repeat expensive fields
+/// within the depth limit
+const MAX_QUERY_COMPLEXITY: usize = 250;
+/// Queries of a batch are executed one by one, each within the limits above
+pub const MAX_BATCH_QUERIES: usize = 10;
+
+pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn build_schema() -> GraphQLSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+}
+
+/// User the request is authenticated as, in the form the REST handlers take it
+pub fn current_user(ctx: &Context<'_>) -> AuthenticatedUser {
+    AuthenticatedUser {
+        user_id: ctx.data_unchecked::<AuthenticatedUser>().user_id,
+    }
+}
+
+pub fn loader<'a>(ctx: &Context<'a>) -> &'a DataLoader<DbLoader> {
+    ctx.data_unchecked::<DataLoader<DbLoader>>()
+}
+
+pub fn event_bus<'a>(ctx: &Context<'a>) -> &'a State<EventBus> {
+    State::from(ctx.data_unchecked::<EventBus>())
+}
+
+/// Successful response of a REST handler
+pub trait HandlerResponse {
+    type Data;
+
+    fn into_data(self) -> Self::Data;
+}
+
+impl<T> HandlerResponse for Json<SuccessResponse<T>> {
+    type Data = T;
+
+    fn into_data(self) -> T {
+        self.into_inner().into_data()
+    }
+}
+
+impl<T> HandlerResponse for VersionedResponse<T> {
+    type Data = T;
+
+    fn into_data(self) -> T {
+        self.into_data()
+    }
+}
+
+/// Data of the response of a REST handler, mutations run the same handlers as the REST routes
+pub fn into_data<R: HandlerResponse>(response: Result<R, ErrorResponse>) -> Result<R::Data, Error> {
+    response
+        .map(HandlerResponse::into_data)
+        .map_err(graphql_error)
+}
+
+/// Error with the HTTP status the REST route would respond with in the `status` extension,
+/// conflicts have the current row in the `current` extension
+pub fn graphql_error(error_response: ErrorResponse) -> Error {
+    error_response.log_in_current_span();
+    let status = error_response.status().code;
+    let current = error_response
+        .current()
+        .and_then(|current| Value::from_json(current.clone()).ok());
+    Error::new(error_response.message()).extend_with(|_, extensions| {
+        extensions.set("status", status);
+        if let Some(current) = &current {
+            extensions.set("current", current.clone());
+        }
+    })
+}
+
+/// Database errors are not exposed, same as in the REST routes
+pub fn server_error(err: DbErr) -> Error {
+    graphql_error(ErrorResponse::new(None, Status::InternalServerError).with_source(err))
+}
+
+pub fn not_found_error(entity: &str, id: i32) -> Error {
+    graphql_error(ErrorResponse::new(
+        Some(format!("{} with id {} does not exist", entity, id)),
+        Status::NotFound,
+    ))
+}
@@ -0,0 +1,228 @@
+use async_graphql::{dataloader::Loader, Error};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::HashMap;
+
+use backend::entities::{
+    label,
+    prelude::{Label, Task, TaskGroup, Workspace},
+    task, task_group, workspace,
+};
+
+use super::lib::server_error;
+
+/// Batches loads of one GraphQL request into a query per entity and key type.
+///
+/// Loader is created for each request, only rows of the authenticated user are loaded
+/// and trashed rows are left out the same way as in the REST routes.
+pub struct DbLoader {
+    db: DatabaseConnection,
+    user_id: i32,
+}
+
+impl DbLoader {
+    pub fn new(db: DatabaseConnection, user_id: i32) -> DbLoader {
+        DbLoader { db, user_id }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorkspaceId(pub i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskGroupId(pub i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(pub i32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LabelId(pub i32);
+
+/// Task groups of the workspace
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorkspaceTaskGroups(pub i32);
+
+/// Labels of the workspace
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WorkspaceLabels(pub i32);
+
+/// Tasks of the task group
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskGroupTasks(pub i32);
+
+#[rocket::async_trait]
+impl Loader<WorkspaceId> for DbLoader {
+    type Value = workspace::Model;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[WorkspaceId],
+    ) -> Result<HashMap<WorkspaceId, workspace::Model>, Error> {
+        let workspaces_models = Workspace::find()
+            .filter(workspace::Column::Id.is_in(keys.iter().map(|key| key.0)))
+            .filter(workspace::Column::UserId.eq(self.user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        Ok(workspaces_models
+            .into_iter()
+            .map(|workspace_model| (WorkspaceId(workspace_model.id), workspace_model))
+            .collect())
+    }
+}
+
+#[rocket::async_trait]
+impl Loader<TaskGroupId> for DbLoader {
+    type Value = task_group::Model;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[TaskGroupId],
+    ) -> Result<HashMap<TaskGroupId, task_group::Model>, Error> {
+        let task_groups_models = TaskGroup::find()
+            .filter(task_group::Column::Id.is_in(keys.iter().map(|key| key.0)))
+            .filter(task_group::Column::UserId.eq(self.user_id))
+            .filter(task_group::Column::DeletedAt.is_null())
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        Ok(task_groups_models
+            .into_iter()
+            .map(|task_group_model| (TaskGroupId(task_group_model.id), task_group_model))
+            .collect())
+    }
+}
+
+#[rocket::async_trait]
+impl Loader<TaskId> for DbLoader {
+    type Value = task::Model;
+    type Error = Error;
+
+    async fn load(&self, keys: &[TaskId]) -> Result<HashMap<TaskId, task::Model>, Error> {
+        // Tasks of trashed task groups are not accessible
+        let tasks_models = Task::find()
+            .filter(task::Column::Id.is_in(keys.iter().map(|key| key.0)))
+            .filter(task::Column::UserId.eq(self.user_id))
+            .find_also_related(TaskGroup)
+            .filter(task_group::Column::DeletedAt.is_null())
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        Ok(tasks_models
+            .into_iter()
+            .map(|(task_model, _)| (TaskId(task_model.id), task_model))
+            .collect())
+    }
+}
+
+#[rocket::async_trait]
+impl Loader<LabelId> for DbLoader {
+    type Value = label::Model;
+    type Error = Error;
+
+    async fn load(&self, keys: &[LabelId]) -> Result<HashMap<LabelId, label::Model>, Error> {
+        let labels_models = Label::find()
+            .filter(label::Column::Id.is_in(keys.iter().map(|key| key.0)))
+            .filter(label::Column::UserId.eq(self.user_id))
+            .filter(label::Column::DeletedAt.is_null())
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        Ok(labels_models
+            .into_iter()
+            .map(|label_model| (LabelId(label_model.id), label_model))
+            .collect())
+    }
+}
+
+#[rocket::async_trait]
+impl Loader<WorkspaceTaskGroups> for DbLoader {
+    type Value = Vec<task_group::Model>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[WorkspaceTaskGroups],
+    ) -> Result<HashMap<WorkspaceTaskGroups, Vec<task_group::Model>>, Error> {
+        let task_groups_models = TaskGroup::find()
+            .filter(task_group::Column::WorkspaceId.is_in(keys.iter().map(|key| key.0)))
+            .filter(task_group::Column::UserId.eq(self.user_id))
+            .filter(task_group::Column::DeletedAt.is_null())
+            .order_by_asc(task_group::Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        let mut task_groups: HashMap<WorkspaceTaskGroups, Vec<task_group::Model>> = HashMap::new();
+        for task_group_model in task_groups_models.into_iter() {
+            task_groups
+                .entry(WorkspaceTaskGroups(task_group_model.workspace_id))
+                .or_default()
+                .push(task_group_model);
+        }
+        Ok(task_groups)
+    }
+}
+
+#[rocket::async_trait]
+impl Loader<WorkspaceLabels> for DbLoader {
+    type Value = Vec<label::Model>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[WorkspaceLabels],
+    ) -> Result<HashMap<WorkspaceLabels, Vec<label::Model>>, Error> {
+        let labels_models = Label::find()
+            .filter(label::Column::WorkspaceId.is_in(keys.iter().map(|key| key.0)))
+            .filter(label::Column::UserId.eq(self.user_id))
+            .filter(label::Column::DeletedAt.is_null())
+            .order_by_asc(label::Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        let mut labels: HashMap<WorkspaceLabels, Vec<label::Model>> = HashMap::new();
+        for label_model in labels_models.into_iter() {
+            labels
+                .entry(WorkspaceLabels(label_model.workspace_id))
+                .or_default()
+                .push(label_model);
+        }
+        Ok(labels)
+    }
+}
+
+#[rocket::async_trait]
+impl Loader<TaskGroupTasks> for DbLoader {
+    type Value = Vec<task::Model>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[TaskGroupTasks],
+    ) -> Result<HashMap<TaskGroupTasks, Vec<task::Model>>, Error> {
+        let tasks_models = Task::find()
+            .filter(task::Column::TaskGroupId.is_in(keys.iter().map(|key| key.0)))
+            .filter(task::Column::UserId.eq(self.user_id))
+            .order_by_asc(task::Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(server_error)?;
+
+        let mut tasks: HashMap<TaskGroupTasks, Vec<task::Model>> = HashMap::new();
+        for task_model in tasks_models.into_iter() {
+            tasks
+                .entry(TaskGroupTasks(task_model.task_group_id))
+                .or_default()
+                .push(task_model);
+        }
+        Ok(tasks)
+    }
+}
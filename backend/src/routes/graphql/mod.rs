@@ -0,0 +1,6 @@
+pub mod execute;
+pub mod lib;
+pub mod loader;
+pub mod mutation;
+pub mod query;
+pub mod types;
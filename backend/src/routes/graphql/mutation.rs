@@ -0,0 +1,221 @@
+use async_graphql::{Context, Object, Result};
+use rocket::serde::json::Json;
+
+use crate::routes::{
+    label::{self, insert::NewLabel, update::LabelDataToUpdate},
+    lib::IfMatch,
+    task::{self, insert::NewTask, update::TaskDataToUpdate},
+    task_group::{
+        self, delete::DeletedTaskGroup, insert::NewTaskGroup, update::ModifiedTaskGroupData,
+    },
+    workspace::{
+        self, delete::DeletedWorkspace, insert::NewWorkspace, update::ModifiedWorkspaceData,
+    },
+};
+
+use super::{
+    lib::{current_user, event_bus, into_data, loader, not_found_error},
+    loader::{LabelId, TaskGroupId, TaskId, WorkspaceId},
+    types::{Label, Task, TaskGroup, Workspace},
+};
+
+/// Mutations run the handlers of the REST routes, so they are validated the same way
+/// and publish the same events. Saved entity is loaded again to allow nested selections.
+/// Changes with `version` are rejected when the row was changed since that version.
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn create_workspace(&self, ctx: &Context<'_>, data: NewWorkspace) -> Result<Workspace> {
+        let inserted_workspace =
+            into_data(workspace::insert::create(Json(data), current_user(ctx)).await)?;
+        load_workspace(ctx, inserted_workspace.id).await
+    }
+
+    async fn update_workspace(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        data: ModifiedWorkspaceData,
+    ) -> Result<Workspace> {
+        into_data(
+            workspace::update::handler(
+                id,
+                Json(data),
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )?;
+        load_workspace(ctx, id).await
+    }
+
+    async fn delete_workspace(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: Option<i32>,
+    ) -> Result<DeletedWorkspace> {
+        into_data(
+            workspace::delete::handler(
+                id,
+                version,
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )
+    }
+
+    async fn create_task_group(&self, ctx: &Context<'_>, data: NewTaskGroup) -> Result<TaskGroup> {
+        let inserted_task_group = into_data(
+            task_group::insert::create(Json(data), current_user(ctx), event_bus(ctx)).await,
+        )?;
+        load_task_group(ctx, inserted_task_group.id).await
+    }
+
+    async fn update_task_group(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        data: ModifiedTaskGroupData,
+    ) -> Result<TaskGroup> {
+        into_data(
+            task_group::update::handler(
+                id,
+                Json(data),
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )?;
+        load_task_group(ctx, id).await
+    }
+
+    async fn delete_task_group(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: Option<i32>,
+    ) -> Result<DeletedTaskGroup> {
+        into_data(
+            task_group::delete::handler(
+                id,
+                version,
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )
+    }
+
+    async fn create_task(&self, ctx: &Context<'_>, data: NewTask) -> Result<Task> {
+        let inserted_task =
+            into_data(task::insert::create(Json(data), current_user(ctx), event_bus(ctx)).await)?;
+        load_task(ctx, inserted_task.id).await
+    }
+
+    async fn update_task(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        data: TaskDataToUpdate,
+    ) -> Result<Task> {
+        into_data(
+            task::update::handler(
+                id,
+                Json(data),
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )?;
+        load_task(ctx, id).await
+    }
+
+    async fn delete_task(&self, ctx: &Context<'_>, id: i32, version: Option<i32>) -> Result<bool> {
+        into_data(
+            task::delete::handler(
+                id,
+                version,
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )?;
+        Ok(true)
+    }
+
+    async fn create_label(&self, ctx: &Context<'_>, data: NewLabel) -> Result<Label> {
+        let saved_label =
+            into_data(label::insert::create(Json(data), current_user(ctx), event_bus(ctx)).await)?;
+        load_label(ctx, saved_label.id).await
+    }
+
+    async fn update_label(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        data: LabelDataToUpdate,
+    ) -> Result<Label> {
+        into_data(
+            label::update::handler(
+                id,
+                Json(data),
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )?;
+        load_label(ctx, id).await
+    }
+
+    async fn delete_label(&self, ctx: &Context<'_>, id: i32, version: Option<i32>) -> Result<bool> {
+        into_data(
+            label::delete::handler(
+                id,
+                version,
+                IfMatch(None),
+                current_user(ctx),
+                event_bus(ctx),
+            )
+            .await,
+        )?;
+        Ok(true)
+    }
+}
+
+async fn load_workspace(ctx: &Context<'_>, id: i32) -> Result<Workspace> {
+    match loader(ctx).load_one(WorkspaceId(id)).await? {
+        Some(workspace_model) => Ok(Workspace(workspace_model)),
+        None => Err(not_found_error("Workspace", id)),
+    }
+}
+
+async fn load_task_group(ctx: &Context<'_>, id: i32) -> Result<TaskGroup> {
+    match loader(ctx).load_one(TaskGroupId(id)).await? {
+        Some(task_group_model) => Ok(TaskGroup(task_group_model)),
+        None => Err(not_found_error("Task group", id)),
+    }
+}
+
+async fn load_task(ctx: &Context<'_>, id: i32) -> Result<Task> {
+    match loader(ctx).load_one(TaskId(id)).await? {
+        Some(task_model) => Ok(Task(task_model)),
+        None => Err(not_found_error("Task", id)),
+    }
+}
+
+async fn load_label(ctx: &Context<'_>, id: i32) -> Result<Label> {
+    match loader(ctx).load_one(LabelId(id)).await? {
+        Some(label_model) => Ok(Label(label_model)),
+        None => Err(not_found_error("Label", id)),
+    }
+}
@@ -0,0 +1,47 @@
+use async_graphql::{Context, Object, Result};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+use backend::entities::{prelude::Workspace as WorkspaceEntity, workspace};
+
+use crate::routes::{self, user::get::FoundUserData};
+
+use super::{
+    lib::{current_user, into_data, loader, server_error},
+    loader::{TaskGroupId, TaskId, WorkspaceId},
+    types::{Task, TaskGroup, Workspace},
+};
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Signed in user
+    async fn me(&self, ctx: &Context<'_>) -> Result<FoundUserData> {
+        into_data(routes::user::get::handler(current_user(ctx)).await)
+    }
+
+    /// Workspaces of the user, oldest first
+    async fn workspaces(&self, ctx: &Context<'_>) -> Result<Vec<Workspace>> {
+        let db = ctx.data_unchecked::<DatabaseConnection>();
+        let workspaces_models = WorkspaceEntity::find()
+            .filter(workspace::Column::UserId.eq(current_user(ctx).user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .order_by_asc(workspace::Column::Id)
+            .all(db)
+            .await
+            .map_err(server_error)?;
+        Ok(workspaces_models.into_iter().map(Workspace).collect())
+    }
+
+    async fn workspace(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Workspace>> {
+        Ok(loader(ctx).load_one(WorkspaceId(id)).await?.map(Workspace))
+    }
+
+    async fn task_group(&self, ctx: &Context<'_>, id: i32) -> Result<Option<TaskGroup>> {
+        Ok(loader(ctx).load_one(TaskGroupId(id)).await?.map(TaskGroup))
+    }
+
+    async fn task(&self, ctx: &Context<'_>, id: i32) -> Result<Option<Task>> {
+        Ok(loader(ctx).load_one(TaskId(id)).await?.map(Task))
+    }
+}
@@ -0,0 +1,186 @@
+use async_graphql::{Context, Object, Result};
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+use backend::{
+    entities::{label, task, task_group, workspace},
+    services::task_keys::task_key,
+};
+
+use super::{
+    lib::loader,
+    loader::{
+        LabelId, TaskGroupId, TaskGroupTasks, WorkspaceId, WorkspaceLabels, WorkspaceTaskGroups,
+    },
+};
+
+pub struct Workspace(pub workspace::Model);
+
+#[Object]
+impl Workspace {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn key_prefix(&self) -> &str {
+        &self.0.key_prefix
+    }
+
+    /// Version of the row, mutations with it are rejected when the row was changed since
+    async fn version(&self) -> i32 {
+        self.0.version
+    }
+
+    async fn task_groups(&self, ctx: &Context<'_>) -> Result<Vec<TaskGroup>> {
+        let task_groups_models = loader(ctx)
+            .load_one(WorkspaceTaskGroups(self.0.id))
+            .await?
+            .unwrap_or_default();
+        Ok(task_groups_models.into_iter().map(TaskGroup).collect())
+    }
+
+    async fn labels(&self, ctx: &Context<'_>) -> Result<Vec<Label>> {
+        let labels_models = loader(ctx)
+            .load_one(WorkspaceLabels(self.0.id))
+            .await?
+            .unwrap_or_default();
+        Ok(labels_models.into_iter().map(Label).collect())
+    }
+}
+
+pub struct TaskGroup(pub task_group::Model);
+
+#[Object]
+impl TaskGroup {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    /// Version of the row, mutations with it are rejected when the row was changed since
+    async fn version(&self) -> i32 {
+        self.0.version
+    }
+
+    async fn workspace(&self, ctx: &Context<'_>) -> Result<Option<Workspace>> {
+        let workspace_model = loader(ctx)
+            .load_one(WorkspaceId(self.0.workspace_id))
+            .await?;
+        Ok(workspace_model.map(Workspace))
+    }
+
+    async fn tasks(&self, ctx: &Context<'_>) -> Result<Vec<Task>> {
+        let tasks_models = loader(ctx)
+            .load_one(TaskGroupTasks(self.0.id))
+            .await?
+            .unwrap_or_default();
+        Ok(tasks_models.into_iter().map(Task).collect())
+    }
+}
+
+pub struct Task(pub task::Model);
+
+#[Object]
+impl Task {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    /// Key of the task in its workspace, e.g. `WEB-42`
+    async fn key(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let task_group_model = match self.task_group_model(ctx).await? {
+            Some(task_group_model) => task_group_model,
+            None => return Ok(None),
+        };
+        let workspace_model = loader(ctx)
+            .load_one(WorkspaceId(task_group_model.workspace_id))
+            .await?;
+        Ok(workspace_model
+            .map(|workspace_model| task_key(&workspace_model.key_prefix, self.0.number)))
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    async fn due_at(&self) -> Option<DateTimeWithTimeZone> {
+        self.0.due_at
+    }
+
+    /// Version of the row, mutations with it are rejected when the row was changed since
+    async fn version(&self) -> i32 {
+        self.0.version
+    }
+
+    async fn task_group(&self, ctx: &Context<'_>) -> Result<Option<TaskGroup>> {
+        Ok(self.task_group_model(ctx).await?.map(TaskGroup))
+    }
+
+    /// Ids of trashed labels are kept on tasks until the labels are purged, they are left out
+    async fn labels(&self, ctx: &Context<'_>) -> Result<Vec<Label>> {
+        let labels_ids = match &self.0.labels_ids {
+            Some(labels_ids) => labels_ids,
+            None => return Ok(vec![]),
+        };
+        let mut labels_models = loader(ctx)
+            .load_many(labels_ids.iter().map(|label_id| LabelId(*label_id)))
+            .await?;
+        // Keep the order of the ids on the task
+        Ok(labels_ids
+            .iter()
+            .filter_map(|label_id| labels_models.remove(&LabelId(*label_id)))
+            .map(Label)
+            .collect())
+    }
+}
+
+impl Task {
+    async fn task_group_model(&self, ctx: &Context<'_>) -> Result<Option<task_group::Model>> {
+        Ok(loader(ctx)
+            .load_one(TaskGroupId(self.0.task_group_id))
+            .await?)
+    }
+}
+
+pub struct Label(pub label::Model);
+
+#[Object]
+impl Label {
+    async fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    async fn color(&self) -> &str {
+        &self.0.color
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    /// Version of the row, mutations with it are rejected when the row was changed since
+    async fn version(&self) -> i32 {
+        self.0.version
+    }
+
+    async fn workspace(&self, ctx: &Context<'_>) -> Result<Option<Workspace>> {
+        let workspace_model = loader(ctx)
+            .load_one(WorkspaceId(self.0.workspace_id))
+            .await?;
+        Ok(workspace_model.map(Workspace))
+    }
+}
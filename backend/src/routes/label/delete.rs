@@ -1,100 +1,80 @@
-use rocket::{http::Status, serde::json::Json};
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::TransactionTrait;
 
-use backend::{
-    entities::{
-        prelude::{Label, TaskGroup, Workspace},
-        task_group,
-    },
-    establish_db_connection,
-};
-use sea_orm::{
-    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, ModelTrait, QueryFilter,
-    Statement, TransactionError, TransactionTrait,
+use backend::establish_db_connection;
+
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, SuccessResponse},
 };
 
-use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+use super::{
+    lib::{delete_label, find_label},
+    update::UpdatedLabel,
+};
 
-#[delete("/label/<label_id>")]
+/// Moves label to trash, requests with `If-Match` header or `version` are rejected
+/// when the label was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "label",
+    params(
+        ("If-Match" = Option<String>, Header, description = "ETag of the deleted version"),
+        ("version" = Option<i32>, Query, description = "Version of the deleted label, `If-Match` header takes precedence")
+    ),
+    responses(
+        (status = 200, description = "Label moved to trash", body = EmptyResponse),
+        (status = 409, description = "Label was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Label was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/label/<label_id>?<version>")]
 pub async fn handler(
     label_id: i32,
+    version: Option<i32>,
+    if_match: IfMatch,
     user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
 ) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    let expected_version = ExpectedVersion::new(if_match, version);
     let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
-    let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(server_err_response);
-    }
-    let db = db_res.unwrap();
-
-    let tx_res = db
-        .transaction::<_, (), DbErr>(|tx| {
-            Box::pin(async move {
-                let label_to_delete_with_workspace = Label::find_by_id(label_id)
-                    .find_also_related(Workspace)
-                    .one(tx)
-                    .await?;
-
-                let not_found_err_msg = format!(
-                    "Could not delete label with id {}, label does not exist",
-                    label_id
-                );
-                if None == label_to_delete_with_workspace {
-                    return Err(DbErr::Custom(not_found_err_msg));
-                }
-
-                let (label_to_delete, related_workspace) = label_to_delete_with_workspace.unwrap();
+    let not_found_response = ErrorResponse::new(
+        Some(format!(
+            "Could not delete label with id {}, label does not exist",
+            label_id
+        )),
+        Status::NotFound,
+    );
+    let db = match establish_db_connection().await {
+        Ok(db) => db,
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
 
-                if label_to_delete.user_id != user.user_id {
-                    return Err(DbErr::Custom(not_found_err_msg));
-                }
-                if None == related_workspace {
-                    return Err(DbErr::Conn(sea_orm::RuntimeErr::Internal(String::from(""))));
-                }
-
-                label_to_delete.delete(tx).await?;
-
-                // Update tasks containing label id
-                let workspace_model = related_workspace.unwrap();
-                let related_task_groups = TaskGroup::find()
-                    .filter(task_group::Column::WorkspaceId.eq(workspace_model.id))
-                    .filter(task_group::Column::UserId.eq(user.user_id))
-                    .all(tx)
-                    .await?;
-
-                let mut task_groups_conditions: Vec<String> = vec![];
-                for task_group_model in related_task_groups.iter() {
-                    task_groups_conditions.push(
-                        format!(r#""task"."task_group_id" = {}"#, task_group_model.id)
-                    );
-                }
-
-                let update_tasks_stmt = format!(
-                    r#"UPDATE "task" SET "labels_ids" = array_remove("task"."labels_ids", {}) WHERE "task"."user_id" = {} AND "task"."labels_ids" @> ARRAY[{}] AND ({})"#,
-                    label_id,
-                    user.user_id,
-                    label_id,
-                    task_groups_conditions.join(" OR ")
-                );
-                tx.execute(Statement::from_string(
-                    DbBackend::Postgres,
-                    update_tasks_stmt
-                )).await?;
+    let existing_label = match find_label(&db, label_id, user.user_id).await {
+        Ok(Some(label_model)) => label_model,
+        Ok(None) => return Err(not_found_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    if !expected_version.matches(existing_label.version) {
+        return Err(expected_version.conflict_response(
+            &UpdatedLabel::new(existing_label.clone()),
+            existing_label.version,
+        ));
+    }
 
-                Ok(())
-            })
-        })
-        .await;
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    if delete_label(&tx, event_bus, &existing_label, &expected_version).await? {
+        tx.commit().await.map_err(ErrorResponse::server_error)?;
+        return Ok(Json(SuccessResponse::new(())));
+    }
 
-    match tx_res {
-        Ok(_) => Ok(Json(SuccessResponse::new(()))),
-        Err(tx_err) => {
-            return match tx_err {
-                TransactionError::Transaction(db_err) => match db_err {
-                    DbErr::Custom(err) => Err(ErrorResponse::new(Some(err), Status::NotFound)),
-                    _ => Err(server_err_response),
-                },
-                _ => Err(server_err_response),
-            };
-        }
+    // Label was changed or moved to trash since it was selected
+    match find_label(&db, label_id, user.user_id).await {
+        Ok(Some(label_model)) => Err(expected_version
+            .conflict_response(&UpdatedLabel::new(label_model.clone()), label_model.version)),
+        Ok(None) => Err(not_found_response),
+        Err(err) => Err(server_err_response.with_source(err)),
     }
 }
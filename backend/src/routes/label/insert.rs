@@ -1,73 +1,88 @@
+use async_graphql::InputObject;
 use rocket::{
     http::Status,
     serde::{json::Json, Deserialize, Serialize},
+    State,
 };
+use utoipa::ToSchema;
 
-use backend::{entities::label, establish_db_connection};
-use sea_orm::{ActiveModelTrait, DbErr};
+use backend::establish_db_connection;
+use sea_orm::TransactionTrait;
 
-use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{
+        AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, VersionedResponse,
+    },
+};
 
-use super::lib::validate_label_color;
+use super::lib::insert_label;
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "label",
+    request_body = NewLabel,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Created label", body = SavedLabelResponse,
+            headers(("ETag" = String, description = "Version of the label"))),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[post("/label", data = "<data>")]
 pub async fn handler(
     data: Json<NewLabel>,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<SavedLabel>>, ErrorResponse> {
-    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
-    let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(server_err_response);
+    idempotency_key: IdempotencyKey<'_>,
+    event_bus: &State<EventBus>,
+) -> Result<Idempotent<VersionedResponse<SavedLabel>>, ErrorResponse> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
     }
-    let db = db_res.unwrap();
 
-    if let Some(desc) = &data.description {
-        let desc_len_err = validate_len(desc, 1, 30, "Description");
-        if let Some(err) = desc_len_err {
-            return Err(err);
-        }
-    }
+    create(data, user, event_bus).await.map(Idempotent::Created)
+}
 
-    let clr_lowercase = &data.color.to_lowercase();
-    let clr_err = validate_label_color(clr_lowercase);
-    if let Some(err) = clr_err {
-        return Err(err);
+/// Creates the label, also run by the `createLabel` mutation
+pub async fn create(
+    data: Json<NewLabel>,
+    user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<SavedLabel>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
     }
+    let db = db_res.unwrap();
 
-    let insert_res = label::ActiveModel {
-        user_id: sea_orm::ActiveValue::Set(user.user_id),
-        workspace_id: sea_orm::ActiveValue::Set(data.workspace_id),
-        color: sea_orm::ActiveValue::Set(data.color.clone()),
-        description: sea_orm::ActiveValue::Set(data.description.clone()),
-        ..Default::default()
-    }
-    .save(&db)
-    .await;
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let inserted_label = insert_label(
+        &tx,
+        event_bus,
+        user.user_id,
+        data.workspace_id,
+        &data.color,
+        data.description.clone(),
+    )
+    .await?;
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
 
-    match insert_res {
-        Ok(label_model) => Ok(Json(SuccessResponse::new(SavedLabel {
-            workspace_id: data.workspace_id,
-            color: clr_lowercase.clone(),
-            description: data.description.clone(),
-            id: label_model.id.unwrap(),
-        }))),
-        Err(db_err) => match db_err {
-            DbErr::Query(query_err) => {
-                if query_err.to_string().contains("fk_label_id_workspace_id") {
-                    return Err(ErrorResponse::new(
-                            Some(format!("Could not create label with workspace id {}, workspace does not exist", data.workspace_id)),
-                            Status::BadRequest,
-                        ));
-                }
-                Err(server_err_response)
-            }
-            _ => Err(server_err_response),
+    Ok(VersionedResponse::new(
+        SavedLabel {
+            workspace_id: inserted_label.workspace_id,
+            color: inserted_label.color,
+            description: inserted_label.description,
+            id: inserted_label.id,
+            version: inserted_label.version,
         },
-    }
+        inserted_label.version,
+    ))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct NewLabel {
     pub workspace_id: i32,
@@ -75,11 +90,12 @@ pub struct NewLabel {
     pub description: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct SavedLabel {
     pub workspace_id: i32,
     pub color: String,
     pub description: Option<String>,
     pub id: i32,
+    pub version: i32,
 }
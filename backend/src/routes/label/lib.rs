@@ -1,8 +1,23 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use rocket::http::Status;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DbConn, DbErr,
+    EntityTrait, QueryFilter,
+};
 
-use crate::routes::lib::ErrorResponse;
+use backend::{
+    current_timestamp,
+    entities::{label, prelude::Label},
+};
+
+use crate::{
+    events::bus::{EventBus, EventLabel, WorkspaceEventKind},
+    routes::{
+        label::update::LabelDataToUpdate,
+        lib::{validate_len, ErrorResponse, ExpectedVersion},
+    },
+};
 
 pub fn validate_label_color(clr: &String) -> Option<ErrorResponse> {
     lazy_static! {
@@ -17,3 +32,163 @@ pub fn validate_label_color(clr: &String) -> Option<ErrorResponse> {
         )),
     }
 }
+
+/// Labels in trash are not accessible
+pub async fn find_label(
+    db: &DbConn,
+    label_id: i32,
+    user_id: i32,
+) -> Result<Option<label::Model>, DbErr> {
+    Label::find_by_id(label_id)
+        .filter(label::Column::UserId.eq(user_id))
+        .filter(label::Column::DeletedAt.is_null())
+        .one(db)
+        .await
+}
+
+/// Inserts the label into the workspace, colors are stored lowercase.
+/// Used by `POST /label` and batches.
+pub async fn insert_label<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    user_id: i32,
+    workspace_id: i32,
+    color: &str,
+    description: Option<String>,
+) -> Result<label::Model, ErrorResponse> {
+    if let Some(desc) = &description {
+        if let Some(err) = validate_len(desc, 1, 30, "Description") {
+            return Err(err);
+        }
+    }
+    let color = color.to_lowercase();
+    if let Some(err) = validate_label_color(&color) {
+        return Err(err);
+    }
+
+    let insert_res = label::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        workspace_id: ActiveValue::Set(workspace_id),
+        color: ActiveValue::Set(color),
+        description: ActiveValue::Set(description),
+        ..Default::default()
+    }
+    .insert(db)
+    .await;
+    let inserted_label = match insert_res {
+        Ok(inserted_label) => inserted_label,
+        Err(DbErr::Query(query_err))
+            if query_err.to_string().contains("fk_label_id_workspace_id") =>
+        {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Could not create label with workspace id {}, workspace does not exist",
+                    workspace_id
+                )),
+                Status::BadRequest,
+            ))
+        }
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+
+    event_bus
+        .publish(
+            db,
+            workspace_id,
+            WorkspaceEventKind::LabelCreated(EventLabel::new(inserted_label.clone())),
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(inserted_label)
+}
+
+/// Updates the label selected as `label_model` and publishes the change.
+///
+/// Returns `None` when the label was changed or moved to trash since it was selected,
+/// callers tell the two apart. Used by `PATCH /label/<id>` and batches.
+pub async fn update_label<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    label_model: label::Model,
+    data: &LabelDataToUpdate,
+    expected_version: &ExpectedVersion,
+) -> Result<Option<label::Model>, ErrorResponse> {
+    if data.color.is_none() && data.description.is_none() {
+        return Err(ErrorResponse::new(
+            Some("Either color or description has to be provided".into()),
+            Status::BadRequest,
+        ));
+    }
+
+    let version = label_model.version;
+    let mut label_to_update: label::ActiveModel = label_model.into();
+    if let Some(clr) = &data.color {
+        let clr_lowercase = clr.to_lowercase();
+        if let Some(err) = validate_label_color(&clr_lowercase) {
+            return Err(err);
+        }
+        label_to_update.color = ActiveValue::Set(clr_lowercase);
+    }
+    if let Some(desc) = &data.description {
+        if let Some(err) = validate_len(desc, 1, 30, "Description") {
+            return Err(err);
+        }
+        label_to_update.description = ActiveValue::Set(Some(desc.clone()));
+    }
+
+    let update_res = Label::update(label_to_update)
+        .filter(label::Column::DeletedAt.is_null())
+        .filter(expected_version.condition(label::Column::Version, version))
+        .exec(db)
+        .await;
+    let updated_label = match update_res {
+        Ok(updated_label) => updated_label,
+        Err(DbErr::RecordNotFound(_)) => return Ok(None),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+
+    event_bus
+        .publish(
+            db,
+            updated_label.workspace_id,
+            WorkspaceEventKind::LabelUpdated(EventLabel::new(updated_label.clone())),
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(Some(updated_label))
+}
+
+/// Moves the label selected as `label_model` to trash, tasks keep its id so that it
+/// can be restored, the id is removed from tasks once the label is purged from trash.
+///
+/// Returns `false` when the label was changed or moved to trash since it was selected.
+/// Used by `DELETE /label/<id>` and batches.
+pub async fn delete_label<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    label_model: &label::Model,
+    expected_version: &ExpectedVersion,
+) -> Result<bool, ErrorResponse> {
+    let trashed_labels = Label::update_many()
+        .col_expr(label::Column::DeletedAt, Expr::value(current_timestamp()))
+        .filter(label::Column::Id.eq(label_model.id))
+        .filter(label::Column::DeletedAt.is_null())
+        .filter(expected_version.condition(label::Column::Version, label_model.version))
+        .exec(db)
+        .await
+        .map_err(ErrorResponse::server_error)?
+        .rows_affected;
+    if trashed_labels == 0 {
+        return Ok(false);
+    }
+
+    event_bus
+        .publish(
+            db,
+            label_model.workspace_id,
+            WorkspaceEventKind::LabelDeleted { id: label_model.id },
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(true)
+}
@@ -1,101 +1,121 @@
+use async_graphql::InputObject;
 use rocket::{
     http::Status,
     serde::{json::Json, Deserialize, Serialize},
+    State,
 };
+use utoipa::ToSchema;
 
-use backend::{
-    entities::{label, prelude::Label},
-    establish_db_connection,
-};
-use sea_orm::{ActiveModelTrait, EntityTrait};
-
-use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+use backend::{entities::label, establish_db_connection};
+use sea_orm::TransactionTrait;
 
-use super::lib::validate_label_color;
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, VersionedResponse},
+};
 
+use super::lib::{find_label, update_label};
+
+/// Updates label, requests with `If-Match` header or `version` are rejected
+/// when the label was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "label",
+    request_body = LabelDataToUpdate,
+    params(("If-Match" = Option<String>, Header, description = "ETag of the changed version")),
+    responses(
+        (status = 200, description = "Updated label", body = UpdatedLabelResponse,
+            headers(("ETag" = String, description = "Version of the label"))),
+        (status = 409, description = "Label was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Label was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[patch("/label/<label_id>", data = "<data>")]
 pub async fn handler(
     label_id: i32,
     data: Json<LabelDataToUpdate>,
+    if_match: IfMatch,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<UpdatedLabel>>, ErrorResponse> {
-    if None == data.color && None == data.description {
-        return Err(ErrorResponse::new(
-            Some("Either color or description has to be provided".into()),
-            Status::BadRequest,
-        ));
-    }
-
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<UpdatedLabel>, ErrorResponse> {
     let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
     let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(server_err_response);
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
     }
     let db = db_res.unwrap();
 
-    let old_label_select_res = Label::find_by_id(label_id).one(&db).await;
-    if let Err(_) = old_label_select_res {
-        return Err(server_err_response);
-    }
-
-    let old_label = old_label_select_res.unwrap();
+    let expected_version = ExpectedVersion::new(if_match, data.version);
     let not_found_err_response = ErrorResponse::new(
         Some(format!("Label with id {} does not exist", label_id)),
         Status::NotFound,
     );
+    let old_label_model = match find_label(&db, label_id, user.user_id).await {
+        Ok(Some(old_label_model)) => old_label_model,
+        Ok(None) => return Err(not_found_err_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    if !expected_version.matches(old_label_model.version) {
+        return Err(expected_version.conflict_response(
+            &UpdatedLabel::new(old_label_model.clone()),
+            old_label_model.version,
+        ));
+    }
 
-    match old_label {
-        None => Err(not_found_err_response),
-        Some(old_label_model) => {
-            if old_label_model.user_id != user.user_id {
-                return Err(not_found_err_response);
-            }
-
-            let mut label_to_update: label::ActiveModel = old_label_model.into();
-
-            if let Some(clr) = &data.color {
-                let clr_lowercase = clr.to_lowercase();
-                let clr_err = validate_label_color(&clr_lowercase);
-                if let Some(err) = clr_err {
-                    return Err(err);
-                }
-
-                label_to_update.color = sea_orm::ActiveValue::Set(clr_lowercase);
-            }
-
-            if let Some(desc) = &data.description {
-                let desc_len_err = validate_len(desc, 1, 30, "Description");
-                if let Some(err) = desc_len_err {
-                    return Err(err);
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let updated_label_model =
+        match update_label(&tx, event_bus, old_label_model, &data, &expected_version).await? {
+            Some(updated_label_model) => updated_label_model,
+            // Label was changed or moved to trash since it was selected
+            None => {
+                return match find_label(&db, label_id, user.user_id).await {
+                    Ok(Some(label_model)) => Err(expected_version.conflict_response(
+                        &UpdatedLabel::new(label_model.clone()),
+                        label_model.version,
+                    )),
+                    Ok(None) => Err(not_found_err_response),
+                    Err(err) => Err(server_err_response.with_source(err)),
                 }
-
-                label_to_update.description = sea_orm::ActiveValue::Set(Some(desc.to_string()));
-            }
-
-            let updated_label_res = label_to_update.update(&db).await;
-            match updated_label_res {
-                Err(_) => Err(server_err_response),
-                Ok(updated_label_model) => Ok(Json(SuccessResponse::new(UpdatedLabel {
-                    workspace_id: updated_label_model.workspace_id,
-                    description: updated_label_model.description,
-                    color: updated_label_model.color,
-                }))),
             }
-        }
-    }
+        };
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
+
+    let version = updated_label_model.version;
+    Ok(VersionedResponse::new(
+        UpdatedLabel::new(updated_label_model),
+        version,
+    ))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct LabelDataToUpdate {
     pub description: Option<String>,
     pub color: Option<String>,
+    /// Version of the label which is changed, `If-Match` header takes precedence
+    pub version: Option<i32>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdatedLabel {
+    pub id: i32,
     pub workspace_id: i32,
     pub description: Option<String>,
     pub color: String,
+    pub version: i32,
+}
+
+impl UpdatedLabel {
+    pub fn new(label_model: label::Model) -> UpdatedLabel {
+        UpdatedLabel {
+            id: label_model.id,
+            workspace_id: label_model.workspace_id,
+            description: label_model.description,
+            color: label_model.color,
+            version: label_model.version,
+        }
+    }
 }
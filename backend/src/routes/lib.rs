@@ -1,22 +1,40 @@
 use hex;
 use hmac_sha256::HMAC;
 use rocket::{
-    http::Status,
+    fairing::AdHoc,
+    http::{ContentType, Method, Status},
     request::{FromRequest, Outcome},
     response,
     serde::{json::Json, Serialize},
     Request, Response,
 };
-use serde_json::json as serde_json;
-use std::io::Cursor;
+use sea_orm::{ColumnTrait, Condition};
+use serde_json::{json as serde_json, to_value, to_vec, Value};
+use std::{
+    io::{self, Cursor},
+    sync::Mutex,
+};
+use utoipa::ToSchema;
+
+use backend::{
+    establish_db_connection, get_env_var,
+    services::idempotency::{
+        claim_key, complete_key, release_key, request_hash, KeyClaim, StoredResponse,
+    },
+};
 
-use backend::get_env_var;
+use crate::{request_log::RequestId, routes::calendar::lib::generate_token};
 
+/// Body of all successful JSON responses, its schemas for each `data` type are declared
+/// next to `ApiDoc`
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct SuccessResponse<T> {
     success: bool,
     data: T,
+    /// Cursor of the next page of a listing, missing on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
 }
 
 impl<T> SuccessResponse<T> {
@@ -24,53 +42,133 @@ impl<T> SuccessResponse<T> {
         SuccessResponse {
             success: true,
             data,
+            next_cursor: None,
+        }
+    }
+
+    /// Response with one page of a listing, `next_cursor` is `None` on the last page
+    pub fn paginated(data: T, next_cursor: Option<String>) -> SuccessResponse<T> {
+        SuccessResponse {
+            success: true,
+            data,
+            next_cursor,
         }
     }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
 }
 
 #[derive(Debug)]
 pub struct ErrorResponse {
     body: ErrorResponseBody,
     status: Status,
+    /// `ETag` of the current row of a conflict response
+    etag: Option<String>,
+    /// Error which caused the response, it is logged but not sent
+    source: Option<String>,
 }
 
 impl ErrorResponse {
     pub fn new(err_message: Option<String>, status: Status) -> ErrorResponse {
         match err_message {
             Some(em) => ErrorResponse {
-                body: ErrorResponseBody {
-                    success: false,
-                    error: em.into(),
-                },
+                body: ErrorResponseBody::new(em),
                 status,
+                etag: None,
+                source: None,
             },
             None => {
                 let status_reason = status.reason();
                 if let Some(se) = status_reason {
                     return ErrorResponse {
-                        body: ErrorResponseBody {
-                            success: false,
-                            error: se.into(),
-                        },
+                        body: ErrorResponseBody::new(se),
                         status,
+                        etag: None,
+                        source: None,
                     };
                 }
                 ErrorResponse {
-                    body: ErrorResponseBody {
-                        success: false,
-                        error: "Internal server error".into(),
-                    },
+                    body: ErrorResponseBody::new("Internal server error"),
                     status,
+                    etag: None,
+                    source: None,
                 }
             }
         }
     }
+
+    /// Keeps the error which caused the response, so that it is logged with the request id
+    pub fn with_source<E: std::fmt::Display>(mut self, err: E) -> ErrorResponse {
+        self.source = Some(err.to_string());
+        self
+    }
+
+    /// 500 response caused by `err`
+    pub fn server_error<E: std::fmt::Display>(err: E) -> ErrorResponse {
+        ErrorResponse::new(None, Status::InternalServerError).with_source(err)
+    }
+
+    /// 400 response of a request body which could not be read as text
+    pub fn body_read_error(err: io::Error) -> ErrorResponse {
+        let message = match err.kind() {
+            io::ErrorKind::InvalidData => "Body is not valid UTF-8",
+            _ => "Body could not be read",
+        };
+        ErrorResponse::new(Some(message.into()), Status::BadRequest).with_source(err)
+    }
+
+    /// Message of the error, used where errors are reported per item instead of as a response
+    pub fn message(&self) -> &str {
+        &self.body.error
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Current row of a conflict response
+    pub fn current(&self) -> Option<&Value> {
+        self.body.current.as_ref()
+    }
+
+    /// Logs the error within the span of the request
+    pub fn log(&self, request_id: &RequestId) {
+        let _span = request_id.span().entered();
+        self.log_in_current_span();
+    }
+
+    /// Server errors are logged with their source, other errors only when they have one,
+    /// the current span has the request id
+    pub fn log_in_current_span(&self) {
+        if self.status.code >= 500 {
+            tracing::error!(
+                status = self.status.code,
+                error = self.source.as_deref().unwrap_or(&self.body.error),
+                "Request failed"
+            );
+        } else if let Some(source) = &self.source {
+            tracing::warn!(
+                status = self.status.code,
+                error = source.as_str(),
+                "Request rejected"
+            );
+        }
+    }
 }
 
 impl<'r> response::Responder<'r, 'static> for ErrorResponse {
-    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+    fn respond_to(mut self, request: &'r Request<'_>) -> response::Result<'static> {
+        let request_id = RequestId::of(request);
+        self.log(request_id);
+        self.body.request_id = Some(request_id.0.clone());
+
         let res_body = serde_json!(self.body).to_string();
         let build = &mut Response::build();
+        if let Some(etag) = self.etag {
+            build.raw_header("ETag", etag);
+        }
         build
             .status(self.status)
             .sized_body(res_body.len(), Cursor::new(res_body))
@@ -78,11 +176,18 @@ impl<'r> response::Responder<'r, 'static> for ErrorResponse {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct ErrorResponseBody {
     success: bool,
     error: String,
+    /// Current row when the request changed another version of it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    current: Option<Value>,
+    /// Same as the `X-Request-Id` header, for reports of errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
 }
 
 impl ErrorResponseBody {
@@ -90,10 +195,285 @@ impl ErrorResponseBody {
         ErrorResponseBody {
             success: false,
             error: error_message.into(),
+            current: None,
+            request_id: None,
+        }
+    }
+}
+
+/// JSON response of a single row with its version as `ETag`
+pub struct VersionedResponse<T> {
+    response: SuccessResponse<T>,
+    version: i32,
+}
+
+impl<T> VersionedResponse<T> {
+    pub fn new(data: T, version: i32) -> VersionedResponse<T> {
+        VersionedResponse {
+            response: SuccessResponse::new(data),
+            version,
+        }
+    }
+
+    pub fn into_data(self) -> T {
+        self.response.into_data()
+    }
+}
+
+impl<'r, T: Serialize> response::Responder<'r, 'static> for VersionedResponse<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let mut res = response::Responder::respond_to(Json(self.response), request)?;
+        res.set_raw_header("ETag", etag(self.version));
+        Ok(res)
+    }
+}
+
+/// `ETag` of the version of a row
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// `If-Match` header of the request
+pub struct IfMatch(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfMatch {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(IfMatch(
+            request.headers().get_one("If-Match").map(String::from),
+        ))
+    }
+}
+
+/// Version of the row which the client changes, either `ETag` in the `If-Match` header
+/// or `version` of the request. Requests without it change the current version.
+pub enum ExpectedVersion {
+    Any,
+    IfMatch(String),
+    Version(i32),
+}
+
+impl ExpectedVersion {
+    /// `If-Match` takes precedence over `version` of the request
+    pub fn new(if_match: IfMatch, version: Option<i32>) -> ExpectedVersion {
+        match (if_match.0, version) {
+            (Some(if_match), _) => ExpectedVersion::IfMatch(if_match),
+            (None, Some(version)) => ExpectedVersion::Version(version),
+            (None, None) => ExpectedVersion::Any,
+        }
+    }
+
+    pub fn matches(&self, version: i32) -> bool {
+        match self {
+            ExpectedVersion::Any => true,
+            ExpectedVersion::IfMatch(if_match) => {
+                let etag = etag(version);
+                if_match
+                    .split(',')
+                    .map(|tag| tag.trim())
+                    .any(|tag| tag == "*" || tag == etag)
+            }
+            ExpectedVersion::Version(expected_version) => *expected_version == version,
+        }
+    }
+
+    /// Condition of the write of a row which was selected with `version`, the write
+    /// affects no rows when a concurrent request has changed the row in between
+    pub fn condition<C: ColumnTrait>(&self, version_column: C, version: i32) -> Condition {
+        match self {
+            ExpectedVersion::Any => Condition::all(),
+            _ => Condition::all().add(version_column.eq(version)),
+        }
+    }
+
+    /// Same as `condition` for writes done by services, which filter by the version themselves
+    pub fn required_version(&self, version: i32) -> Option<i32> {
+        match self {
+            ExpectedVersion::Any => None,
+            _ => Some(version),
+        }
+    }
+
+    /// 412 for `If-Match` and 409 for `version` of the request, body has the current row
+    pub fn conflict_response<T: Serialize>(&self, current: &T, version: i32) -> ErrorResponse {
+        let status = match self {
+            ExpectedVersion::IfMatch(_) => Status::PreconditionFailed,
+            _ => Status::Conflict,
+        };
+        let mut body = ErrorResponseBody::new(format!(
+            "Row was changed by another request, current version is {}",
+            version
+        ));
+        body.current = to_value(current).ok();
+        ErrorResponse {
+            body,
+            status,
+            etag: Some(etag(version)),
+            source: None,
+        }
+    }
+}
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// `Idempotency-Key` header of a creating request, retries with the same key
+/// get the response of the first request instead of creating the rows again
+pub struct IdempotencyKey<'r> {
+    key: Option<String>,
+    /// Method and path of the request, a key can not be reused for another route
+    route: String,
+    claimed_key: &'r ClaimedIdempotencyKey,
+}
+
+/// Id of the key claimed by the request, its response is stored by `idempotency_fairing`
+#[derive(Default)]
+struct ClaimedIdempotencyKey(Mutex<Option<i32>>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyKey<'r> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(IdempotencyKey {
+            key: request
+                .headers()
+                .get_one("Idempotency-Key")
+                .map(String::from),
+            route: format!("{} {}", request.method(), request.uri().path()),
+            claimed_key: request.local_cache(ClaimedIdempotencyKey::default),
+        })
+    }
+}
+
+impl IdempotencyKey<'_> {
+    /// Claims the key for the request, returns the stored response when the request
+    /// is a retry. Keys are compared by the hash of the parsed `data`, so requests
+    /// which only differ in formatting of the body are the same.
+    pub async fn claim<T: Serialize>(
+        &self,
+        user_id: i32,
+        data: &T,
+    ) -> Result<Option<StoredResponse>, ErrorResponse> {
+        let key = match &self.key {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Idempotency key length must be between 1 and {} characters",
+                    MAX_IDEMPOTENCY_KEY_LEN
+                )),
+                Status::BadRequest,
+            ));
+        }
+
+        let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+        let body = match to_vec(data) {
+            Ok(body) => body,
+            Err(err) => return Err(server_err_response.with_source(err)),
+        };
+        let db = match establish_db_connection().await {
+            Ok(db) => db,
+            Err(err) => return Err(server_err_response.with_source(err)),
+        };
+
+        match claim_key(&db, user_id, key, &request_hash(&self.route, &body)).await {
+            Ok(KeyClaim::Claimed(key_id)) => {
+                *self.claimed_key.0.lock().unwrap() = Some(key_id);
+                Ok(None)
+            }
+            Ok(KeyClaim::Completed(stored_response)) => Ok(Some(stored_response)),
+            Ok(KeyClaim::InProgress) => Err(ErrorResponse::new(
+                Some("Request with the same idempotency key is in progress".into()),
+                Status::Conflict,
+            )),
+            Ok(KeyClaim::Mismatch) => Err(ErrorResponse::new(
+                Some("Idempotency key was already used for another request".into()),
+                Status::UnprocessableEntity,
+            )),
+            Err(err) => Err(server_err_response.with_source(err)),
+        }
+    }
+}
+
+/// Response of a creating route, either of the request or stored for its idempotency key
+pub enum Idempotent<R> {
+    Created(R),
+    Replayed(StoredResponse),
+}
+
+impl<'r, R: response::Responder<'r, 'static>> response::Responder<'r, 'static> for Idempotent<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let stored_response = match self {
+            Idempotent::Created(response) => return response.respond_to(request),
+            Idempotent::Replayed(stored_response) => stored_response,
+        };
+
+        let build = &mut Response::build();
+        if let Some(etag) = stored_response.etag {
+            build.raw_header("ETag", etag);
         }
+        build
+            .status(Status::new(stored_response.status))
+            .header(ContentType::JSON)
+            .raw_header("Idempotent-Replayed", "true")
+            .sized_body(
+                stored_response.body.len(),
+                Cursor::new(stored_response.body),
+            )
+            .ok()
     }
 }
 
+/// Stores responses of requests which claimed an idempotency key. Keys of requests
+/// which failed with a server error are released, so that the request can be retried.
+pub fn idempotency_fairing() -> AdHoc {
+    AdHoc::on_response("Idempotency keys", |request, response| {
+        Box::pin(async move {
+            let claimed_key_id = request
+                .local_cache(ClaimedIdempotencyKey::default)
+                .0
+                .lock()
+                .unwrap()
+                .take();
+            let key_id = match claimed_key_id {
+                Some(key_id) => key_id,
+                None => return,
+            };
+
+            let store_res = async {
+                let db = establish_db_connection().await?;
+                if response.status().code >= 500 {
+                    return release_key(&db, key_id).await;
+                }
+
+                let body = match response.body_mut().to_string().await {
+                    Ok(body) => body,
+                    Err(_) => return release_key(&db, key_id).await,
+                };
+                response.set_sized_body(body.len(), Cursor::new(body.clone()));
+                let stored_response = StoredResponse {
+                    status: response.status().code,
+                    etag: response.headers().get_one("ETag").map(String::from),
+                    body,
+                };
+                complete_key(&db, key_id, stored_response).await
+            }
+            .await;
+
+            if let Err(err) = store_res {
+                error!(
+                    "[IDEMPOTENCY]: Could not store response of idempotency key {}: {}",
+                    key_id, err
+                );
+            }
+        })
+    })
+}
+
 pub struct AuthenticatedUser {
     pub user_id: i32,
 }
@@ -102,17 +482,26 @@ pub struct AuthenticatedUser {
 impl<'r> FromRequest<'r> for AuthenticatedUser {
     type Error = Json<ErrorResponseBody>;
 
+    /// Signed user id is sent in the `id` cookie set on sign in, or by API clients
+    /// as `Authorization: Bearer <signed id>`
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        let cookies = request.cookies();
         let fail_outcome = Outcome::Failure((
             Status::Unauthorized,
             Json(ErrorResponseBody::new("Not authenticated")),
         ));
 
-        match cookies.get("id") {
-            Some(cookie) => {
-                let parsed_cookie = cookie.value();
-                let id_with_signature = parsed_cookie.split(".").collect::<Vec<&str>>();
+        let id_cookie = request.cookies().get("id");
+        let signed_id = match id_cookie {
+            Some(cookie) => Some(cookie.value()),
+            None => request
+                .headers()
+                .get_one("Authorization")
+                .and_then(|authorization| authorization.strip_prefix("Bearer ")),
+        };
+
+        match signed_id {
+            Some(signed_id) => {
+                let id_with_signature = signed_id.split(".").collect::<Vec<&str>>();
 
                 if id_with_signature.len() == 2 {
                     let id = id_with_signature[0];
@@ -120,6 +509,13 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
 
                     let is_authenticated = verify_signature(id.into(), signature.into());
 
+                    // Browsers send the cookie with requests of other sites too
+                    if is_authenticated && id_cookie.is_some() && !has_csrf_token(request) {
+                        return Outcome::Failure((
+                            Status::Forbidden,
+                            Json(ErrorResponseBody::new("Invalid CSRF token")),
+                        ));
+                    }
                     if is_authenticated {
                         return Outcome::Success(AuthenticatedUser {
                             user_id: id.parse::<i32>().unwrap(),
@@ -134,6 +530,48 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
     }
 }
 
+/// Cookie with the CSRF token of cookie authenticated clients, they send it back
+/// in `X-CSRF-Token` header of requests which are not `GET`
+pub const CSRF_COOKIE: &str = "csrf_token";
+
+fn has_csrf_token(request: &Request<'_>) -> bool {
+    if matches!(
+        request.method(),
+        Method::Get | Method::Head | Method::Options
+    ) {
+        return true;
+    }
+
+    match (
+        request.cookies().get(CSRF_COOKIE),
+        request.headers().get_one("X-CSRF-Token"),
+    ) {
+        (Some(cookie), Some(token)) => !token.is_empty() && cookie.value() == token,
+        _ => false,
+    }
+}
+
+/// Sets the CSRF cookie for cookie authenticated clients which do not have it yet
+pub fn csrf_fairing() -> AdHoc {
+    AdHoc::on_response("CSRF token", |request, response| {
+        Box::pin(async move {
+            let cookies = request.cookies();
+            if cookies.get("id").is_none() || cookies.get(CSRF_COOKIE).is_some() {
+                return;
+            }
+
+            response.adjoin_raw_header(
+                "Set-Cookie",
+                format!(
+                    "{}={}; Max-Age=86400; Path=/; SameSite=Strict; Secure=true;",
+                    CSRF_COOKIE,
+                    generate_token()
+                ),
+            );
+        })
+    })
+}
+
 pub fn validate_len(
     input: &String,
     min_len: usize,
@@ -153,6 +591,80 @@ pub fn validate_len(
     None
 }
 
+const DEFAULT_PAGE_LIMIT: u64 = 50;
+const MAX_PAGE_LIMIT: u64 = 100;
+
+/// Position in a listing ordered by id
+pub struct Pagination {
+    pub limit: u64,
+    /// Only rows with greater id belong to the page
+    pub after_id: i32,
+}
+
+impl Pagination {
+    pub fn new(limit: Option<u64>, cursor: Option<String>) -> Result<Pagination, ErrorResponse> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+            return Err(ErrorResponse::new(
+                Some(format!("Limit must be between 1 and {}", MAX_PAGE_LIMIT)),
+                Status::BadRequest,
+            ));
+        }
+
+        let after_id = match cursor {
+            Some(c) => match decode_cursor(&c) {
+                Some(id) => id,
+                None => {
+                    return Err(ErrorResponse::new(
+                        Some("Invalid cursor".into()),
+                        Status::BadRequest,
+                    ))
+                }
+            },
+            None => 0,
+        };
+
+        Ok(Pagination { limit, after_id })
+    }
+
+    /// Number of rows to select, one more than the limit to find out whether next page exists
+    pub fn select_limit(&self) -> u64 {
+        self.limit + 1
+    }
+
+    /// Cuts rows selected with `select_limit` to the page and creates cursor of the next page
+    pub fn page<T, F>(&self, mut rows: Vec<T>, id_of: F) -> (Vec<T>, Option<String>)
+    where
+        F: Fn(&T) -> i32,
+    {
+        if rows.len() as u64 <= self.limit {
+            return (rows, None);
+        }
+
+        rows.truncate(self.limit as usize);
+        let next_cursor = rows.last().map(|row| encode_cursor(id_of(row)));
+        (rows, next_cursor)
+    }
+}
+
+fn encode_cursor(id: i32) -> String {
+    hex::encode(format!("id:{}", id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<i32> {
+    let decoded = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+    decoded.strip_prefix("id:")?.parse::<i32>().ok()
+}
+
+/// Compares secrets in constant time, so that they can not be guessed byte by byte
+pub fn constant_time_eq(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
 
 pub fn create_signature(user_id: &String) -> String {
     let signature_key = get_env_var("SIGNATURE_KEY");
@@ -171,5 +683,5 @@ pub fn verify_signature(user_id: String, provided_signature: String) -> bool {
     hmac.update(user_id.as_bytes());
     let signature = hmac.finalize();
 
-    hex::encode(signature) == provided_signature
+    constant_time_eq(&hex::encode(signature), &provided_signature)
 }
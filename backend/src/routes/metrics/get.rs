@@ -0,0 +1,58 @@
+use rocket::{
+    http::{ContentType, Status},
+    request::{FromRequest, Outcome},
+    serde::json::Json,
+    Request,
+};
+
+use backend::{
+    establish_db_connection, get_optional_env_var,
+    services::metrics::{encode, refresh_gauges},
+};
+
+use crate::routes::lib::{constant_time_eq, ErrorResponse, ErrorResponseBody};
+
+/// Metrics in the Prometheus text format, for the Prometheus server only
+#[get("/metrics")]
+pub async fn handler(_scraper: MetricsScraper) -> Result<(ContentType, String), ErrorResponse> {
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    if let Err(err) = refresh_gauges(&db).await {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
+    }
+
+    Ok((ContentType::Plain, encode()))
+}
+
+/// Metrics are public unless `METRICS_TOKEN` is set, the scraper then has to send it
+/// as `Authorization: Bearer <token>`
+pub struct MetricsScraper;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for MetricsScraper {
+    type Error = Json<ErrorResponseBody>;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected_token = match get_optional_env_var("METRICS_TOKEN") {
+            Some(token) if !token.is_empty() => token,
+            _ => return Outcome::Success(MetricsScraper),
+        };
+
+        let provided_token = request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        match constant_time_eq(&expected_token, provided_token) {
+            true => Outcome::Success(MetricsScraper),
+            false => Outcome::Failure((
+                Status::Unauthorized,
+                Json(ErrorResponseBody::new("Invalid metrics token")),
+            )),
+        }
+    }
+}
@@ -1,8 +1,19 @@
 pub mod lib;
 
 pub mod auth;
+pub mod batch;
+pub mod calendar;
+pub mod docs;
+pub mod git;
+pub mod graphql;
+pub mod metrics;
+pub mod search;
+pub mod sync;
 pub mod task;
 pub mod task_group;
+pub mod trash;
 pub mod user;
+pub mod view;
+pub mod webhook;
 pub mod workspace;
 pub mod label;
@@ -0,0 +1,173 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{DbBackend, DbErr, FromQueryResult, Statement};
+use utoipa::ToSchema;
+
+use backend::establish_db_connection;
+
+use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+const MAX_HITS: i64 = 50;
+
+/// Searches task titles and descriptions, task group titles and workspace titles and
+/// descriptions of the user's workspaces, best matches come first. Text is HTML escaped
+/// before it is highlighted, so that `<mark>` are the only tags of snippets
+const SEARCH_QUERY: &str = r#"
+SELECT "kind", "id", "title", "snippet", "rank", "workspace_id", "workspace_title", "task_group_id", "task_group_title"
+FROM (
+    SELECT
+        'task' AS "kind",
+        "task"."id",
+        "task"."title",
+        ts_headline('english', replace(replace(replace("task"."title" || ' ' || "task"."description", '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), "query", 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2') AS "snippet",
+        ts_rank("task"."search_vector", "query") AS "rank",
+        "workspace"."id" AS "workspace_id",
+        "workspace"."title" AS "workspace_title",
+        "task_group"."id" AS "task_group_id",
+        "task_group"."title" AS "task_group_title"
+    FROM "task"
+    INNER JOIN "task_group" ON "task_group"."id" = "task"."task_group_id"
+    INNER JOIN "workspace" ON "workspace"."id" = "task_group"."workspace_id",
+    websearch_to_tsquery('english', $1) AS "query"
+    WHERE "task"."search_vector" @@ "query"
+        AND "workspace"."user_id" = $2
+        AND "workspace"."deleted_at" IS NULL
+        AND "task_group"."deleted_at" IS NULL
+    UNION ALL
+    SELECT
+        'task_group' AS "kind",
+        "task_group"."id",
+        "task_group"."title",
+        ts_headline('english', replace(replace(replace("task_group"."title", '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), "query", 'StartSel=<mark>, StopSel=</mark>') AS "snippet",
+        ts_rank("task_group"."search_vector", "query") AS "rank",
+        "workspace"."id" AS "workspace_id",
+        "workspace"."title" AS "workspace_title",
+        NULL AS "task_group_id",
+        NULL AS "task_group_title"
+    FROM "task_group"
+    INNER JOIN "workspace" ON "workspace"."id" = "task_group"."workspace_id",
+    websearch_to_tsquery('english', $1) AS "query"
+    WHERE "task_group"."search_vector" @@ "query"
+        AND "workspace"."user_id" = $2
+        AND "workspace"."deleted_at" IS NULL
+        AND "task_group"."deleted_at" IS NULL
+    UNION ALL
+    SELECT
+        'workspace' AS "kind",
+        "workspace"."id",
+        "workspace"."title",
+        ts_headline('english', replace(replace(replace("workspace"."title" || ' ' || coalesce("workspace"."description", ''), '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), "query", 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2') AS "snippet",
+        ts_rank("workspace"."search_vector", "query") AS "rank",
+        "workspace"."id" AS "workspace_id",
+        "workspace"."title" AS "workspace_title",
+        NULL AS "task_group_id",
+        NULL AS "task_group_title"
+    FROM "workspace",
+    websearch_to_tsquery('english', $1) AS "query"
+    WHERE "workspace"."search_vector" @@ "query"
+        AND "workspace"."user_id" = $2
+        AND "workspace"."deleted_at" IS NULL
+) AS "hits"
+ORDER BY "rank" DESC, "id" ASC
+LIMIT $3
+"#;
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "search",
+    responses(
+        (status = 200, description = "Hits ordered by rank", body = SearchHitsResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/search?<q>")]
+pub async fn handler(
+    q: Option<String>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Vec<SearchHit>>>, ErrorResponse> {
+    let query = q.unwrap_or_default().trim().to_string();
+    let query_len_err = validate_len(&query, 1, 100, "Search query");
+    if let Some(err) = query_len_err {
+        return Err(err);
+    }
+
+    let search_res: Result<Vec<FoundHit>, DbErr> = async move {
+        let db = establish_db_connection().await?;
+        let found_hits = FoundHit::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            SEARCH_QUERY,
+            vec![query.into(), user.user_id.into(), MAX_HITS.into()],
+        ))
+        .all(&db)
+        .await?;
+        Ok(found_hits)
+    }
+    .await;
+
+    match search_res {
+        Ok(found_hits) => Ok(Json(SuccessResponse::new(
+            found_hits
+                .into_iter()
+                .map(|found_hit| SearchHit {
+                    kind: found_hit.kind,
+                    id: found_hit.id,
+                    title: found_hit.title,
+                    snippet: found_hit.snippet,
+                    rank: found_hit.rank,
+                    workspace: HitWorkspace {
+                        id: found_hit.workspace_id,
+                        title: found_hit.workspace_title,
+                    },
+                    task_group: match (found_hit.task_group_id, found_hit.task_group_title) {
+                        (Some(id), Some(title)) => Some(HitTaskGroup { id, title }),
+                        _ => None,
+                    },
+                })
+                .collect(),
+        ))),
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
+
+#[derive(FromQueryResult)]
+struct FoundHit {
+    kind: String,
+    id: i32,
+    title: String,
+    snippet: String,
+    rank: f32,
+    workspace_id: i32,
+    workspace_title: String,
+    task_group_id: Option<i32>,
+    task_group_title: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SearchHit {
+    pub kind: String,
+    pub id: i32,
+    pub title: String,
+    /// HTML escaped text with matches wrapped in `<mark>`
+    pub snippet: String,
+    pub rank: f32,
+    pub workspace: HitWorkspace,
+    pub task_group: Option<HitTaskGroup>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct HitWorkspace {
+    pub id: i32,
+    pub title: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct HitTaskGroup {
+    pub id: i32,
+    pub title: String,
+}
@@ -0,0 +1,297 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        label,
+        prelude::{Label, Task, TaskGroup, Tombstone, Workspace},
+        task, task_group, tombstone, workspace,
+    },
+    establish_db_connection,
+    services::{
+        changes::{last_change_seq, ENTITY_LABEL, ENTITY_TASK_GROUP, ENTITY_WORKSPACE},
+        task_keys::task_key,
+    },
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+/// Changes of the user's workspaces, task groups, tasks and labels since the sync
+/// which returned `since`, without it all rows which are not in trash are returned.
+///
+/// Rows changed several times are returned once with their current data. Tasks
+/// of deleted task groups are not listed as deleted, they are returned again
+/// when the task group is restored.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "sync",
+    responses(
+        (status = 200, description = "Changed rows and token of the next sync", body = SyncChangesResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/sync?<since>")]
+pub async fn handler(
+    since: Option<String>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<SyncChanges>>, ErrorResponse> {
+    let since_seq = match &since {
+        Some(token) => match decode_token(token) {
+            Some(seq) => Some(seq),
+            None => {
+                return Err(ErrorResponse::new(
+                    Some("Invalid sync token".into()),
+                    Status::BadRequest,
+                ))
+            }
+        },
+        None => None,
+    };
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Changes up to this value are committed, later ones are returned by the next sync
+    let to_seq = match last_change_seq(&db, user.user_id).await {
+        Ok(to_seq) => to_seq,
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    let from_seq = since_seq.unwrap_or(0);
+
+    let workspaces_res = Workspace::find()
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::ChangeSeq.gt(from_seq))
+        .filter(workspace::Column::ChangeSeq.lte(to_seq))
+        .order_by_asc(workspace::Column::ChangeSeq)
+        .all(&db)
+        .await;
+    let task_groups_res = TaskGroup::find()
+        .filter(task_group::Column::UserId.eq(user.user_id))
+        .filter(task_group::Column::ChangeSeq.gt(from_seq))
+        .filter(task_group::Column::ChangeSeq.lte(to_seq))
+        .order_by_asc(task_group::Column::ChangeSeq)
+        .all(&db)
+        .await;
+    let labels_res = Label::find()
+        .filter(label::Column::UserId.eq(user.user_id))
+        .filter(label::Column::ChangeSeq.gt(from_seq))
+        .filter(label::Column::ChangeSeq.lte(to_seq))
+        .order_by_asc(label::Column::ChangeSeq)
+        .all(&db)
+        .await;
+    // Tasks of trashed task groups are not accessible
+    let tasks_res = Task::find()
+        .filter(task::Column::UserId.eq(user.user_id))
+        .filter(task::Column::ChangeSeq.gt(from_seq))
+        .filter(task::Column::ChangeSeq.lte(to_seq))
+        .find_also_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .order_by_asc(task::Column::ChangeSeq)
+        .all(&db)
+        .await;
+    let (workspaces_models, task_groups_models, labels_models, tasks_models) =
+        match (workspaces_res, task_groups_res, labels_res, tasks_res) {
+            (Ok(workspaces), Ok(task_groups), Ok(labels), Ok(tasks)) => {
+                (workspaces, task_groups, labels, tasks)
+            }
+            (Err(err), _, _, _)
+            | (_, Err(err), _, _)
+            | (_, _, Err(err), _)
+            | (_, _, _, Err(err)) => return Err(server_err_response.with_source(err)),
+        };
+
+    // Workspaces of changed tasks may be unchanged, their tasks are changed when the prefix is
+    let key_prefixes: HashMap<i32, String> = match Workspace::find()
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .all(&db)
+        .await
+    {
+        Ok(workspaces) => workspaces
+            .into_iter()
+            .map(|workspace_model| (workspace_model.id, workspace_model.key_prefix))
+            .collect(),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    // Tombstones are only needed by clients which already have rows
+    let mut deleted: Vec<SyncDeleted> = vec![];
+    if since_seq.is_some() {
+        let tombstones_res = Tombstone::find()
+            .filter(tombstone::Column::UserId.eq(user.user_id))
+            .filter(tombstone::Column::ChangeSeq.gt(from_seq))
+            .filter(tombstone::Column::ChangeSeq.lte(to_seq))
+            .order_by_asc(tombstone::Column::ChangeSeq)
+            .all(&db)
+            .await;
+        match tombstones_res {
+            Ok(tombstones) => {
+                deleted.extend(tombstones.into_iter().map(|tombstone_model| SyncDeleted {
+                    entity: tombstone_model.entity,
+                    id: tombstone_model.entity_id,
+                }))
+            }
+            Err(err) => return Err(server_err_response.with_source(err)),
+        }
+    }
+
+    // Rows moved to trash are deleted for clients, restored rows are returned again
+    let mut workspaces: Vec<SyncWorkspace> = vec![];
+    for workspace_model in workspaces_models.into_iter() {
+        match workspace_model.deleted_at {
+            Some(_) => deleted.push(SyncDeleted::new(ENTITY_WORKSPACE, workspace_model.id)),
+            None => workspaces.push(SyncWorkspace {
+                id: workspace_model.id,
+                title: workspace_model.title,
+                description: workspace_model.description,
+                key_prefix: workspace_model.key_prefix,
+                version: workspace_model.version,
+            }),
+        }
+    }
+    let mut task_groups: Vec<SyncTaskGroup> = vec![];
+    for task_group_model in task_groups_models.into_iter() {
+        match task_group_model.deleted_at {
+            Some(_) => deleted.push(SyncDeleted::new(ENTITY_TASK_GROUP, task_group_model.id)),
+            None => task_groups.push(SyncTaskGroup {
+                id: task_group_model.id,
+                workspace_id: task_group_model.workspace_id,
+                title: task_group_model.title,
+                version: task_group_model.version,
+            }),
+        }
+    }
+    let mut labels: Vec<SyncLabel> = vec![];
+    for label_model in labels_models.into_iter() {
+        match label_model.deleted_at {
+            Some(_) => deleted.push(SyncDeleted::new(ENTITY_LABEL, label_model.id)),
+            None => labels.push(SyncLabel {
+                id: label_model.id,
+                workspace_id: label_model.workspace_id,
+                color: label_model.color,
+                description: label_model.description,
+                version: label_model.version,
+            }),
+        }
+    }
+    let tasks: Vec<SyncTask> = tasks_models
+        .into_iter()
+        .filter_map(|(task_model, task_group_model)| {
+            let key_prefix = key_prefixes.get(&task_group_model?.workspace_id)?;
+            Some(SyncTask {
+                id: task_model.id,
+                key: task_key(key_prefix, task_model.number),
+                task_group_id: task_model.task_group_id,
+                title: task_model.title,
+                description: task_model.description,
+                labels_ids: task_model.labels_ids,
+                due_at: task_model.due_at,
+                version: task_model.version,
+            })
+        })
+        .collect();
+    // First sync has nothing to delete, rows in trash are left out
+    if since_seq.is_none() {
+        deleted.clear();
+    }
+
+    Ok(Json(SuccessResponse::new(SyncChanges {
+        token: encode_token(to_seq),
+        workspaces,
+        task_groups,
+        tasks,
+        labels,
+        deleted,
+    })))
+}
+
+fn encode_token(change_seq: i64) -> String {
+    hex::encode(format!("seq:{}", change_seq))
+}
+
+fn decode_token(token: &str) -> Option<i64> {
+    let decoded = String::from_utf8(hex::decode(token).ok()?).ok()?;
+    decoded.strip_prefix("seq:")?.parse::<i64>().ok()
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SyncChanges {
+    /// Token of the next sync
+    pub token: String,
+    pub workspaces: Vec<SyncWorkspace>,
+    pub task_groups: Vec<SyncTaskGroup>,
+    pub tasks: Vec<SyncTask>,
+    pub labels: Vec<SyncLabel>,
+    /// Rows deleted or moved to trash since the previous sync
+    pub deleted: Vec<SyncDeleted>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SyncWorkspace {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub key_prefix: String,
+    pub version: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SyncTaskGroup {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub title: String,
+    pub version: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SyncTask {
+    pub id: i32,
+    pub key: String,
+    pub task_group_id: i32,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Option<Vec<i32>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub version: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SyncLabel {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub color: String,
+    pub description: Option<String>,
+    pub version: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SyncDeleted {
+    /// Either `workspace`, `task_group`, `task` or `label`
+    pub entity: String,
+    pub id: i32,
+}
+
+impl SyncDeleted {
+    fn new(entity: &str, id: i32) -> SyncDeleted {
+        SyncDeleted {
+            entity: entity.into(),
+            id,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::TransactionTrait;
+
+use backend::establish_db_connection;
+
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, SuccessResponse},
+};
+
+use super::lib::{delete_task, find_task, task_conflict_response};
+
+/// Deletes task, requests with `If-Match` header or `version` are rejected
+/// when the task was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    params(
+        ("If-Match" = Option<String>, Header, description = "ETag of the deleted version"),
+        ("version" = Option<i32>, Query, description = "Version of the deleted task, `If-Match` header takes precedence")
+    ),
+    responses(
+        (status = 200, description = "Deleted task", body = EmptyResponse),
+        (status = 409, description = "Task was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Task was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/task/<task_id>?<version>")]
+pub async fn handler(
+    task_id: i32,
+    version: Option<i32>,
+    if_match: IfMatch,
+    user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
+) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let expected_version = ExpectedVersion::new(if_match, version);
+    let not_found_response = ErrorResponse::new(
+        Some(format!(
+            "Could not delete task with id {}, task does not exist",
+            task_id
+        )),
+        Status::NotFound,
+    );
+    // Tasks of trashed task groups are deleted when the task group is purged
+    let (task_model, task_group_model) = match find_task(&db, task_id, user.user_id).await {
+        Ok(Some((task_model, Some(task_group_model)))) => (task_model, task_group_model),
+        Ok(_) => return Err(not_found_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    if !expected_version.matches(task_model.version) {
+        return Err(task_conflict_response(
+            &db,
+            &expected_version,
+            task_model,
+            task_group_model.workspace_id,
+        )
+        .await);
+    }
+
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let is_deleted = delete_task(
+        &tx,
+        event_bus,
+        &task_model,
+        task_group_model.workspace_id,
+        &expected_version,
+    )
+    .await?;
+    if is_deleted {
+        tx.commit().await.map_err(ErrorResponse::server_error)?;
+        return Ok(Json(SuccessResponse::new(())));
+    }
+
+    // Task was changed or deleted since it was selected
+    match find_task(&db, task_id, user.user_id).await {
+        Ok(Some((task_model, Some(task_group_model)))) => Err(task_conflict_response(
+            &db,
+            &expected_version,
+            task_model,
+            task_group_model.workspace_id,
+        )
+        .await),
+        Ok(_) => Err(not_found_response),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
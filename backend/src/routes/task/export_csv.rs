@@ -0,0 +1,103 @@
+use rocket::http::{ContentType, Status};
+
+use backend::{establish_db_connection, services::task_keys::task_key};
+
+use crate::routes::{
+    lib::{AuthenticatedUser, ErrorResponse},
+    workspace::export::ExportFile,
+};
+
+use super::lib::{
+    csv_label_name, escape_csv_cell, find_workspace_tasks, CSV_COLUMNS, CSV_LABELS_SEPARATOR,
+    STATUS_DONE, STATUS_OPEN,
+};
+
+/// Tasks of the workspace as CSV, one row per task with the columns of `CSV_COLUMNS`
+/// Cells which spreadsheets would evaluate as formulas are prefixed with `'`.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    responses(
+        (status = 200, description = "Tasks as CSV", body = String, content_type = "text/csv"),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>/tasks.csv")]
+pub async fn handler(
+    workspace_id: i32,
+    user: AuthenticatedUser,
+) -> Result<ExportFile, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let workspace_tasks = match find_workspace_tasks(&db, user.user_id, workspace_id).await {
+        Ok(Some(workspace_tasks)) => workspace_tasks,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let mut writer = csv::Writer::from_writer(vec![]);
+    if let Err(err) = writer.write_record(CSV_COLUMNS) {
+        return Err(server_err_response.with_source(err));
+    }
+    for task_model in workspace_tasks.tasks.iter() {
+        let task_group_title = workspace_tasks
+            .task_groups
+            .iter()
+            .find(|tg| tg.id == task_model.task_group_id)
+            .map(|tg| tg.title.as_str())
+            .unwrap_or_default();
+        let labels: Vec<&str> = task_model
+            .labels_ids
+            .iter()
+            .flatten()
+            .filter_map(|label_id| workspace_tasks.labels.iter().find(|l| &l.id == label_id))
+            .map(csv_label_name)
+            .collect();
+        let status = match workspace_tasks.done_task_group_id == Some(task_model.task_group_id) {
+            true => STATUS_DONE,
+            false => STATUS_OPEN,
+        };
+
+        let write_res = writer.write_record(
+            [
+                task_model.id.to_string().as_str(),
+                &task_key(&workspace_tasks.workspace.key_prefix, task_model.number),
+                task_group_title,
+                &task_model.title,
+                &task_model.description,
+                &labels.join(CSV_LABELS_SEPARATOR),
+                &task_model
+                    .due_at
+                    .map(|due_at| due_at.to_rfc3339())
+                    .unwrap_or_default(),
+                status,
+            ]
+            .map(|cell| escape_csv_cell(cell).into_owned()),
+        );
+        if let Err(err) = &write_res {
+            return Err(server_err_response.with_source(err));
+        }
+    }
+
+    let content = match writer.into_inner().map(String::from_utf8) {
+        Ok(Ok(content)) => content,
+        Ok(Err(err)) => return Err(server_err_response.with_source(err)),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    Ok(ExportFile::new(
+        ContentType::CSV,
+        content,
+        format!("workspace-{}-tasks.csv", workspace_id),
+    ))
+}
@@ -0,0 +1,473 @@
+//! Query language for filtering tasks, e.g.
+//! `label:bug group:"In progress" due:<2026-11-01 is:open assignee:me some text`
+//!
+//! A filter is a whitespace separated list of terms which all have to match. A term is either
+//! `field:value` or free text matched against task title and description. Values containing
+//! whitespace have to be wrapped in double quotes.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rocket::http::Status;
+use sea_orm::{sea_query::Expr, ColumnTrait, Condition};
+
+use backend::entities::{label, task, task_group};
+
+use crate::routes::lib::ErrorResponse;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub kind: TermKind,
+    /// Term as it was written in the filter
+    pub token: String,
+    /// 1-based position of the term in the filter
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermKind {
+    /// Label description or id
+    Label(String),
+    /// Task group title or id
+    Group(String),
+    Due(Comparison, NaiveDate),
+    Is(TaskState),
+    /// Tasks are only visible to their owner, so all of them are assigned to the user
+    AssigneeMe,
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+/// Tasks in the done task group of the git integration are done, all others are open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Open,
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    pub message: String,
+    pub token: String,
+    pub position: usize,
+}
+
+impl FilterError {
+    fn new<M: Into<String>>(message: M, token: &str, position: usize) -> FilterError {
+        FilterError {
+            message: message.into(),
+            token: token.into(),
+            position,
+        }
+    }
+
+    fn at_term<M: Into<String>>(message: M, term: &Term) -> FilterError {
+        FilterError::new(message, &term.token, term.position)
+    }
+}
+
+impl From<FilterError> for ErrorResponse {
+    fn from(err: FilterError) -> ErrorResponse {
+        ErrorResponse::new(
+            Some(format!(
+                "Invalid filter at position {} ({}): {}",
+                err.position, err.token, err.message
+            )),
+            Status::BadRequest,
+        )
+    }
+}
+
+/// Data the filter is evaluated against
+pub struct FilterContext<'a> {
+    pub task_groups: &'a [task_group::Model],
+    pub labels: &'a [label::Model],
+    /// Done task group of the git integration of the workspace
+    pub done_task_group_id: Option<i32>,
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Filter, FilterError> {
+        let mut terms = vec![];
+        for raw_token in tokenize(input)? {
+            terms.push(parse_term(raw_token)?);
+        }
+        Ok(Filter { terms })
+    }
+
+    /// Compiles filter into condition over `task` table, all terms have to match
+    pub fn to_condition(&self, ctx: &FilterContext) -> Result<Condition, FilterError> {
+        let mut condition = Condition::all();
+
+        for term in self.terms.iter() {
+            condition = match &term.kind {
+                TermKind::Label(label) => {
+                    let labels_ids: Vec<i32> = ctx
+                        .labels
+                        .iter()
+                        .filter(|label_model| {
+                            label.parse::<i32>() == Ok(label_model.id)
+                                || label_model
+                                    .description
+                                    .as_ref()
+                                    .is_some_and(|desc| desc.eq_ignore_ascii_case(label))
+                        })
+                        .map(|label_model| label_model.id)
+                        .collect();
+                    if labels_ids.is_empty() {
+                        return Err(FilterError::at_term(
+                            format!("label {} does not exist", label),
+                            term,
+                        ));
+                    }
+
+                    let mut labels_condition = Condition::any();
+                    for label_id in labels_ids {
+                        labels_condition = labels_condition.add(Expr::cust_with_values(
+                            r#""task"."labels_ids" @> ARRAY[$1]"#,
+                            vec![label_id],
+                        ));
+                    }
+                    condition.add(labels_condition)
+                }
+                TermKind::Group(group) => {
+                    let task_groups_ids: Vec<i32> = ctx
+                        .task_groups
+                        .iter()
+                        .filter(|task_group_model| {
+                            group.parse::<i32>() == Ok(task_group_model.id)
+                                || task_group_model.title.eq_ignore_ascii_case(group)
+                        })
+                        .map(|task_group_model| task_group_model.id)
+                        .collect();
+                    if task_groups_ids.is_empty() {
+                        return Err(FilterError::at_term(
+                            format!("task group {} does not exist", group),
+                            term,
+                        ));
+                    }
+
+                    condition.add(task::Column::TaskGroupId.is_in(task_groups_ids))
+                }
+                TermKind::Due(comparison, date) => {
+                    // Dates are days in UTC, tasks without due date never match
+                    let day_start = start_of_day(*date);
+                    let next_day_start = start_of_day(date.succ_opt().unwrap_or(*date));
+                    let due_at = task::Column::DueAt;
+                    condition.add(match comparison {
+                        Comparison::Lt => due_at.lt(day_start),
+                        Comparison::Le => due_at.lt(next_day_start),
+                        Comparison::Eq => due_at.gte(day_start).and(due_at.lt(next_day_start)),
+                        Comparison::Ge => due_at.gte(day_start),
+                        Comparison::Gt => due_at.gte(next_day_start),
+                    })
+                }
+                TermKind::Is(state) => match (state, ctx.done_task_group_id) {
+                    (TaskState::Open, Some(done_task_group_id)) => {
+                        condition.add(task::Column::TaskGroupId.ne(done_task_group_id))
+                    }
+                    (TaskState::Done, Some(done_task_group_id)) => {
+                        condition.add(task::Column::TaskGroupId.eq(done_task_group_id))
+                    }
+                    // Without done task group all tasks are open
+                    (TaskState::Open, None) => condition,
+                    (TaskState::Done, None) => condition.add(Expr::cust("FALSE")),
+                },
+                TermKind::AssigneeMe => condition,
+                TermKind::Text(text) => {
+                    let pattern = format!("%{}%", escape_like(text));
+                    condition.add(
+                        Condition::any()
+                            .add(Expr::cust_with_values(
+                                r#""task"."title" ILIKE $1"#,
+                                vec![pattern.clone()],
+                            ))
+                            .add(Expr::cust_with_values(
+                                r#""task"."description" ILIKE $1"#,
+                                vec![pattern],
+                            )),
+                    )
+                }
+            };
+        }
+
+        Ok(condition)
+    }
+}
+
+struct RawToken {
+    text: String,
+    position: usize,
+    field: Option<String>,
+    value: String,
+}
+
+fn tokenize(input: &str) -> Result<Vec<RawToken>, FilterError> {
+    let mut tokens = vec![];
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut end = start;
+        let mut field = None;
+        let mut value = String::new();
+        let mut in_quotes = false;
+
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() && !in_quotes {
+                break;
+            }
+            chars.next();
+            end = i + c.len_utf8();
+
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ':' if !in_quotes && field.is_none() => field = Some(std::mem::take(&mut value)),
+                _ => value.push(c),
+            }
+        }
+
+        let text = &input[start..end];
+        let position = input[..start].chars().count() + 1;
+        if in_quotes {
+            return Err(FilterError::new("missing closing quote", text, position));
+        }
+
+        tokens.push(RawToken {
+            text: text.into(),
+            position,
+            field,
+            value,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn parse_term(raw_token: RawToken) -> Result<Term, FilterError> {
+    let err = |message: &str| FilterError::new(message, &raw_token.text, raw_token.position);
+
+    if raw_token.value.is_empty() {
+        return Err(err("missing value"));
+    }
+
+    let kind = match raw_token.field.as_deref().map(|f| f.to_lowercase()) {
+        None => TermKind::Text(raw_token.value.clone()),
+        Some(field) => match field.as_str() {
+            "label" => TermKind::Label(raw_token.value.clone()),
+            "group" => TermKind::Group(raw_token.value.clone()),
+            "due" => {
+                let value = raw_token.value.as_str();
+                let (comparison, date) = if let Some(date) = value.strip_prefix("<=") {
+                    (Comparison::Le, date)
+                } else if let Some(date) = value.strip_prefix(">=") {
+                    (Comparison::Ge, date)
+                } else if let Some(date) = value.strip_prefix('<') {
+                    (Comparison::Lt, date)
+                } else if let Some(date) = value.strip_prefix('>') {
+                    (Comparison::Gt, date)
+                } else if let Some(date) = value.strip_prefix('=') {
+                    (Comparison::Eq, date)
+                } else {
+                    (Comparison::Eq, value)
+                };
+
+                match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    Ok(date) => TermKind::Due(comparison, date),
+                    Err(_) => return Err(err("invalid date, expected YYYY-MM-DD")),
+                }
+            }
+            "is" => match raw_token.value.to_lowercase().as_str() {
+                "open" => TermKind::Is(TaskState::Open),
+                "done" => TermKind::Is(TaskState::Done),
+                _ => return Err(err("unknown state, expected open or done")),
+            },
+            "assignee" => match raw_token.value.to_lowercase().as_str() {
+                "me" => TermKind::AssigneeMe,
+                _ => return Err(err("only assignee:me is supported")),
+            },
+            "" => return Err(err("missing field name")),
+            _ => return Err(err("unknown field")),
+        },
+    };
+
+    Ok(Term {
+        kind,
+        token: raw_token.text,
+        position: raw_token.position,
+    })
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    DateTime::<Utc>::from_utc(date.and_hms_opt(0, 0, 0).unwrap(), Utc)
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DbBackend, EntityTrait, QueryFilter, QueryTrait};
+
+    use backend::entities::prelude::Task;
+
+    use super::*;
+
+    fn parse_kinds(input: &str) -> Vec<TermKind> {
+        Filter::parse(input)
+            .unwrap()
+            .terms
+            .into_iter()
+            .map(|term| term.kind)
+            .collect()
+    }
+
+    fn parse_err(input: &str) -> FilterError {
+        Filter::parse(input).unwrap_err()
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    fn tasks_sql(input: &str, done_task_group_id: Option<i32>) -> String {
+        let condition = Filter::parse(input)
+            .unwrap()
+            .to_condition(&FilterContext {
+                task_groups: &[],
+                labels: &[],
+                done_task_group_id,
+            })
+            .unwrap();
+        Task::find()
+            .filter(condition)
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn quoted_values_may_contain_whitespace_and_colons() {
+        let filter = Filter::parse(r#"group:"In progress" "fix: login""#).unwrap();
+        assert_eq!(
+            filter.terms,
+            vec![
+                Term {
+                    kind: TermKind::Group("In progress".into()),
+                    token: r#"group:"In progress""#.into(),
+                    position: 1,
+                },
+                Term {
+                    kind: TermKind::Text("fix: login".into()),
+                    token: r#""fix: login""#.into(),
+                    position: 21,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fields_are_case_insensitive() {
+        assert_eq!(
+            parse_kinds("LABEL:Bug Is:Open ASSIGNEE:Me"),
+            vec![
+                TermKind::Label("Bug".into()),
+                TermKind::Is(TaskState::Open),
+                TermKind::AssigneeMe,
+            ]
+        );
+    }
+
+    #[test]
+    fn due_dates_are_compared_by_operator() {
+        let filter = "due:<2026-11-01 due:<=2026-11-02 due:=2026-11-03 \
+                      due:2026-11-04 due:>=2026-11-05 due:>2026-11-06";
+        assert_eq!(
+            parse_kinds(filter),
+            vec![
+                TermKind::Due(Comparison::Lt, date("2026-11-01")),
+                TermKind::Due(Comparison::Le, date("2026-11-02")),
+                TermKind::Due(Comparison::Eq, date("2026-11-03")),
+                TermKind::Due(Comparison::Eq, date("2026-11-04")),
+                TermKind::Due(Comparison::Ge, date("2026-11-05")),
+                TermKind::Due(Comparison::Gt, date("2026-11-06")),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected_at_their_token() {
+        assert_eq!(
+            parse_err("label:bug due:<2026-13-01"),
+            FilterError::new("invalid date, expected YYYY-MM-DD", "due:<2026-13-01", 11)
+        );
+        assert_eq!(
+            parse_err("due:<=tomorrow").message,
+            "invalid date, expected YYYY-MM-DD"
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        assert_eq!(
+            parse_err(r#"bug group:"In progress"#),
+            FilterError::new("missing closing quote", r#"group:"In progress"#, 5)
+        );
+        assert_eq!(
+            parse_err("bug owner:me"),
+            FilterError::new("unknown field", "owner:me", 5)
+        );
+        assert_eq!(
+            parse_err("bug label:"),
+            FilterError::new("missing value", "label:", 5)
+        );
+        assert_eq!(
+            parse_err(":bug"),
+            FilterError::new("missing field name", ":bug", 1)
+        );
+        assert_eq!(
+            parse_err("is:closed"),
+            FilterError::new("unknown state, expected open or done", "is:closed", 1)
+        );
+        assert_eq!(
+            parse_err("assignee:someone"),
+            FilterError::new("only assignee:me is supported", "assignee:someone", 1)
+        );
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(parse_err("größe  due:x").position, 8);
+    }
+
+    #[test]
+    fn task_state_depends_on_the_done_task_group() {
+        assert!(tasks_sql("is:done", Some(3)).contains(r#""task"."task_group_id" = 3"#));
+        assert!(tasks_sql("is:open", Some(3)).contains(r#""task"."task_group_id" <> 3"#));
+        // Without done task group all tasks are open
+        assert!(!tasks_sql("is:open", None).contains("WHERE"));
+        assert!(tasks_sql("is:done", None).contains("FALSE"));
+    }
+
+    #[test]
+    fn assignee_me_matches_all_tasks() {
+        assert!(!tasks_sql("assignee:me", None).contains("WHERE"));
+    }
+}
@@ -0,0 +1,562 @@
+use rocket::{
+    data::{Data, ToByteUnit},
+    http::Status,
+    serde::{json::Json, Serialize},
+    State,
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue, ColumnTrait, DbErr, EntityTrait,
+    QueryFilter, TransactionTrait,
+};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use backend::{
+    current_timestamp,
+    entities::{prelude::Task, task, task_history},
+    establish_db_connection,
+    services::task_keys::allocate_task_numbers,
+};
+
+use crate::{
+    events::bus::{EventBus, EventTask, WorkspaceEventKind},
+    routes::lib::{
+        validate_len, AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, SuccessResponse,
+    },
+};
+
+use super::lib::{
+    csv_label_name, find_workspace_tasks, parse_due_at, unescape_csv_cell, WorkspaceTasks,
+    CSV_LABELS_SEPARATOR, HISTORY_MOVED,
+};
+
+const CSV_LIMIT_MIB: usize = 5;
+
+/// Creates tasks from rows without `id` and updates tasks of the rows with `id`,
+/// columns are the same as in `GET /workspace/<id>/tasks.csv`, `key` and `status` are ignored.
+/// The `'` prefix of cells escaped by the export is removed.
+///
+/// Rows with errors are skipped and reported, the other rows are saved in a single transaction.
+/// Without the `labels` or `due_at` column labels or due dates of updated tasks are kept.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    request_body(content = String, content_type = "text/csv"),
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Saved and skipped rows", body = ImportedTasksResponse),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[post("/workspace/<workspace_id>/tasks.csv", data = "<data>")]
+pub async fn handler(
+    workspace_id: i32,
+    data: Data<'_>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey<'_>,
+    event_bus: &State<EventBus>,
+) -> Result<Idempotent<Json<SuccessResponse<ImportedTasks>>>, ErrorResponse> {
+    let read_res = data.open(CSV_LIMIT_MIB.mebibytes()).into_string().await;
+    let content = match read_res {
+        Ok(content) if content.is_complete() => content.into_inner(),
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!("CSV can not be larger than {} MiB", CSV_LIMIT_MIB)),
+                Status::PayloadTooLarge,
+            ))
+        }
+        Err(err) => return Err(ErrorResponse::body_read_error(err)),
+    };
+
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &content).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let workspace_tasks = match find_workspace_tasks(&db, user.user_id, workspace_id).await {
+        Ok(Some(workspace_tasks)) => workspace_tasks,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let columns = match reader.headers() {
+        Ok(headers) => CsvColumns::new(headers)?,
+        Err(err) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Header of the CSV is not valid: {}", err)),
+                Status::UnprocessableEntity,
+            ))
+        }
+    };
+
+    let mut changes: Vec<TaskChange> = vec![];
+    let mut errors: Vec<RowError> = vec![];
+    let mut unchanged = 0;
+    let mut updated_rows: HashMap<i32, u64> = HashMap::new();
+    for record_res in reader.records() {
+        let record = match record_res {
+            Ok(record) => record,
+            Err(err) => {
+                errors.push(RowError {
+                    row: err.position().map(|p| p.line()).unwrap_or_default(),
+                    error: format!("Row is not valid CSV: {}", err),
+                });
+                continue;
+            }
+        };
+        let row = record.position().map(|p| p.line()).unwrap_or_default();
+
+        match columns.parse_row(&record, &workspace_tasks) {
+            Ok(TaskChange::Update { task_model, .. })
+                if updated_rows.contains_key(&task_model.id) =>
+            {
+                errors.push(RowError {
+                    row,
+                    error: format!(
+                        "Task with id {} is already updated by row {}",
+                        task_model.id, updated_rows[&task_model.id]
+                    ),
+                });
+            }
+            Ok(change) => {
+                if let TaskChange::Update { task_model, .. } = &change {
+                    updated_rows.insert(task_model.id, row);
+                }
+                match change.is_unchanged() {
+                    true => unchanged += 1,
+                    false => changes.push(change),
+                }
+            }
+            Err(error) => errors.push(RowError { row, error }),
+        }
+    }
+
+    let user_id = user.user_id;
+    let event_bus = event_bus.inner().clone();
+    let key_prefix = workspace_tasks.workspace.key_prefix.clone();
+    let tx_res = db
+        .transaction::<_, (usize, usize, Vec<RowError>), DbErr>(|tx| {
+            Box::pin(async move {
+                let created_count = changes
+                    .iter()
+                    .filter(|change| matches!(change, TaskChange::Create { .. }))
+                    .count() as i32;
+                let mut next_number = match created_count {
+                    0 => 0,
+                    _ => allocate_task_numbers(tx, workspace_id, created_count).await?,
+                };
+
+                let mut saved_changes: Vec<SavedChange> = vec![];
+                let mut conflicts: Vec<RowError> = vec![];
+                for change in changes.into_iter() {
+                    match change {
+                        TaskChange::Create {
+                            task_group_id,
+                            title,
+                            description,
+                            labels_ids,
+                            due_at,
+                        } => {
+                            let inserted_task = task::ActiveModel {
+                                number: ActiveValue::Set(next_number),
+                                user_id: ActiveValue::Set(user_id),
+                                task_group_id: ActiveValue::Set(task_group_id),
+                                title: ActiveValue::Set(title),
+                                description: ActiveValue::Set(description),
+                                labels_ids: ActiveValue::Set(
+                                    labels_ids.filter(|li| !li.is_empty()),
+                                ),
+                                due_at: ActiveValue::Set(due_at.flatten()),
+                                ..Default::default()
+                            }
+                            .insert(tx)
+                            .await?;
+                            next_number += 1;
+                            saved_changes.push(SavedChange::Created(inserted_task));
+                        }
+                        TaskChange::Update {
+                            task_model,
+                            task_group_id,
+                            title,
+                            description,
+                            labels_ids,
+                            due_at,
+                        } => {
+                            let task_id = task_model.id;
+                            let version = task_model.version;
+                            let from_task_group_id = task_model.task_group_id;
+                            let is_modified = title != task_model.title
+                                || description != task_model.description
+                                || labels_ids
+                                    .as_ref()
+                                    .is_some_and(|li| !has_labels(&task_model, li))
+                                || due_at.is_some_and(|due_at| due_at != task_model.due_at);
+
+                            let mut task_to_update: task::ActiveModel = task_model.into();
+                            task_to_update.task_group_id = ActiveValue::Set(task_group_id);
+                            task_to_update.title = ActiveValue::Set(title);
+                            task_to_update.description = ActiveValue::Set(description);
+                            if let Some(labels_ids) = labels_ids {
+                                // Empty cell removes all labels
+                                task_to_update.labels_ids = ActiveValue::Set(
+                                    (!labels_ids.is_empty()).then_some(labels_ids),
+                                );
+                            }
+                            if let Some(due_at) = due_at {
+                                task_to_update.due_at = ActiveValue::Set(due_at);
+                            }
+                            let update_res = Task::update(task_to_update)
+                                .filter(task::Column::Version.eq(version))
+                                .exec(tx)
+                                .await;
+                            let updated_task = match update_res {
+                                Ok(updated_task) => updated_task,
+                                // Task was changed by another request since it was selected
+                                Err(DbErr::RecordNotFound(_)) => {
+                                    conflicts.push(RowError {
+                                        row: updated_rows[&task_id],
+                                        error: format!(
+                                            "Task with id {} was changed by another request",
+                                            task_id
+                                        ),
+                                    });
+                                    continue;
+                                }
+                                Err(err) => return Err(err),
+                            };
+
+                            if updated_task.task_group_id != from_task_group_id {
+                                task_history::ActiveModel {
+                                    task_id: ActiveValue::Set(updated_task.id),
+                                    action: ActiveValue::Set(HISTORY_MOVED.into()),
+                                    from_task_group_id: ActiveValue::Set(Some(from_task_group_id)),
+                                    to_task_group_id: ActiveValue::Set(Some(
+                                        updated_task.task_group_id,
+                                    )),
+                                    created_at: ActiveValue::Set(current_timestamp()),
+                                    ..Default::default()
+                                }
+                                .insert(tx)
+                                .await?;
+                            }
+                            saved_changes.push(SavedChange::Updated {
+                                task_model: updated_task,
+                                from_task_group_id,
+                                is_modified,
+                            });
+                        }
+                    }
+                }
+
+                // Events are published in the transaction, after all changes were saved
+                let mut created = 0;
+                let mut updated = 0;
+                for saved_change in saved_changes.into_iter() {
+                    match saved_change {
+                        SavedChange::Created(task_model) => {
+                            created += 1;
+                            event_bus
+                                .publish(
+                                    tx,
+                                    workspace_id,
+                                    WorkspaceEventKind::TaskCreated(EventTask::new(
+                                        task_model,
+                                        &key_prefix,
+                                    )),
+                                )
+                                .await?;
+                        }
+                        SavedChange::Updated {
+                            task_model,
+                            from_task_group_id,
+                            is_modified,
+                        } => {
+                            updated += 1;
+                            if task_model.task_group_id != from_task_group_id {
+                                event_bus
+                                    .publish(
+                                        tx,
+                                        workspace_id,
+                                        WorkspaceEventKind::TaskMoved {
+                                            id: task_model.id,
+                                            from_task_group_id,
+                                            to_task_group_id: task_model.task_group_id,
+                                        },
+                                    )
+                                    .await?;
+                            }
+                            if is_modified {
+                                event_bus
+                                    .publish(
+                                        tx,
+                                        workspace_id,
+                                        WorkspaceEventKind::TaskUpdated(EventTask::new(
+                                            task_model,
+                                            &key_prefix,
+                                        )),
+                                    )
+                                    .await?;
+                            }
+                        }
+                    }
+                }
+                Ok((created, updated, conflicts))
+            })
+        })
+        .await;
+
+    let (created, updated) = match tx_res {
+        Ok((created, updated, conflicts)) => {
+            errors.extend(conflicts);
+            errors.sort_by_key(|row_error| row_error.row);
+            (created, updated)
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    Ok(Idempotent::Created(Json(SuccessResponse::new(
+        ImportedTasks {
+            created,
+            updated,
+            unchanged,
+            errors,
+        },
+    ))))
+}
+
+/// Positions of the imported columns in the header
+struct CsvColumns {
+    id: usize,
+    group: usize,
+    title: usize,
+    description: usize,
+    labels: Option<usize>,
+    due_at: Option<usize>,
+}
+
+impl CsvColumns {
+    fn new(headers: &csv::StringRecord) -> Result<CsvColumns, ErrorResponse> {
+        let position = |column: &str| {
+            headers
+                .iter()
+                .position(|header| header.eq_ignore_ascii_case(column))
+        };
+        let required_position = |column: &str| {
+            position(column).ok_or_else(|| {
+                ErrorResponse::new(
+                    Some(format!("CSV does not have the {} column", column)),
+                    Status::UnprocessableEntity,
+                )
+            })
+        };
+
+        Ok(CsvColumns {
+            id: required_position("id")?,
+            group: required_position("group")?,
+            title: required_position("title")?,
+            description: required_position("description")?,
+            labels: position("labels"),
+            due_at: position("due_at"),
+        })
+    }
+
+    /// Validates the row the same way as inserting or updating a single task
+    fn parse_row(
+        &self,
+        record: &csv::StringRecord,
+        workspace_tasks: &WorkspaceTasks,
+    ) -> Result<TaskChange, String> {
+        let cell = |index: usize| unescape_csv_cell(record.get(index).unwrap_or_default());
+
+        let task_model = match cell(self.id) {
+            "" => None,
+            id => {
+                let task_id = id
+                    .parse::<i32>()
+                    .map_err(|_| format!("Id {} is not a number", id))?;
+                let task_model = workspace_tasks
+                    .tasks
+                    .iter()
+                    .find(|task_model| task_model.id == task_id)
+                    .ok_or_else(|| {
+                        format!("Task with id {} does not exist in the workspace", task_id)
+                    })?;
+                Some(task_model.clone())
+            }
+        };
+
+        let task_group_id = match (cell(self.group), &task_model) {
+            ("", Some(task_model)) => task_model.task_group_id,
+            ("", None) => return Err("Group is required for new tasks".into()),
+            (group, _) => workspace_tasks
+                .task_groups
+                .iter()
+                .find(|tg| tg.title.eq_ignore_ascii_case(group))
+                .map(|tg| tg.id)
+                .ok_or_else(|| format!("Task group {} does not exist", group))?,
+        };
+
+        let title = cell(self.title).to_string();
+        if let Some(err) = validate_len(&title, 1, 50, "Title") {
+            return Err(err.message().into());
+        }
+        let description = cell(self.description).to_string();
+        if let Some(err) = validate_len(&description, 1, 255, "Description") {
+            return Err(err.message().into());
+        }
+
+        let labels_ids = match self.labels {
+            Some(index) => Some(parse_labels(cell(index), workspace_tasks)?),
+            None => None,
+        };
+        let due_at = match self.due_at {
+            Some(index) => {
+                Some(parse_due_at(cell(index)).map_err(|err| err.message().to_string())?)
+            }
+            None => None,
+        };
+
+        Ok(match task_model {
+            Some(task_model) => TaskChange::Update {
+                task_model,
+                task_group_id,
+                title,
+                description,
+                labels_ids,
+                due_at,
+            },
+            None => TaskChange::Create {
+                task_group_id,
+                title,
+                description,
+                labels_ids,
+                due_at,
+            },
+        })
+    }
+}
+
+/// Finds labels of the workspace by the names used in the export
+fn parse_labels(cell: &str, workspace_tasks: &WorkspaceTasks) -> Result<Vec<i32>, String> {
+    let mut labels_ids: Vec<i32> = vec![];
+    let mut missing_labels: Vec<&str> = vec![];
+    for label_name in cell
+        .split(CSV_LABELS_SEPARATOR)
+        .map(str::trim)
+        .filter(|label_name| !label_name.is_empty())
+    {
+        let found_label = workspace_tasks
+            .labels
+            .iter()
+            .find(|l| csv_label_name(l).eq_ignore_ascii_case(label_name));
+        match found_label {
+            Some(label_model) if !labels_ids.contains(&label_model.id) => {
+                labels_ids.push(label_model.id)
+            }
+            Some(_) => (),
+            None => missing_labels.push(label_name),
+        }
+    }
+
+    match missing_labels.is_empty() {
+        true => Ok(labels_ids),
+        false => Err(format!("Labels {} do not exist", missing_labels.join(", "))),
+    }
+}
+
+enum TaskChange {
+    Create {
+        task_group_id: i32,
+        title: String,
+        description: String,
+        labels_ids: Option<Vec<i32>>,
+        due_at: Option<Option<DateTimeWithTimeZone>>,
+    },
+    Update {
+        task_model: task::Model,
+        task_group_id: i32,
+        title: String,
+        description: String,
+        /// `None` keeps the labels
+        labels_ids: Option<Vec<i32>>,
+        /// `None` keeps the due date
+        due_at: Option<Option<DateTimeWithTimeZone>>,
+    },
+}
+
+impl TaskChange {
+    /// Rows of an exported CSV which were not edited do not touch their tasks
+    fn is_unchanged(&self) -> bool {
+        match self {
+            TaskChange::Create { .. } => false,
+            TaskChange::Update {
+                task_model,
+                task_group_id,
+                title,
+                description,
+                labels_ids,
+                due_at,
+            } => {
+                task_group_id == &task_model.task_group_id
+                    && title == &task_model.title
+                    && description == &task_model.description
+                    && labels_ids
+                        .as_ref()
+                        .is_none_or(|li| has_labels(task_model, li))
+                    && due_at.is_none_or(|due_at| due_at == task_model.due_at)
+            }
+        }
+    }
+}
+
+/// Task has exactly these labels, in any order
+fn has_labels(task_model: &task::Model, labels_ids: &[i32]) -> bool {
+    let task_labels_ids = task_model.labels_ids.as_deref().unwrap_or_default();
+    task_labels_ids.len() == labels_ids.len()
+        && labels_ids
+            .iter()
+            .all(|label_id| task_labels_ids.contains(label_id))
+}
+
+enum SavedChange {
+    Created(task::Model),
+    Updated {
+        task_model: task::Model,
+        from_task_group_id: i32,
+        /// Title, description, labels or due date were changed, not only the task group
+        is_modified: bool,
+    },
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportedTasks {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub errors: Vec<RowError>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct RowError {
+    /// Line of the row in the CSV, the header is on line 1
+    pub row: u64,
+    pub error: String,
+}
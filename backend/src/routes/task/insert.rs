@@ -1,35 +1,73 @@
+use async_graphql::InputObject;
 use rocket::{
     http::Status,
     serde::{json::Json, Deserialize, Serialize},
+    State,
 };
+use utoipa::ToSchema;
 
 use backend::{
-    entities::{
-        label,
-        prelude::{Label, TaskGroup},
-        task,
-    },
+    entities::prelude::{TaskGroup, Workspace},
     establish_db_connection,
+    services::task_keys::task_key,
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, EntityTrait, TransactionTrait};
+
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{
+        AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, VersionedResponse,
+    },
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Condition};
 
-use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+use super::lib::{find_missing_labels_ids, insert_task, TaskToInsert};
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    request_body = NewTask,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Created task", body = InsertedTaskResponse,
+            headers(("ETag" = String, description = "Version of the task"))),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[post("/task", data = "<data>")]
 pub async fn handler(
     data: Json<NewTask>,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<NewTask>>, ErrorResponse> {
+    idempotency_key: IdempotencyKey<'_>,
+    event_bus: &State<EventBus>,
+) -> Result<Idempotent<VersionedResponse<InsertedTask>>, ErrorResponse> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    create(data, user, event_bus).await.map(Idempotent::Created)
+}
+
+/// Creates the task, also run by the `createTask` mutation
+pub async fn create(
+    data: Json<NewTask>,
+    user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<InsertedTask>, ErrorResponse> {
     let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
     let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(server_err_response);
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
     }
     let db = db_res.unwrap();
     // Validate task group id
-    let select_existing_task_group_res = TaskGroup::find_by_id(data.task_group_id).one(&db).await;
-    if let Err(_) = select_existing_task_group_res {
-        return Err(server_err_response);
+    let select_existing_task_group_res = TaskGroup::find_by_id(data.task_group_id)
+        .find_also_related(Workspace)
+        .one(&db)
+        .await;
+    if let Err(err) = &select_existing_task_group_res {
+        return Err(server_err_response.with_source(err));
     }
     let existing_task_group = select_existing_task_group_res.unwrap();
     let not_found_err_msg = ErrorResponse::new(
@@ -40,96 +78,91 @@ pub async fn handler(
         Status::NotFound,
     );
     match existing_task_group {
-        Some(task_group) => {
-            if task_group.user_id != user.user_id {
+        Some((task_group, Some(workspace))) => {
+            if task_group.user_id != user.user_id || task_group.deleted_at.is_some() {
                 return Err(not_found_err_msg);
             }
 
-            // Validate lengths
-            let title_len_err = validate_len(&data.title, 1, 50, "Title");
-            if let Some(err) = title_len_err {
-                return Err(err);
-            }
-            let desc_len_err = validate_len(&data.description, 1, 255, "Description");
-            if let Some(err) = desc_len_err {
-                return Err(err);
-            }
-
             // Validate labels ids
-            match &data.labels_ids {
-                Some(li) => {
-                    if li.len() > 0 {
-                        let select_labels_stmt = Label::find()
-                            .filter(label::Column::UserId.eq(user.user_id))
-                            .filter(label::Column::WorkspaceId.eq(task_group.workspace_id));
-                        let mut select_labels_condition = Condition::any();
-                        for label_id in li.iter() {
-                            select_labels_condition = select_labels_condition.add(
-                                label::Column::Id.eq(*label_id)
-                            );
-                        }
-                        let select_labels_res = select_labels_stmt.filter(select_labels_condition).all(&db).await;
-                        if let Err(_) = select_labels_res {
-                            return Err(server_err_response);
-                        }
-                        let selected_labels = select_labels_res.unwrap();
-                        let mut not_found_labels_ids = String::from("");
-                        for label_id in li.iter() {
-                            if selected_labels.iter().all(|sl| &sl.id != label_id) {
-                                not_found_labels_ids.push_str(&format!("{}, ", label_id));
-                            }
-                        }
-                        if not_found_labels_ids.len() > 0 {
-                            return Err(ErrorResponse::new(
-                                Some(format!(
-                                    "Could not create task with labels ids {}labels do not exist",
-                                    not_found_labels_ids
-                                )),
-                                Status::Conflict,
-                            ));
-                        }
-                    }
+            if let Some(li) = &data.labels_ids {
+                let missing_labels_ids_res =
+                    find_missing_labels_ids(&db, user.user_id, task_group.workspace_id, li).await;
+                if let Err(err) = &missing_labels_ids_res {
+                    return Err(server_err_response.with_source(err));
                 }
-                None => (),
-            }
-
-            let mut task_to_insert = task::ActiveModel {
-                user_id: sea_orm::ActiveValue::Set(user.user_id),
-                task_group_id: sea_orm::ActiveValue::Set(data.task_group_id),
-                title: sea_orm::ActiveValue::Set(data.title.clone()),
-                description: sea_orm::ActiveValue::Set(data.description.clone()),
-                ..Default::default()
-            };
-
-            if let Some(labels_ids) = &data.labels_ids {
-                if labels_ids.len() > 0 {
-                    // Insert only if provided, if not leave as default (None)
-                    task_to_insert.labels_ids = sea_orm::ActiveValue::Set(data.labels_ids.clone())
+                let missing_labels_ids = missing_labels_ids_res.unwrap();
+                if !missing_labels_ids.is_empty() {
+                    let mut not_found_labels_ids = String::from("");
+                    for label_id in missing_labels_ids.iter() {
+                        not_found_labels_ids.push_str(&format!("{}, ", label_id));
+                    }
+                    return Err(ErrorResponse::new(
+                        Some(format!(
+                            "Could not create task with labels ids {}labels do not exist",
+                            not_found_labels_ids
+                        )),
+                        Status::Conflict,
+                    ));
                 }
             }
 
-            let insert_res = task_to_insert.save(&db).await;
+            // Number is taken from the workspace counter in the transaction of the insert
+            let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+            let inserted_task = insert_task(
+                &tx,
+                event_bus,
+                user.user_id,
+                &workspace,
+                TaskToInsert {
+                    task_group_id: data.task_group_id,
+                    title: data.title.clone(),
+                    description: data.description.clone(),
+                    labels_ids: data.labels_ids.clone().unwrap_or_default(),
+                    due_at: data.due_at.clone(),
+                },
+            )
+            .await?;
+            tx.commit().await.map_err(ErrorResponse::server_error)?;
 
-            if let Err(_) = insert_res {
-                return Err(server_err_response);
-            }
-            let inserted_task = insert_res.unwrap();
-            Ok(Json(SuccessResponse::new(NewTask {
-                task_group_id: inserted_task.task_group_id.unwrap(),
-                title: inserted_task.title.unwrap(),
-                description: inserted_task.description.unwrap(),
-                labels_ids: inserted_task.labels_ids.unwrap(),
-            })))
+            Ok(VersionedResponse::new(
+                InsertedTask {
+                    id: inserted_task.id,
+                    key: task_key(&workspace.key_prefix, inserted_task.number),
+                    task_group_id: inserted_task.task_group_id,
+                    title: inserted_task.title,
+                    description: inserted_task.description,
+                    labels_ids: inserted_task.labels_ids,
+                    due_at: inserted_task.due_at,
+                    version: inserted_task.version,
+                },
+                inserted_task.version,
+            ))
         }
-        None => Err(not_found_err_msg),
+        _ => Err(not_found_err_msg),
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct NewTask {
     pub task_group_id: i32,
     pub title: String,
     pub description: String,
     pub labels_ids: Option<Vec<i32>>,
+    /// Date (2026-11-01) or RFC 3339 date and time
+    pub due_at: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct InsertedTask {
+    pub id: i32,
+    pub key: String,
+    pub task_group_id: i32,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Option<Vec<i32>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub version: i32,
 }
@@ -0,0 +1,512 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rocket::http::Status;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue, ColumnTrait, Condition,
+    ConnectionTrait, DbConn, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+use std::borrow::Cow;
+
+use backend::{
+    current_timestamp,
+    entities::{
+        label,
+        prelude::{GitIntegration, Label, Task, TaskGroup, Workspace},
+        task, task_group, task_history, workspace,
+    },
+    services::task_keys::allocate_task_numbers,
+};
+
+use crate::{
+    events::bus::{EventBus, EventTask, WorkspaceEventKind},
+    routes::{
+        lib::{validate_len, ErrorResponse, ExpectedVersion},
+        task::update::UpdatedTask,
+    },
+};
+
+/// Ids from `labels_ids` which are not ids of user's labels in the workspace,
+/// labels in trash can not be assigned to tasks either
+pub async fn find_missing_labels_ids<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    workspace_id: i32,
+    labels_ids: &[i32],
+) -> Result<Vec<i32>, DbErr> {
+    if labels_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut select_labels_condition = Condition::any();
+    for label_id in labels_ids.iter() {
+        select_labels_condition = select_labels_condition.add(label::Column::Id.eq(*label_id));
+    }
+    let selected_labels = Label::find()
+        .filter(label::Column::UserId.eq(user_id))
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .filter(label::Column::DeletedAt.is_null())
+        .filter(select_labels_condition)
+        .all(db)
+        .await?;
+
+    Ok(labels_ids
+        .iter()
+        .filter(|label_id| selected_labels.iter().all(|sl| &sl.id != *label_id))
+        .copied()
+        .collect())
+}
+
+/// Parses due date of a task, either RFC 3339 date and time or a date which is due
+/// at midnight UTC, empty value removes the due date
+pub fn parse_due_at(due_at: &str) -> Result<Option<DateTimeWithTimeZone>, ErrorResponse> {
+    if due_at.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(due_at) {
+        return Ok(Some(date_time));
+    }
+    match NaiveDate::parse_from_str(due_at, "%Y-%m-%d") {
+        Ok(date) => Ok(Some(
+            DateTime::<Utc>::from_utc(date.and_hms_opt(0, 0, 0).unwrap(), Utc).into(),
+        )),
+        Err(_) => Err(ErrorResponse::new(
+            Some(format!(
+                "Due date {} is neither a date (2026-11-01) nor an RFC 3339 date and time",
+                due_at
+            )),
+            Status::UnprocessableEntity,
+        )),
+    }
+}
+
+/// Tasks of trashed task groups are not accessible
+pub async fn find_task(
+    db: &DbConn,
+    task_id: i32,
+    user_id: i32,
+) -> Result<Option<(task::Model, Option<task_group::Model>)>, DbErr> {
+    Task::find_by_id(task_id)
+        .filter(task::Column::UserId.eq(user_id))
+        .find_also_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .one(db)
+        .await
+}
+
+/// Conflict response with the current task, its key needs the prefix of the workspace
+pub async fn task_conflict_response(
+    db: &DbConn,
+    expected_version: &ExpectedVersion,
+    task_model: task::Model,
+    workspace_id: i32,
+) -> ErrorResponse {
+    match Workspace::find_by_id(workspace_id).one(db).await {
+        Ok(Some(workspace_model)) => {
+            let version = task_model.version;
+            expected_version.conflict_response(
+                &UpdatedTask::new(task_model, &workspace_model.key_prefix),
+                version,
+            )
+        }
+        Err(err) => ErrorResponse::new(None, Status::InternalServerError).with_source(err),
+        _ => ErrorResponse::new(None, Status::InternalServerError),
+    }
+}
+
+/// Commit referencing the task was linked to it
+pub const HISTORY_COMMIT_LINKED: &str = "commit_linked";
+/// Task was moved to another task group
+pub const HISTORY_MOVED: &str = "moved";
+
+/// Task which is inserted, the caller checks its task group and labels
+pub struct TaskToInsert {
+    pub task_group_id: i32,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Vec<i32>,
+    pub due_at: Option<String>,
+}
+
+/// Changes of a task, fields which are `None` are kept. The caller checks the labels,
+/// task group is checked here as the task can only be moved within its workspace.
+pub struct TaskChanges {
+    pub task_group_id: Option<i32>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Empty list removes all labels
+    pub labels_ids: Option<Vec<i32>>,
+    /// Empty string removes the due date
+    pub due_at: Option<String>,
+}
+
+/// Inserts the task into a task group of `workspace_model`, its number is taken
+/// from the workspace counter. Used by `POST /task` and batches.
+pub async fn insert_task<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    user_id: i32,
+    workspace_model: &workspace::Model,
+    data: TaskToInsert,
+) -> Result<task::Model, ErrorResponse> {
+    if let Some(err) = validate_len(&data.title, 1, 50, "Title") {
+        return Err(err);
+    }
+    if let Some(err) = validate_len(&data.description, 1, 255, "Description") {
+        return Err(err);
+    }
+    let due_at = match &data.due_at {
+        Some(due_at) => parse_due_at(due_at)?,
+        None => None,
+    };
+
+    let number = allocate_task_numbers(db, workspace_model.id, 1)
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    let inserted_task = task::ActiveModel {
+        number: ActiveValue::Set(number),
+        user_id: ActiveValue::Set(user_id),
+        task_group_id: ActiveValue::Set(data.task_group_id),
+        title: ActiveValue::Set(data.title),
+        description: ActiveValue::Set(data.description),
+        labels_ids: ActiveValue::Set((!data.labels_ids.is_empty()).then_some(data.labels_ids)),
+        due_at: ActiveValue::Set(due_at),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(ErrorResponse::server_error)?;
+
+    event_bus
+        .publish(
+            db,
+            workspace_model.id,
+            WorkspaceEventKind::TaskCreated(EventTask::new(
+                inserted_task.clone(),
+                &workspace_model.key_prefix,
+            )),
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(inserted_task)
+}
+
+/// Updates the task selected as `task_model`, moving it to another task group
+/// is recorded in its history. Publishes the move and the change separately.
+///
+/// Returns `None` when the task was changed or deleted since it was selected,
+/// callers tell the two apart. Used by `PATCH /task/<id>` and batches.
+pub async fn update_task<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    task_model: task::Model,
+    workspace_model: &workspace::Model,
+    changes: TaskChanges,
+    expected_version: &ExpectedVersion,
+) -> Result<Option<task::Model>, ErrorResponse> {
+    if changes.task_group_id.is_none()
+        && changes.title.is_none()
+        && changes.description.is_none()
+        && changes.labels_ids.is_none()
+        && changes.due_at.is_none()
+    {
+        return Err(ErrorResponse::new(
+            Some(
+                "Either task_group_id, title, description, labels_ids or due_at has to be provided"
+                    .into(),
+            ),
+            Status::BadRequest,
+        ));
+    }
+    if let Some(title) = &changes.title {
+        if let Some(err) = validate_len(title, 1, 50, "Title") {
+            return Err(err);
+        }
+    }
+    if let Some(desc) = &changes.description {
+        if let Some(err) = validate_len(desc, 1, 255, "Description") {
+            return Err(err);
+        }
+    }
+    let due_at = match &changes.due_at {
+        Some(due_at) => Some(parse_due_at(due_at)?),
+        None => None,
+    };
+
+    let from_task_group_id = task_model.task_group_id;
+    // Task can only be moved within its workspace as labels belong to the workspace
+    if let Some(task_group_id) = changes.task_group_id {
+        if task_group_id != from_task_group_id {
+            let found_task_group = TaskGroup::find_by_id(task_group_id)
+                .filter(task_group::Column::UserId.eq(task_model.user_id))
+                .filter(task_group::Column::WorkspaceId.eq(workspace_model.id))
+                .filter(task_group::Column::DeletedAt.is_null())
+                .one(db)
+                .await
+                .map_err(ErrorResponse::server_error)?;
+            if found_task_group.is_none() {
+                return Err(ErrorResponse::new(
+                    Some(format!(
+                        "Could not move task to task group with id {}, task group does not exist",
+                        task_group_id
+                    )),
+                    Status::NotFound,
+                ));
+            }
+        }
+    }
+
+    let is_modified = changes.title.is_some()
+        || changes.description.is_some()
+        || changes.labels_ids.is_some()
+        || changes.due_at.is_some();
+    let version = task_model.version;
+    let mut task_to_update: task::ActiveModel = task_model.into();
+    if let Some(task_group_id) = changes.task_group_id {
+        task_to_update.task_group_id = ActiveValue::Set(task_group_id);
+    }
+    if let Some(title) = changes.title {
+        task_to_update.title = ActiveValue::Set(title);
+    }
+    if let Some(desc) = changes.description {
+        task_to_update.description = ActiveValue::Set(desc);
+    }
+    if let Some(labels_ids) = changes.labels_ids {
+        task_to_update.labels_ids =
+            ActiveValue::Set((!labels_ids.is_empty()).then_some(labels_ids));
+    }
+    if let Some(due_at) = due_at {
+        task_to_update.due_at = ActiveValue::Set(due_at);
+    }
+
+    let update_res = Task::update(task_to_update)
+        .filter(expected_version.condition(task::Column::Version, version))
+        .exec(db)
+        .await;
+    let updated_task = match update_res {
+        Ok(updated_task) => updated_task,
+        Err(DbErr::RecordNotFound(_)) => return Ok(None),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+
+    if updated_task.task_group_id != from_task_group_id {
+        task_history::ActiveModel {
+            task_id: ActiveValue::Set(updated_task.id),
+            action: ActiveValue::Set(HISTORY_MOVED.into()),
+            from_task_group_id: ActiveValue::Set(Some(from_task_group_id)),
+            to_task_group_id: ActiveValue::Set(Some(updated_task.task_group_id)),
+            created_at: ActiveValue::Set(current_timestamp()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(ErrorResponse::server_error)?;
+
+        event_bus
+            .publish(
+                db,
+                workspace_model.id,
+                WorkspaceEventKind::TaskMoved {
+                    id: updated_task.id,
+                    from_task_group_id,
+                    to_task_group_id: updated_task.task_group_id,
+                },
+            )
+            .await
+            .map_err(ErrorResponse::server_error)?;
+    }
+    if is_modified {
+        event_bus
+            .publish(
+                db,
+                workspace_model.id,
+                WorkspaceEventKind::TaskUpdated(EventTask::new(
+                    updated_task.clone(),
+                    &workspace_model.key_prefix,
+                )),
+            )
+            .await
+            .map_err(ErrorResponse::server_error)?;
+    }
+    Ok(Some(updated_task))
+}
+
+/// Deletes the task selected as `task_model` from a task group of the workspace.
+///
+/// Returns `false` when the task was changed or deleted since it was selected.
+/// Used by `DELETE /task/<id>` and batches.
+pub async fn delete_task<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    task_model: &task::Model,
+    workspace_id: i32,
+    expected_version: &ExpectedVersion,
+) -> Result<bool, ErrorResponse> {
+    let deleted_tasks = Task::delete_many()
+        .filter(task::Column::Id.eq(task_model.id))
+        .filter(expected_version.condition(task::Column::Version, task_model.version))
+        .exec(db)
+        .await
+        .map_err(ErrorResponse::server_error)?
+        .rows_affected;
+    if deleted_tasks == 0 {
+        return Ok(false);
+    }
+
+    event_bus
+        .publish(
+            db,
+            workspace_id,
+            WorkspaceEventKind::TaskDeleted {
+                id: task_model.id,
+                task_group_id: task_model.task_group_id,
+            },
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(true)
+}
+
+/// Columns of the CSV export, import reads the same columns except `key` and `status`
+pub const CSV_COLUMNS: [&str; 8] = [
+    "id",
+    "key",
+    "group",
+    "title",
+    "description",
+    "labels",
+    "due_at",
+    "status",
+];
+/// Labels in a CSV cell are separated by semicolons as descriptions may contain commas
+pub const CSV_LABELS_SEPARATOR: &str = ";";
+/// Status of tasks in the done task group of the git integration
+pub const STATUS_DONE: &str = "done";
+pub const STATUS_OPEN: &str = "open";
+
+/// Workspace with its task groups, labels and tasks which are not in trash
+pub struct WorkspaceTasks {
+    pub workspace: workspace::Model,
+    pub task_groups: Vec<task_group::Model>,
+    pub labels: Vec<label::Model>,
+    pub done_task_group_id: Option<i32>,
+    pub tasks: Vec<task::Model>,
+}
+
+/// Returns `None` if the user has no such workspace outside of trash
+pub async fn find_workspace_tasks(
+    db: &DbConn,
+    user_id: i32,
+    workspace_id: i32,
+) -> Result<Option<WorkspaceTasks>, DbErr> {
+    let found_workspace = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(db)
+        .await?;
+    let found_workspace = match found_workspace {
+        Some(workspace_model) => workspace_model,
+        None => return Ok(None),
+    };
+
+    let task_groups = found_workspace
+        .find_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .order_by_asc(task_group::Column::Id)
+        .all(db)
+        .await?;
+    let labels = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .order_by_asc(label::Column::Id)
+        .all(db)
+        .await?;
+    let done_task_group_id = found_workspace
+        .find_related(GitIntegration)
+        .one(db)
+        .await?
+        .and_then(|integration| integration.done_task_group_id);
+
+    let mut tasks: Vec<task::Model> = vec![];
+    if !task_groups.is_empty() {
+        tasks = Task::find()
+            .filter(task::Column::UserId.eq(user_id))
+            .filter(task::Column::TaskGroupId.is_in(task_groups.iter().map(|tg| tg.id)))
+            .order_by_asc(task::Column::Id)
+            .all(db)
+            .await?;
+    }
+    // Ids of trashed labels are kept on tasks until the labels are purged
+    for task_model in tasks.iter_mut() {
+        if let Some(labels_ids) = task_model.labels_ids.as_mut() {
+            labels_ids.retain(|label_id| labels.iter().any(|l| &l.id == label_id));
+        }
+    }
+
+    Ok(Some(WorkspaceTasks {
+        workspace: found_workspace,
+        task_groups,
+        labels,
+        done_task_group_id,
+        tasks,
+    }))
+}
+
+/// Labels are identified by description in CSV, labels without one by color
+pub fn csv_label_name(label_model: &label::Model) -> &str {
+    label_model
+        .description
+        .as_deref()
+        .unwrap_or(&label_model.color)
+}
+
+/// Spreadsheets evaluate cells starting with these characters as formulas
+const CSV_FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Cells which would be evaluated as formulas are prefixed with `'`, so are cells which
+/// would be unescaped by `unescape_csv_cell` otherwise
+pub fn escape_csv_cell(value: &str) -> Cow<'_, str> {
+    match needs_csv_escape(value) {
+        true => Cow::Owned(format!("'{}", value)),
+        false => Cow::Borrowed(value),
+    }
+}
+
+/// Reverses `escape_csv_cell`
+pub fn unescape_csv_cell(value: &str) -> &str {
+    match value.strip_prefix('\'') {
+        Some(escaped_value) if needs_csv_escape(escaped_value) => escaped_value,
+        _ => value,
+    }
+}
+
+fn needs_csv_escape(value: &str) -> bool {
+    match value.strip_prefix('\'') {
+        Some(rest) => needs_csv_escape(rest),
+        None => value.starts_with(CSV_FORMULA_PREFIXES),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formulas_are_escaped() {
+        assert_eq!(escape_csv_cell("=HYPERLINK(\"x\")"), "'=HYPERLINK(\"x\")");
+        assert_eq!(escape_csv_cell("+1"), "'+1");
+        assert_eq!(escape_csv_cell("-1"), "'-1");
+        assert_eq!(escape_csv_cell("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_csv_cell("\tcmd"), "'\tcmd");
+        assert_eq!(escape_csv_cell("\rcmd"), "'\rcmd");
+        assert_eq!(escape_csv_cell("Fix login"), "Fix login");
+        assert_eq!(escape_csv_cell("a=b"), "a=b");
+        assert_eq!(escape_csv_cell("'quoted'"), "'quoted'");
+    }
+
+    #[test]
+    fn escaped_cells_are_unescaped_to_the_same_value() {
+        for value in ["=1+1", "-", "'=1", "''@x", "'", "'a", "Fix login", ""] {
+            assert_eq!(unescape_csv_cell(&escape_csv_cell(value)), value);
+        }
+        assert_eq!(unescape_csv_cell("'plain"), "'plain");
+    }
+}
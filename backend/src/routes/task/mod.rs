@@ -1 +1,10 @@
-pub mod insert;
\ No newline at end of file
+pub mod delete;
+pub mod export_csv;
+pub mod filter;
+pub mod import_csv;
+pub mod insert;
+pub mod lib;
+pub mod select_all;
+pub mod select_by_key;
+pub mod select_history;
+pub mod update;
@@ -0,0 +1,146 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        label,
+        prelude::{GitIntegration, Label, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+    establish_db_connection,
+    services::task_keys::task_key,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::filter::{Filter, FilterContext};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    responses(
+        (status = 200, description = "Tasks matching the filter", body = FoundTasksResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>/tasks?<filter>")]
+pub async fn handler(
+    workspace_id: i32,
+    filter: Option<String>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Vec<FoundTask>>>, ErrorResponse> {
+    let parsed_filter = Filter::parse(filter.as_deref().unwrap_or_default())?;
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_workspace_res = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let found_workspace = match select_workspace_res {
+        Ok(Some(workspace_model)) => workspace_model,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    // Filter is evaluated against task groups and labels which are not in trash
+    let related_task_groups_res = found_workspace
+        .find_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .all(&db)
+        .await;
+    let related_labels_res = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .all(&db)
+        .await;
+    let related_git_integration_res = found_workspace.find_related(GitIntegration).one(&db).await;
+    let (task_groups, labels, git_integration) = match (
+        related_task_groups_res,
+        related_labels_res,
+        related_git_integration_res,
+    ) {
+        (Ok(task_groups), Ok(labels), Ok(git_integration)) => {
+            (task_groups, labels, git_integration)
+        }
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+            return Err(server_err_response.with_source(err))
+        }
+    };
+
+    if task_groups.is_empty() {
+        return Ok(Json(SuccessResponse::new(vec![])));
+    }
+
+    let filter_condition = parsed_filter.to_condition(&FilterContext {
+        task_groups: &task_groups,
+        labels: &labels,
+        done_task_group_id: git_integration
+            .as_ref()
+            .and_then(|integration| integration.done_task_group_id),
+    })?;
+
+    let found_tasks_res = Task::find()
+        .filter(task::Column::TaskGroupId.is_in(task_groups.iter().map(|tg| tg.id)))
+        .filter(filter_condition)
+        .order_by_asc(task::Column::Id)
+        .all(&db)
+        .await;
+
+    match found_tasks_res {
+        Ok(tasks_models) => Ok(Json(SuccessResponse::new(
+            tasks_models
+                .into_iter()
+                .map(|task_model| FoundTask {
+                    id: task_model.id,
+                    key: task_key(&found_workspace.key_prefix, task_model.number),
+                    task_group_id: task_model.task_group_id,
+                    title: task_model.title,
+                    description: task_model.description,
+                    due_at: task_model.due_at,
+                    version: task_model.version,
+                    // Ids of trashed labels are kept on tasks until the labels are purged
+                    labels_ids: task_model.labels_ids.map(|labels_ids| {
+                        labels_ids
+                            .into_iter()
+                            .filter(|label_id| labels.iter().any(|l| &l.id == label_id))
+                            .collect()
+                    }),
+                })
+                .collect(),
+        ))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct FoundTask {
+    pub id: i32,
+    pub key: String,
+    pub task_group_id: i32,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Option<Vec<i32>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub version: i32,
+}
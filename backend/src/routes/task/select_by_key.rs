@@ -0,0 +1,110 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter};
+
+use backend::{
+    entities::{
+        label,
+        prelude::{Label, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+    establish_db_connection,
+    services::task_keys::{parse_task_key, task_key},
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::select_all::FoundTask;
+
+/// Finds task by its key, e.g. `WEB-42`, prefix of the key is case insensitive
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    responses(
+        (status = 200, description = "Task with the key", body = FoundTaskResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/task/by-key/<key>")]
+pub async fn handler(
+    key: String,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<FoundTask>>, ErrorResponse> {
+    let not_found_err_response = ErrorResponse::new(
+        Some(format!("Task with key {} does not exist", key)),
+        Status::NotFound,
+    );
+    let (key_prefix, number) = match parse_task_key(&key) {
+        Some(parsed_key) => parsed_key,
+        None => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Task key {} is not valid, expected key prefix and number, e.g. WEB-42",
+                    key
+                )),
+                Status::BadRequest,
+            ))
+        }
+    };
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_workspace_res = Workspace::find()
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::KeyPrefix.eq(key_prefix))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let found_workspace = match select_workspace_res {
+        Ok(Some(workspace_model)) => workspace_model,
+        Ok(None) => return Err(not_found_err_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    // Tasks of trashed task groups are not accessible
+    let select_task_res = Task::find()
+        .filter(task::Column::UserId.eq(user.user_id))
+        .filter(task::Column::Number.eq(number))
+        .find_also_related(TaskGroup)
+        .filter(task_group::Column::WorkspaceId.eq(found_workspace.id))
+        .filter(task_group::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let task_model = match select_task_res {
+        Ok(Some((task_model, Some(_)))) => task_model,
+        Ok(_) => return Err(not_found_err_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let related_labels_res = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .all(&db)
+        .await;
+    if let Err(err) = &related_labels_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let labels = related_labels_res.unwrap();
+
+    Ok(Json(SuccessResponse::new(FoundTask {
+        id: task_model.id,
+        key: task_key(&found_workspace.key_prefix, task_model.number),
+        task_group_id: task_model.task_group_id,
+        title: task_model.title,
+        description: task_model.description,
+        due_at: task_model.due_at,
+        version: task_model.version,
+        // Ids of trashed labels are kept on tasks until the labels are purged
+        labels_ids: task_model.labels_ids.map(|labels_ids| {
+            labels_ids
+                .into_iter()
+                .filter(|label_id| labels.iter().any(|l| &l.id == label_id))
+                .collect()
+        }),
+    })))
+}
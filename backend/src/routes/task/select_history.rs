@@ -0,0 +1,127 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        prelude::{Task, TaskCommit, TaskGroup, TaskHistory},
+        task, task_commit, task_group, task_history,
+    },
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+/// History of the task together with the commits linked to it, oldest first
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    responses(
+        (status = 200, description = "History of the task", body = FoundTaskHistoryResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/task/<task_id>/history")]
+pub async fn handler(
+    task_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<FoundTaskHistory>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_task_res = Task::find_by_id(task_id)
+        .filter(task::Column::UserId.eq(user.user_id))
+        .find_also_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let task_model = match select_task_res {
+        Ok(Some((task_model, Some(_)))) => task_model,
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Task with id {} does not exist", task_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let history_res = task_model
+        .find_related(TaskHistory)
+        .order_by_asc(task_history::Column::Id)
+        .all(&db)
+        .await;
+    let commits_res = task_model
+        .find_related(TaskCommit)
+        .order_by_asc(task_commit::Column::Id)
+        .all(&db)
+        .await;
+
+    match (history_res, commits_res) {
+        (Ok(history_models), Ok(commits_models)) => {
+            Ok(Json(SuccessResponse::new(FoundTaskHistory {
+                entries: history_models
+                    .into_iter()
+                    .map(|history_model| HistoryEntry {
+                        action: history_model.action,
+                        commit_sha: history_model.commit_sha,
+                        from_task_group_id: history_model.from_task_group_id,
+                        to_task_group_id: history_model.to_task_group_id,
+                        created_at: history_model.created_at,
+                    })
+                    .collect(),
+                commits: commits_models
+                    .into_iter()
+                    .map(|commit_model| LinkedCommit {
+                        sha: commit_model.sha,
+                        message: commit_model.message,
+                        url: commit_model.url,
+                        author: commit_model.author,
+                        linked_at: commit_model.created_at,
+                    })
+                    .collect(),
+            })))
+        }
+        (Err(err), _) | (_, Err(err)) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct FoundTaskHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub commits: Vec<LinkedCommit>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct HistoryEntry {
+    /// commit_linked or moved
+    pub action: String,
+    pub commit_sha: Option<String>,
+    pub from_task_group_id: Option<i32>,
+    pub to_task_group_id: Option<i32>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct LinkedCommit {
+    pub sha: String,
+    pub message: String,
+    pub url: Option<String>,
+    pub author: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub linked_at: DateTimeWithTimeZone,
+}
@@ -0,0 +1,193 @@
+use async_graphql::InputObject;
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+    State,
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, EntityTrait, TransactionTrait};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{prelude::Workspace, task},
+    establish_db_connection,
+    services::task_keys::task_key,
+};
+
+use crate::{
+    events::bus::EventBus,
+    routes::lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, VersionedResponse},
+};
+
+use super::lib::{
+    find_missing_labels_ids, find_task, task_conflict_response, update_task, TaskChanges,
+};
+
+/// Updates task, providing `task_group_id` moves the task to another task group
+/// of the same workspace. Requests with `If-Match` header or `version` are rejected
+/// when the task was changed since that version.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task",
+    request_body = TaskDataToUpdate,
+    params(("If-Match" = Option<String>, Header, description = "ETag of the changed version")),
+    responses(
+        (status = 200, description = "Updated task", body = UpdatedTaskResponse,
+            headers(("ETag" = String, description = "Version of the task"))),
+        (status = 409, description = "Task was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Task was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[patch("/task/<task_id>", data = "<data>")]
+pub async fn handler(
+    task_id: i32,
+    data: Json<TaskDataToUpdate>,
+    if_match: IfMatch,
+    user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<UpdatedTask>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let expected_version = ExpectedVersion::new(if_match, data.version);
+    let not_found_response = ErrorResponse::new(
+        Some(format!("Task with id {} does not exist", task_id)),
+        Status::NotFound,
+    );
+    let (task_model, current_task_group) = match find_task(&db, task_id, user.user_id).await {
+        Ok(Some((task_model, Some(task_group_model)))) => (task_model, task_group_model),
+        Ok(_) => return Err(not_found_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    if !expected_version.matches(task_model.version) {
+        return Err(task_conflict_response(
+            &db,
+            &expected_version,
+            task_model,
+            current_task_group.workspace_id,
+        )
+        .await);
+    }
+
+    // Validate labels ids
+    if let Some(li) = &data.labels_ids {
+        let missing_labels_ids_res =
+            find_missing_labels_ids(&db, user.user_id, current_task_group.workspace_id, li).await;
+        if let Err(err) = &missing_labels_ids_res {
+            return Err(server_err_response.with_source(err));
+        }
+        let missing_labels_ids = missing_labels_ids_res.unwrap();
+        if !missing_labels_ids.is_empty() {
+            let mut not_found_labels_ids = String::from("");
+            for label_id in missing_labels_ids.iter() {
+                not_found_labels_ids.push_str(&format!("{}, ", label_id));
+            }
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Could not update task with labels ids {}labels do not exist",
+                    not_found_labels_ids
+                )),
+                Status::Conflict,
+            ));
+        }
+    }
+
+    let workspace_model = match Workspace::find_by_id(current_task_group.workspace_id)
+        .one(&db)
+        .await
+    {
+        Ok(Some(workspace_model)) => workspace_model,
+        Err(err) => return Err(server_err_response.with_source(err)),
+        _ => return Err(server_err_response),
+    };
+
+    // History and events are written in the transaction of the update
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let changes = TaskChanges {
+        task_group_id: data.task_group_id,
+        title: data.title.clone(),
+        description: data.description.clone(),
+        labels_ids: data.labels_ids.clone(),
+        due_at: data.due_at.clone(),
+    };
+    let updated_task = match update_task(
+        &tx,
+        event_bus,
+        task_model,
+        &workspace_model,
+        changes,
+        &expected_version,
+    )
+    .await?
+    {
+        Some(updated_task) => updated_task,
+        // Task was changed or deleted since it was selected
+        None => {
+            return match find_task(&db, task_id, user.user_id).await {
+                Ok(Some((task_model, Some(task_group_model)))) => Err(task_conflict_response(
+                    &db,
+                    &expected_version,
+                    task_model,
+                    task_group_model.workspace_id,
+                )
+                .await),
+                Ok(_) => Err(not_found_response),
+                Err(err) => Err(server_err_response.with_source(err)),
+            }
+        }
+    };
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
+
+    let version = updated_task.version;
+    Ok(VersionedResponse::new(
+        UpdatedTask::new(updated_task, &workspace_model.key_prefix),
+        version,
+    ))
+}
+
+#[derive(Deserialize, ToSchema, InputObject)]
+#[serde(crate = "rocket::serde")]
+pub struct TaskDataToUpdate {
+    pub task_group_id: Option<i32>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub labels_ids: Option<Vec<i32>>,
+    /// Date (2026-11-01) or RFC 3339 date and time, empty string removes the due date
+    pub due_at: Option<String>,
+    /// Version of the task which is changed, `If-Match` header takes precedence
+    pub version: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdatedTask {
+    pub id: i32,
+    pub key: String,
+    pub task_group_id: i32,
+    pub title: String,
+    pub description: String,
+    pub labels_ids: Option<Vec<i32>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub version: i32,
+}
+
+impl UpdatedTask {
+    pub fn new(task_model: task::Model, key_prefix: &str) -> UpdatedTask {
+        UpdatedTask {
+            id: task_model.id,
+            key: task_key(key_prefix, task_model.number),
+            task_group_id: task_model.task_group_id,
+            title: task_model.title,
+            description: task_model.description,
+            labels_ids: task_model.labels_ids,
+            due_at: task_model.due_at,
+            version: task_model.version,
+        }
+    }
+}
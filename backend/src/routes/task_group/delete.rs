@@ -1,65 +1,97 @@
-use rocket::{http::Status, serde::json::Json};
-use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, TransactionError, TransactionTrait};
+use async_graphql::SimpleObject;
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+    State,
+};
+use sea_orm::TransactionTrait;
+use utoipa::ToSchema;
+
+use backend::establish_db_connection;
 
-use backend::{
-    entities::{
-        prelude::{Task, TaskGroup},
-        task, task_group,
+use crate::{
+    events::bus::EventBus,
+    routes::{
+        lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, SuccessResponse},
+        task_group::{
+            lib::delete_task_group,
+            update::{find_task_group, UpdatedTaskGroup},
+        },
     },
-    establish_db_connection,
 };
 
-use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
-
-#[delete("/task-group/<task_group_id>")]
+/// Moves task group to trash, requests with `If-Match` header or `version` are rejected
+/// when the task group was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task_group",
+    params(
+        ("If-Match" = Option<String>, Header, description = "ETag of the deleted version"),
+        ("version" = Option<i32>, Query, description = "Version of the deleted task group, `If-Match` header takes precedence")
+    ),
+    responses(
+        (status = 200, description = "Task group moved to trash", body = DeletedTaskGroupResponse),
+        (status = 409, description = "Task group was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Task group was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/task-group/<task_group_id>?<version>")]
 pub async fn handler(
     task_group_id: i32,
+    version: Option<i32>,
+    if_match: IfMatch,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
-    let server_error_response = ErrorResponse::new(None, Status::InternalServerError);
-    let db_res = establish_db_connection().await;
-
-    if let Err(_) = db_res {
-        return Err(server_error_response);
+    event_bus: &State<EventBus>,
+) -> Result<Json<SuccessResponse<DeletedTaskGroup>>, ErrorResponse> {
+    let expected_version = ExpectedVersion::new(if_match, version);
+    let not_found_response = ErrorResponse::new(
+        format!("Task group with id {} does not exist", task_group_id).into(),
+        Status::NotFound,
+    );
+    let db = match establish_db_connection().await {
+        Ok(db) => db,
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    let task_group_model = match find_task_group(&db, task_group_id, user.user_id).await {
+        Ok(Some(task_group_model)) => task_group_model,
+        Ok(None) => return Err(not_found_response),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    if !expected_version.matches(task_group_model.version) {
+        return Err(expected_version.conflict_response(
+            &UpdatedTaskGroup::new(task_group_model.clone()),
+            task_group_model.version,
+        ));
     }
 
-    let db = db_res.unwrap();
-
-    // Delete all task group and all related tasks
-    let tx_res = db
-        .transaction::<_, (), DbErr>(|tx| {
-            Box::pin(async move {
-                let delete_task_group_res = TaskGroup::delete_many()
-                    .filter(task_group::Column::Id.eq(task_group_id))
-                    .filter(task_group::Column::UserId.eq(user.user_id))
-                    .exec(tx)
-                    .await?;
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let deleted_counts =
+        delete_task_group(&tx, event_bus, &task_group_model, &expected_version).await?;
+    let deleted = match deleted_counts {
+        Some(deleted) => deleted,
+        // Task group was changed or moved to trash since it was selected
+        None => {
+            return match find_task_group(&db, task_group_id, user.user_id).await {
+                Ok(Some(task_group_model)) => Err(expected_version.conflict_response(
+                    &UpdatedTaskGroup::new(task_group_model.clone()),
+                    task_group_model.version,
+                )),
+                Ok(None) => Err(not_found_response),
+                Err(err) => Err(ErrorResponse::server_error(err)),
+            };
+        }
+    };
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
 
-                if delete_task_group_res.rows_affected == 0 {
-                    return Err(DbErr::Custom(format!(
-                        "Task group with id {} does not exist",
-                        task_group_id
-                    )));
-                }
-
-                Task::delete_many()
-                    .filter(task::Column::TaskGroupId.eq(task_group_id))
-                    .filter(task::Column::UserId.eq(user.user_id))
-                    .exec(tx)
-                    .await?;
-
-                Ok(())
-            })
-        })
-        .await;
+    Ok(Json(SuccessResponse::new(DeletedTaskGroup {
+        tasks: deleted.tasks,
+    })))
+}
 
-    match tx_res {
-        Ok(_) => Ok(Json(SuccessResponse::new(()))),
-        Err(tx_err) => match tx_err {
-            TransactionError::Transaction(DbErr::Custom(err)) => {
-                Err(ErrorResponse::new(Some(err), Status::NotFound))
-            }
-            _ => Err(server_error_response),
-        },
-    }
+#[derive(Serialize, ToSchema, SimpleObject)]
+#[serde(crate = "rocket::serde")]
+pub struct DeletedTaskGroup {
+    pub tasks: u64,
 }
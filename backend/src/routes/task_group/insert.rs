@@ -1,80 +1,117 @@
+use async_graphql::InputObject;
 use rocket::{
     http::Status,
     serde::{json::Json, Deserialize, Serialize},
+    State,
 };
+use utoipa::ToSchema;
 
 use backend::{
-    entities::{prelude::Workspace, task_group, workspace},
+    entities::{prelude::Workspace, workspace},
     establish_db_connection,
 };
-use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
 
-use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+use crate::{
+    events::bus::EventBus,
+    routes::{
+        lib::{AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, VersionedResponse},
+        task_group::lib::insert_task_group,
+    },
+};
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task_group",
+    request_body = NewTaskGroup,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Created task group", body = InsertedTaskGroupResponse,
+            headers(("ETag" = String, description = "Version of the task group"))),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[post("/task-group", data = "<data>")]
 pub async fn handler(
     data: Json<NewTaskGroup>,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<InsertedTaskGroup>>, ErrorResponse> {
+    idempotency_key: IdempotencyKey<'_>,
+    event_bus: &State<EventBus>,
+) -> Result<Idempotent<VersionedResponse<InsertedTaskGroup>>, ErrorResponse> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    create(data, user, event_bus).await.map(Idempotent::Created)
+}
+
+/// Creates the task group, also run by the `createTaskGroup` mutation
+pub async fn create(
+    data: Json<NewTaskGroup>,
+    user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<InsertedTaskGroup>, ErrorResponse> {
     let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
     let db_res = establish_db_connection().await;
 
-    if let Err(_) = db_res {
-        return Err(server_err_response);
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
     }
 
     let db = db_res.unwrap();
     // Validate workspace_id
     let get_workspace_res = Workspace::find_by_id(data.workspace_id)
         .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
         .one(&db)
         .await;
 
-    if let Err(_) = get_workspace_res {
-        return Err(server_err_response);
+    if let Err(err) = &get_workspace_res {
+        return Err(server_err_response.with_source(err));
     }
 
-    // Validate title
-    let title_err = validate_len(&data.title, 1, 50, "Title");
-
-    if let Some(err) = title_err {
-        return Err(err);
-    }
-
-    match &get_workspace_res.unwrap() {
-        Some(_) => {
-            let insert_res = task_group::ActiveModel {
-                user_id: ActiveValue::Set(user.user_id),
-                workspace_id: ActiveValue::Set(data.workspace_id),
-                title: ActiveValue::Set(data.title.clone()),
-                ..Default::default()
-            }
-            .insert(&db)
-            .await;
-
-            match insert_res {
-                Ok(inserted_task_group) => Ok(Json(SuccessResponse::new(InsertedTaskGroup {
-                    title: inserted_task_group.title,
-                }))),
-                Err(_) => Err(server_err_response),
-            }
-        }
-        None => Err(ErrorResponse::new(
+    if get_workspace_res.unwrap().is_none() {
+        return Err(ErrorResponse::new(
             Some(format!("Workspace with id {} does not exist", data.workspace_id).into()),
             Status::NotFound,
-        )),
+        ));
     }
+
+    // Event is published in the transaction of the insert, so it is never lost
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let inserted_task_group = insert_task_group(
+        &tx,
+        event_bus,
+        user.user_id,
+        data.workspace_id,
+        data.title.clone(),
+    )
+    .await?;
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
+
+    Ok(VersionedResponse::new(
+        InsertedTaskGroup {
+            id: inserted_task_group.id,
+            title: inserted_task_group.title,
+            version: inserted_task_group.version,
+        },
+        inserted_task_group.version,
+    ))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct NewTaskGroup {
     pub workspace_id: i32,
     pub title: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct InsertedTaskGroup {
+    pub id: i32,
     pub title: String,
+    pub version: i32,
 }
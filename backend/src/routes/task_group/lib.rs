@@ -0,0 +1,129 @@
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseTransaction, DbErr,
+    EntityTrait, QueryFilter,
+};
+
+use backend::{
+    entities::{prelude::TaskGroup, task_group},
+    services::deletion::{trash_task_group_in, DeletedCounts},
+};
+
+use crate::{
+    events::bus::{EventBus, WorkspaceEventKind},
+    routes::lib::{validate_len, ErrorResponse, ExpectedVersion},
+};
+
+/// Inserts the task group into the workspace, which the caller has checked.
+/// Used by `POST /task-group` and batches.
+pub async fn insert_task_group<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    user_id: i32,
+    workspace_id: i32,
+    title: String,
+) -> Result<task_group::Model, ErrorResponse> {
+    if let Some(err) = validate_len(&title, 1, 50, "Title") {
+        return Err(err);
+    }
+
+    let inserted_task_group = task_group::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        workspace_id: ActiveValue::Set(workspace_id),
+        title: ActiveValue::Set(title),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(ErrorResponse::server_error)?;
+    event_bus
+        .publish(
+            db,
+            workspace_id,
+            WorkspaceEventKind::TaskGroupCreated {
+                id: inserted_task_group.id,
+                title: inserted_task_group.title.clone(),
+            },
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(inserted_task_group)
+}
+
+/// Renames the task group selected as `task_group_model` and publishes the change.
+///
+/// Returns `None` when the task group was changed or moved to trash since it was selected,
+/// callers tell the two apart. Used by `PATCH /task-group/<id>` and batches.
+pub async fn update_task_group<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    task_group_model: task_group::Model,
+    title: String,
+    expected_version: &ExpectedVersion,
+) -> Result<Option<task_group::Model>, ErrorResponse> {
+    if let Some(err) = validate_len(&title, 1, 50, "Title") {
+        return Err(err);
+    }
+
+    let version = task_group_model.version;
+    let mut task_group_to_update: task_group::ActiveModel = task_group_model.into();
+    task_group_to_update.title = ActiveValue::Set(title);
+    let update_res = TaskGroup::update(task_group_to_update)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .filter(expected_version.condition(task_group::Column::Version, version))
+        .exec(db)
+        .await;
+    let updated_task_group = match update_res {
+        Ok(updated_task_group) => updated_task_group,
+        Err(DbErr::RecordNotFound(_)) => return Ok(None),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+
+    event_bus
+        .publish(
+            db,
+            updated_task_group.workspace_id,
+            WorkspaceEventKind::TaskGroupUpdated {
+                id: updated_task_group.id,
+                title: updated_task_group.title.clone(),
+            },
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(Some(updated_task_group))
+}
+
+/// Moves the task group to trash, its tasks stay attached to it.
+/// Used by `DELETE /task-group/<id>` and batches.
+///
+/// Returns `None` when the task group was changed or trashed since `task_group_model`
+/// was selected, the transaction has to be rolled back then.
+pub async fn delete_task_group(
+    tx: &DatabaseTransaction,
+    event_bus: &EventBus,
+    task_group_model: &task_group::Model,
+    expected_version: &ExpectedVersion,
+) -> Result<Option<DeletedCounts>, ErrorResponse> {
+    let trash_res = trash_task_group_in(
+        tx,
+        task_group_model.id,
+        task_group_model.user_id,
+        expected_version.required_version(task_group_model.version),
+    )
+    .await;
+    let deleted = match trash_res {
+        Ok(deleted) => deleted,
+        Err(DbErr::RecordNotFound(_)) => return Ok(None),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    event_bus
+        .publish(
+            tx,
+            task_group_model.workspace_id,
+            WorkspaceEventKind::TaskGroupDeleted {
+                id: task_group_model.id,
+            },
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(Some(deleted))
+}
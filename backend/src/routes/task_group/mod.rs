@@ -1,3 +1,6 @@
 pub mod delete;
 pub mod insert;
+pub mod select_tasks;
 pub mod update;
+
+pub mod lib;
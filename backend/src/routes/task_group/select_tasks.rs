@@ -0,0 +1,111 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect};
+
+use backend::{
+    entities::{
+        label,
+        prelude::{Label, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+    establish_db_connection,
+    services::task_keys::task_key,
+};
+
+use crate::routes::{
+    lib::{AuthenticatedUser, ErrorResponse, Pagination, SuccessResponse},
+    workspace::select_one::GroupTask,
+};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task_group",
+    responses(
+        (status = 200, description = "One page of tasks", body = GroupTasksResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/task-group/<task_group_id>/tasks?<limit>&<cursor>")]
+pub async fn handler(
+    task_group_id: i32,
+    limit: Option<u64>,
+    cursor: Option<String>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Vec<GroupTask>>>, ErrorResponse> {
+    let pagination = Pagination::new(limit, cursor)?;
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Find task group, neither task group nor its workspace can be in trash
+    let select_task_group_res = TaskGroup::find_by_id(task_group_id)
+        .filter(task_group::Column::UserId.eq(user.user_id))
+        .filter(task_group::Column::DeletedAt.is_null())
+        .find_also_related(Workspace)
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let found_workspace = match select_task_group_res {
+        Ok(Some((_, Some(workspace_model)))) => workspace_model,
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Task group with id {} does not exist",
+                    task_group_id
+                )),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let related_labels_res = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .all(&db)
+        .await;
+    if let Err(err) = &related_labels_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let labels = related_labels_res.unwrap();
+
+    let found_tasks_res = Task::find()
+        .filter(task::Column::TaskGroupId.eq(task_group_id))
+        .filter(task::Column::UserId.eq(user.user_id))
+        .filter(task::Column::Id.gt(pagination.after_id))
+        .order_by_asc(task::Column::Id)
+        .limit(pagination.select_limit())
+        .all(&db)
+        .await;
+
+    match found_tasks_res {
+        Ok(tasks_models) => {
+            let (tasks_models, next_cursor) =
+                pagination.page(tasks_models, |task_model| task_model.id);
+            let tasks = tasks_models
+                .into_iter()
+                .map(|task_model| GroupTask {
+                    id: task_model.id,
+                    key: task_key(&found_workspace.key_prefix, task_model.number),
+                    title: task_model.title,
+                    description: task_model.description,
+                    due_at: task_model.due_at,
+                    version: task_model.version,
+                    // Ids of trashed labels are kept on tasks until the labels are purged
+                    labels_ids: task_model.labels_ids.map(|labels_ids| {
+                        labels_ids
+                            .into_iter()
+                            .filter(|label_id| labels.iter().any(|l| &l.id == label_id))
+                            .collect()
+                    }),
+                })
+                .collect();
+            Ok(Json(SuccessResponse::paginated(tasks, next_cursor)))
+        }
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
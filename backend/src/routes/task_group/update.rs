@@ -1,69 +1,144 @@
+use async_graphql::InputObject;
 use rocket::{
     http::Status,
     serde::{json::Json, Deserialize, Serialize},
+    State,
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, TransactionTrait};
+use utoipa::ToSchema;
 
-use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+use crate::{
+    events::bus::EventBus,
+    routes::{
+        lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, VersionedResponse},
+        task_group::lib::update_task_group,
+    },
+};
 
 use backend::{
     entities::{prelude::TaskGroup, task_group},
     establish_db_connection,
 };
 
+/// Updates task group, requests with `If-Match` header or `version` are rejected
+/// when the task group was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "task_group",
+    request_body = ModifiedTaskGroupData,
+    params(("If-Match" = Option<String>, Header, description = "ETag of the changed version")),
+    responses(
+        (status = 200, description = "Updated task group", body = UpdatedTaskGroupResponse,
+            headers(("ETag" = String, description = "Version of the task group"))),
+        (status = 409, description = "Task group was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Task group was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[patch("/task-group/<task_group_id>", data = "<data>")]
 pub async fn handler(
     task_group_id: i32,
     data: Json<ModifiedTaskGroupData>,
+    if_match: IfMatch,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<ModifiedTaskGroupData>>, ErrorResponse> {
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<UpdatedTaskGroup>, ErrorResponse> {
     let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
     let db_res = establish_db_connection().await;
 
-    if let Err(_) = db_res {
-        return Err(server_err_response);
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
     }
 
     let db = db_res.unwrap();
 
-    // Validate title len
-    let title_len_err = validate_len(&data.title, 1, 50, "Title");
-    if let Some(err) = title_len_err {
-        return Err(err);
-    }
-
-    let select_res = TaskGroup::find()
-        .filter(task_group::Column::Id.eq(task_group_id))
-        .filter(task_group::Column::UserId.eq(user.user_id))
-        .one(&db)
-        .await;
-
-    if let Err(_) = select_res {
-        return Err(server_err_response);
+    let expected_version = ExpectedVersion::new(if_match, data.version);
+    let not_found_response = ErrorResponse::new(
+        Some(format!("Task group with id {} does not exist", task_group_id).into()),
+        Status::NotFound,
+    );
+    let task_group = match find_task_group(&db, task_group_id, user.user_id).await {
+        Ok(Some(task_group)) => task_group,
+        Ok(None) => return Err(not_found_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+    if !expected_version.matches(task_group.version) {
+        return Err(expected_version.conflict_response(
+            &UpdatedTaskGroup::new(task_group.clone()),
+            task_group.version,
+        ));
     }
 
-    match select_res.unwrap() {
-        Some(task_group) => {
-            let mut task_group_active_model: task_group::ActiveModel = task_group.into();
-            task_group_active_model.title = sea_orm::Set(data.title.clone());
-            let update_res = task_group_active_model.update(&db).await;
-
-            match update_res {
-                Ok(_) => Ok(Json(SuccessResponse::new(ModifiedTaskGroupData {
-                    title: data.title.clone(),
-                }))),
-                Err(_) => Err(server_err_response),
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let updated_task_group = update_task_group(
+        &tx,
+        event_bus,
+        task_group,
+        data.title.clone(),
+        &expected_version,
+    )
+    .await?;
+    let updated_task_group = match updated_task_group {
+        Some(updated_task_group) => updated_task_group,
+        // Task group was changed or moved to trash since it was selected
+        None => {
+            return match find_task_group(&db, task_group_id, user.user_id).await {
+                Ok(Some(task_group)) => Err(expected_version.conflict_response(
+                    &UpdatedTaskGroup::new(task_group.clone()),
+                    task_group.version,
+                )),
+                Ok(None) => Err(not_found_response),
+                Err(err) => Err(server_err_response.with_source(err)),
             }
         }
-        None => Err(ErrorResponse::new(
-            Some(format!("Task group with id {} does not exist", task_group_id).into()),
-            Status::NotFound,
-        )),
-    }
+    };
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
+
+    let version = updated_task_group.version;
+    Ok(VersionedResponse::new(
+        UpdatedTaskGroup::new(updated_task_group),
+        version,
+    ))
 }
 
-#[derive(Deserialize, Serialize)]
+pub async fn find_task_group(
+    db: &DatabaseConnection,
+    task_group_id: i32,
+    user_id: i32,
+) -> Result<Option<task_group::Model>, DbErr> {
+    TaskGroup::find()
+        .filter(task_group::Column::Id.eq(task_group_id))
+        .filter(task_group::Column::UserId.eq(user_id))
+        .filter(task_group::Column::DeletedAt.is_null())
+        .one(db)
+        .await
+}
+
+#[derive(Deserialize, Serialize, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct ModifiedTaskGroupData {
-    title: String,
+    pub title: String,
+    /// Version of the task group which is changed, `If-Match` header takes precedence
+    pub version: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdatedTaskGroup {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub title: String,
+    pub version: i32,
+}
+
+impl UpdatedTaskGroup {
+    pub fn new(task_group_model: task_group::Model) -> UpdatedTaskGroup {
+        UpdatedTaskGroup {
+            id: task_group_model.id,
+            workspace_id: task_group_model.workspace_id,
+            title: task_group_model.title,
+            version: task_group_model.version,
+        }
+    }
 }
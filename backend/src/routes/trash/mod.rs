@@ -0,0 +1,2 @@
+pub mod restore;
+pub mod select_all;
@@ -0,0 +1,254 @@
+use rocket::{http::Status, serde::json::Json, State};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, sea_query::Expr, ColumnTrait, DbConn, DbErr, EntityTrait,
+    QueryFilter, TransactionError, TransactionTrait,
+};
+
+use backend::{
+    entities::{
+        label,
+        prelude::{Label, TaskGroup, Workspace},
+        task_group, workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::{
+    events::bus::{EventBus, WorkspaceEventKind},
+    routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse},
+};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Restored item", body = EmptyResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[post("/trash/<entity_type>/<entity_id>/restore")]
+pub async fn handler(
+    entity_type: &str,
+    entity_id: i32,
+    user: AuthenticatedUser,
+    event_bus: &State<EventBus>,
+) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let restore_res = match entity_type {
+        "workspace" => restore_workspace(&db, entity_id, user.user_id).await,
+        "task-group" => restore_task_group(&db, event_bus, entity_id, user.user_id).await,
+        "label" => restore_label(&db, event_bus, entity_id, user.user_id).await,
+        _ => Err(ErrorResponse::new(
+            Some(format!("Unknown trash entity type {}", entity_type)),
+            Status::NotFound,
+        )),
+    };
+
+    match restore_res {
+        Ok(_) => Ok(Json(SuccessResponse::new(()))),
+        Err(err) => Err(err),
+    }
+}
+
+/// Restores workspace together with task groups and labels which were trashed with it
+async fn restore_workspace(
+    db: &DbConn,
+    workspace_id: i32,
+    user_id: i32,
+) -> Result<(), ErrorResponse> {
+    let tx_res = db
+        .transaction::<_, (), DbErr>(|tx| {
+            Box::pin(async move {
+                let trashed_workspace = Workspace::find_by_id(workspace_id)
+                    .filter(workspace::Column::UserId.eq(user_id))
+                    .filter(workspace::Column::DeletedAt.is_not_null())
+                    .one(tx)
+                    .await?;
+
+                let deleted_at = match trashed_workspace {
+                    Some(workspace_model) => workspace_model.deleted_at.unwrap(),
+                    None => {
+                        return Err(DbErr::Custom(format!(
+                            "Workspace with id {} is not in trash",
+                            workspace_id
+                        )))
+                    }
+                };
+
+                TaskGroup::update_many()
+                    .col_expr(
+                        task_group::Column::DeletedAt,
+                        Expr::value(None::<DateTimeWithTimeZone>),
+                    )
+                    .filter(task_group::Column::WorkspaceId.eq(workspace_id))
+                    .filter(task_group::Column::DeletedAt.eq(deleted_at))
+                    .exec(tx)
+                    .await?;
+
+                Label::update_many()
+                    .col_expr(
+                        label::Column::DeletedAt,
+                        Expr::value(None::<DateTimeWithTimeZone>),
+                    )
+                    .filter(label::Column::WorkspaceId.eq(workspace_id))
+                    .filter(label::Column::DeletedAt.eq(deleted_at))
+                    .exec(tx)
+                    .await?;
+
+                Workspace::update_many()
+                    .col_expr(
+                        workspace::Column::DeletedAt,
+                        Expr::value(None::<DateTimeWithTimeZone>),
+                    )
+                    .filter(workspace::Column::Id.eq(workspace_id))
+                    .exec(tx)
+                    .await?;
+
+                Ok(())
+            })
+        })
+        .await;
+
+    match tx_res {
+        Ok(_) => Ok(()),
+        Err(TransactionError::Transaction(DbErr::Custom(err))) => {
+            Err(ErrorResponse::new(Some(err), Status::NotFound))
+        }
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
+
+async fn restore_task_group(
+    db: &DbConn,
+    event_bus: &EventBus,
+    task_group_id: i32,
+    user_id: i32,
+) -> Result<(), ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+
+    let select_res = TaskGroup::find_by_id(task_group_id)
+        .filter(task_group::Column::UserId.eq(user_id))
+        .filter(task_group::Column::DeletedAt.is_not_null())
+        .find_also_related(Workspace)
+        .one(db)
+        .await;
+    if let Err(err) = &select_res {
+        return Err(server_err_response.with_source(err));
+    }
+
+    match select_res.unwrap() {
+        Some((_, Some(related_workspace))) => {
+            if related_workspace.deleted_at.is_some() {
+                return Err(ErrorResponse::new(
+                    Some(format!(
+                        "Could not restore task group with id {}, workspace with id {} is in trash",
+                        task_group_id, related_workspace.id
+                    )),
+                    Status::Conflict,
+                ));
+            }
+
+            let restore_res: Result<(), DbErr> = async {
+                let tx = db.begin().await?;
+                TaskGroup::update_many()
+                    .col_expr(
+                        task_group::Column::DeletedAt,
+                        Expr::value(None::<DateTimeWithTimeZone>),
+                    )
+                    .filter(task_group::Column::Id.eq(task_group_id))
+                    .exec(&tx)
+                    .await?;
+                event_bus
+                    .publish(
+                        &tx,
+                        related_workspace.id,
+                        WorkspaceEventKind::TaskGroupRestored { id: task_group_id },
+                    )
+                    .await?;
+                tx.commit().await
+            }
+            .await;
+
+            match restore_res {
+                Ok(_) => Ok(()),
+                Err(err) => Err(server_err_response.with_source(err)),
+            }
+        }
+        _ => Err(ErrorResponse::new(
+            Some(format!(
+                "Task group with id {} is not in trash",
+                task_group_id
+            )),
+            Status::NotFound,
+        )),
+    }
+}
+
+async fn restore_label(
+    db: &DbConn,
+    event_bus: &EventBus,
+    label_id: i32,
+    user_id: i32,
+) -> Result<(), ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+
+    let select_res = Label::find_by_id(label_id)
+        .filter(label::Column::UserId.eq(user_id))
+        .filter(label::Column::DeletedAt.is_not_null())
+        .find_also_related(Workspace)
+        .one(db)
+        .await;
+    if let Err(err) = &select_res {
+        return Err(server_err_response.with_source(err));
+    }
+
+    match select_res.unwrap() {
+        Some((_, Some(related_workspace))) => {
+            if related_workspace.deleted_at.is_some() {
+                return Err(ErrorResponse::new(
+                    Some(format!(
+                        "Could not restore label with id {}, workspace with id {} is in trash",
+                        label_id, related_workspace.id
+                    )),
+                    Status::Conflict,
+                ));
+            }
+
+            let restore_res: Result<(), DbErr> = async {
+                let tx = db.begin().await?;
+                Label::update_many()
+                    .col_expr(
+                        label::Column::DeletedAt,
+                        Expr::value(None::<DateTimeWithTimeZone>),
+                    )
+                    .filter(label::Column::Id.eq(label_id))
+                    .exec(&tx)
+                    .await?;
+                event_bus
+                    .publish(
+                        &tx,
+                        related_workspace.id,
+                        WorkspaceEventKind::LabelRestored { id: label_id },
+                    )
+                    .await?;
+                tx.commit().await
+            }
+            .await;
+
+            match restore_res {
+                Ok(_) => Ok(()),
+                Err(err) => Err(server_err_response.with_source(err)),
+            }
+        }
+        _ => Err(ErrorResponse::new(
+            Some(format!("Label with id {} is not in trash", label_id)),
+            Status::NotFound,
+        )),
+    }
+}
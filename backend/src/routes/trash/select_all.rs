@@ -0,0 +1,140 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{prelude::DateTimeWithTimeZone, ColumnTrait, DbErr, EntityTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        label,
+        prelude::{Label, TaskGroup, Workspace},
+        task_group, workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Trashed items", body = FoundTrashResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/trash")]
+pub async fn handler(
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<FoundTrash>>, ErrorResponse> {
+    let select_res: Result<FoundTrash, DbErr> = async move {
+        let db = establish_db_connection().await?;
+        let workspaces_models = Workspace::find()
+            .filter(workspace::Column::UserId.eq(user.user_id))
+            .filter(workspace::Column::DeletedAt.is_not_null())
+            .all(&db)
+            .await?;
+        let task_groups_models = TaskGroup::find()
+            .filter(task_group::Column::UserId.eq(user.user_id))
+            .filter(task_group::Column::DeletedAt.is_not_null())
+            .all(&db)
+            .await?;
+        let labels_models = Label::find()
+            .filter(label::Column::UserId.eq(user.user_id))
+            .filter(label::Column::DeletedAt.is_not_null())
+            .all(&db)
+            .await?;
+
+        // Task groups and labels of trashed workspace are restored together with the
+        // workspace, list only those which were trashed on their own
+        let is_in_trashed_workspace = |workspace_id: i32| {
+            workspaces_models
+                .iter()
+                .any(|workspace_model| workspace_model.id == workspace_id)
+        };
+
+        let task_groups = task_groups_models
+            .iter()
+            .filter(|task_group_model| !is_in_trashed_workspace(task_group_model.workspace_id))
+            .map(|task_group_model| TrashedTaskGroup {
+                id: task_group_model.id,
+                workspace_id: task_group_model.workspace_id,
+                title: task_group_model.title.clone(),
+                deleted_at: task_group_model.deleted_at.unwrap(),
+            })
+            .collect();
+        let labels = labels_models
+            .iter()
+            .filter(|label_model| !is_in_trashed_workspace(label_model.workspace_id))
+            .map(|label_model| TrashedLabel {
+                id: label_model.id,
+                workspace_id: label_model.workspace_id,
+                color: label_model.color.clone(),
+                description: label_model.description.clone(),
+                deleted_at: label_model.deleted_at.unwrap(),
+            })
+            .collect();
+        let workspaces = workspaces_models
+            .iter()
+            .map(|workspace_model| TrashedWorkspace {
+                id: workspace_model.id,
+                title: workspace_model.title.clone(),
+                description: workspace_model.description.clone(),
+                deleted_at: workspace_model.deleted_at.unwrap(),
+            })
+            .collect();
+
+        Ok(FoundTrash {
+            workspaces,
+            task_groups,
+            labels,
+        })
+    }
+    .await;
+
+    match select_res {
+        Ok(found_trash) => Ok(Json(SuccessResponse::new(found_trash))),
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct FoundTrash {
+    pub workspaces: Vec<TrashedWorkspace>,
+    pub task_groups: Vec<TrashedTaskGroup>,
+    pub labels: Vec<TrashedLabel>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct TrashedWorkspace {
+    pub id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub deleted_at: DateTimeWithTimeZone,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct TrashedTaskGroup {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub title: String,
+    #[schema(value_type = String, format = DateTime)]
+    pub deleted_at: DateTimeWithTimeZone,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct TrashedLabel {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub color: String,
+    pub description: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub deleted_at: DateTimeWithTimeZone,
+}
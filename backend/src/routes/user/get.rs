@@ -1,4 +1,6 @@
 use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+use async_graphql::SimpleObject;
+use utoipa::ToSchema;
 
 use rocket::{
     http::Status,
@@ -11,13 +13,22 @@ use backend::{
     establish_db_connection,
 };
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "user",
+    responses(
+        (status = 200, description = "Signed in user", body = FoundUserResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[get("/user")]
 pub async fn handler(
     authenticated_user: AuthenticatedUser,
 ) -> Result<Json<SuccessResponse<FoundUserData>>, ErrorResponse> {
     let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(ErrorResponse::new(None, Status::InternalServerError));
+    if let Err(err) = &db_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
     }
     let db = db_res.unwrap();
     let saved_user_res = User::find_by_id(authenticated_user.user_id)
@@ -25,15 +36,16 @@ pub async fn handler(
         .all(&db)
         .await;
 
-    if let Err(_) = saved_user_res {
-        return Err(ErrorResponse::new(None, Status::InternalServerError));
+    if let Err(err) = &saved_user_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
     }
 
     let saved_user_vec = saved_user_res.unwrap();
     if saved_user_vec.len() == 0 {
         // User has to exit if request contained signed cookie
         // Respond with server error
-        return Err(ErrorResponse::new(None, Status::InternalServerError));
+        return Err(ErrorResponse::new(None, Status::InternalServerError)
+            .with_source("User of the session does not exist"));
     }
 
     let (saved_user, social_profiles) = &saved_user_vec[0];
@@ -44,7 +56,8 @@ pub async fn handler(
     if None == default_social_profile_option {
         // Default social profile has to exist if user exists
         // Respond with server error
-        return Err(ErrorResponse::new(None, Status::InternalServerError));
+        return Err(ErrorResponse::new(None, Status::InternalServerError)
+            .with_source("Default social profile of the user does not exist"));
     }
 
     let default_social_profile = default_social_profile_option.unwrap();
@@ -56,8 +69,9 @@ pub async fn handler(
     })))
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema, SimpleObject)]
 #[serde(crate = "rocket::serde")]
+#[graphql(name = "User")]
 pub struct FoundUserData {
     pub provider_type: String,
     pub username: String,
@@ -0,0 +1,52 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, DbErr, DeleteResult, EntityTrait, QueryFilter};
+
+use backend::{
+    entities::{prelude::SavedView, saved_view},
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "view",
+    responses(
+        (status = 200, description = "Deleted view", body = EmptyResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/workspace/<workspace_id>/views/<view_id>")]
+pub async fn handler(
+    workspace_id: i32,
+    view_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    // Only owner of the view can delete it
+    let delete_res: Result<DeleteResult, DbErr> = async move {
+        let db = establish_db_connection().await?;
+        let deleted_view = SavedView::delete_many()
+            .filter(saved_view::Column::Id.eq(view_id))
+            .filter(saved_view::Column::WorkspaceId.eq(workspace_id))
+            .filter(saved_view::Column::UserId.eq(user.user_id))
+            .exec(&db)
+            .await?;
+        Ok(deleted_view)
+    }
+    .await;
+
+    match delete_res {
+        Ok(delete_result) => {
+            if delete_result.rows_affected == 0 {
+                return Err(ErrorResponse::new(
+                    Some(format!("View with id {} does not exist", view_id)),
+                    Status::NotFound,
+                ));
+            }
+
+            Ok(Json(SuccessResponse::new(())))
+        }
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
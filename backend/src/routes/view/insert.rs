@@ -0,0 +1,125 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{prelude::Workspace, saved_view, workspace},
+    establish_db_connection,
+};
+
+use crate::routes::lib::{
+    AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, SuccessResponse,
+};
+
+use super::lib::{
+    validate_filter, validate_option, validate_title, validate_visible_fields, SavedView,
+    GROUP_BY_OPTIONS, SORT_BY_OPTIONS, SORT_ORDER_OPTIONS, VISIBLE_FIELDS_OPTIONS,
+};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "view",
+    request_body = NewView,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Created view", body = SavedViewResponse),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[post("/workspace/<workspace_id>/views", data = "<data>")]
+pub async fn handler(
+    workspace_id: i32,
+    data: Json<NewView>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey<'_>,
+) -> Result<Idempotent<Json<SuccessResponse<SavedView>>>, ErrorResponse> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    let filter = data.filter.clone().unwrap_or_default();
+    let sort_by = data.sort_by.clone().unwrap_or_else(|| "id".into());
+    let sort_order = data.sort_order.clone().unwrap_or_else(|| "asc".into());
+    let visible_fields = data
+        .visible_fields
+        .clone()
+        .unwrap_or_else(|| VISIBLE_FIELDS_OPTIONS.map(String::from).to_vec());
+
+    // Validate view
+    let validation_errs = [
+        validate_title(&data.title),
+        validate_filter(&filter),
+        validate_option(&sort_by, &SORT_BY_OPTIONS, "Sort by"),
+        validate_option(&sort_order, &SORT_ORDER_OPTIONS, "Sort order"),
+        data.group_by
+            .as_ref()
+            .and_then(|group_by| validate_option(group_by, &GROUP_BY_OPTIONS, "Group by")),
+        validate_visible_fields(&visible_fields),
+    ];
+    if let Some(err) = validation_errs.into_iter().flatten().next() {
+        return Err(err);
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Validate workspace_id
+    let get_workspace_res = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    match get_workspace_res {
+        Ok(Some(_)) => (),
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    }
+
+    let insert_res = saved_view::ActiveModel {
+        workspace_id: ActiveValue::Set(workspace_id),
+        user_id: ActiveValue::Set(user.user_id),
+        title: ActiveValue::Set(data.title.clone()),
+        filter: ActiveValue::Set(filter),
+        sort_by: ActiveValue::Set(sort_by),
+        sort_order: ActiveValue::Set(sort_order),
+        group_by: ActiveValue::Set(data.group_by.clone()),
+        visible_fields: ActiveValue::Set(visible_fields),
+        shared: ActiveValue::Set(data.shared.unwrap_or(false)),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await;
+
+    match insert_res {
+        Ok(inserted_view) => Ok(Idempotent::Created(Json(SuccessResponse::new(
+            SavedView::new(inserted_view, user.user_id),
+        )))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewView {
+    pub title: String,
+    pub filter: Option<String>,
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,
+    pub group_by: Option<String>,
+    pub visible_fields: Option<Vec<String>>,
+    pub shared: Option<bool>,
+}
@@ -0,0 +1,113 @@
+use rocket::{http::Status, serde::Serialize};
+use sea_orm::{ColumnTrait, Condition, Order};
+use utoipa::ToSchema;
+
+use backend::entities::{saved_view, task};
+
+use crate::routes::{
+    lib::{validate_len, ErrorResponse},
+    task::filter::Filter,
+};
+
+pub const SORT_BY_OPTIONS: [&str; 4] = ["id", "title", "task_group", "due_at"];
+pub const SORT_ORDER_OPTIONS: [&str; 2] = ["asc", "desc"];
+pub const GROUP_BY_OPTIONS: [&str; 2] = ["task_group", "label"];
+pub const VISIBLE_FIELDS_OPTIONS: [&str; 5] =
+    ["title", "description", "labels_ids", "task_group_id", "due_at"];
+
+/// Views the user can see, their own and the shared ones
+pub fn visible_views_condition(user_id: i32) -> Condition {
+    Condition::any()
+        .add(saved_view::Column::UserId.eq(user_id))
+        .add(saved_view::Column::Shared.eq(true))
+}
+
+pub fn sort_column(sort_by: &str) -> task::Column {
+    match sort_by {
+        "title" => task::Column::Title,
+        "task_group" => task::Column::TaskGroupId,
+        "due_at" => task::Column::DueAt,
+        _ => task::Column::Id,
+    }
+}
+
+pub fn sort_order(sort_order: &str) -> Order {
+    match sort_order {
+        "desc" => Order::Desc,
+        _ => Order::Asc,
+    }
+}
+
+pub fn validate_title(title: &String) -> Option<ErrorResponse> {
+    validate_len(title, 1, 50, "Title")
+}
+
+/// Filter is stored as written, only its syntax is validated as labels and task groups
+/// it refers to can change
+pub fn validate_filter(filter: &String) -> Option<ErrorResponse> {
+    if let Some(err) = validate_len(filter, 0, 255, "Filter") {
+        return Some(err);
+    }
+
+    match Filter::parse(filter) {
+        Ok(_) => None,
+        Err(err) => Some(err.into()),
+    }
+}
+
+pub fn validate_option(value: &str, options: &[&str], property: &str) -> Option<ErrorResponse> {
+    match options.contains(&value) {
+        true => None,
+        false => Some(ErrorResponse::new(
+            Some(format!(
+                "{} must be one of {}",
+                property,
+                options.join(", ")
+            )),
+            Status::UnprocessableEntity,
+        )),
+    }
+}
+
+pub fn validate_visible_fields(visible_fields: &[String]) -> Option<ErrorResponse> {
+    for field in visible_fields.iter() {
+        if let Some(err) = validate_option(field, &VISIBLE_FIELDS_OPTIONS, "Visible field") {
+            return Some(err);
+        }
+    }
+
+    None
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SavedView {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub title: String,
+    pub filter: String,
+    pub sort_by: String,
+    pub sort_order: String,
+    pub group_by: Option<String>,
+    pub visible_fields: Vec<String>,
+    pub shared: bool,
+    /// Whether the view belongs to the user, only owner can modify the view
+    pub owned: bool,
+}
+
+impl SavedView {
+    pub fn new(view_model: saved_view::Model, user_id: i32) -> SavedView {
+        SavedView {
+            id: view_model.id,
+            workspace_id: view_model.workspace_id,
+            title: view_model.title,
+            filter: view_model.filter,
+            sort_by: view_model.sort_by,
+            sort_order: view_model.sort_order,
+            group_by: view_model.group_by,
+            visible_fields: view_model.visible_fields,
+            shared: view_model.shared,
+            owned: view_model.user_id == user_id,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod delete;
+pub mod insert;
+pub mod select_all;
+pub mod select_tasks;
+pub mod update;
+
+pub mod lib;
@@ -0,0 +1,65 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+
+use backend::{
+    entities::{
+        prelude::{SavedView as SavedViewEntity, Workspace},
+        saved_view, workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::{visible_views_condition, SavedView};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "view",
+    responses(
+        (status = 200, description = "Views of the workspace", body = SavedViewsResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>/views")]
+pub async fn handler(
+    workspace_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Vec<SavedView>>>, ErrorResponse> {
+    let user_id = user.user_id;
+    let select_res: Result<Option<Vec<saved_view::Model>>, DbErr> = async move {
+        let db = establish_db_connection().await?;
+        let found_workspace = Workspace::find_by_id(workspace_id)
+            .filter(workspace::Column::UserId.eq(user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .one(&db)
+            .await?;
+        if found_workspace.is_none() {
+            return Ok(None);
+        }
+
+        let found_views = SavedViewEntity::find()
+            .filter(saved_view::Column::WorkspaceId.eq(workspace_id))
+            .filter(visible_views_condition(user_id))
+            .order_by_asc(saved_view::Column::Id)
+            .all(&db)
+            .await?;
+        Ok(Some(found_views))
+    }
+    .await;
+
+    match select_res {
+        Ok(Some(views_models)) => Ok(Json(SuccessResponse::new(
+            views_models
+                .into_iter()
+                .map(|view_model| SavedView::new(view_model, user_id))
+                .collect(),
+        ))),
+        Ok(None) => Err(ErrorResponse::new(
+            Some(format!("Workspace with id {} does not exist", workspace_id)),
+            Status::NotFound,
+        )),
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
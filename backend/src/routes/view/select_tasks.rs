@@ -0,0 +1,253 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        label,
+        prelude::{
+            GitIntegration, Label, SavedView as SavedViewEntity, Task, TaskGroup, Workspace,
+        },
+        task, task_group, workspace,
+    },
+    establish_db_connection,
+    services::task_keys::task_key,
+};
+
+use crate::routes::{
+    lib::{AuthenticatedUser, ErrorResponse, SuccessResponse},
+    task::filter::{Filter, FilterContext},
+};
+
+use super::lib::{sort_column, sort_order, visible_views_condition, SavedView};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "view",
+    responses(
+        (status = 200, description = "Tasks of the view", body = ViewTasksResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/views/<view_id>/tasks")]
+pub async fn handler(
+    view_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<ViewTasks>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let not_found_err_response = ErrorResponse::new(
+        Some(format!("View with id {} does not exist", view_id)),
+        Status::NotFound,
+    );
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Find view and make sure user has access to its workspace
+    let select_view_res = SavedViewEntity::find_by_id(view_id)
+        .filter(visible_views_condition(user.user_id))
+        .find_also_related(Workspace)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let (view_model, found_workspace) = match select_view_res {
+        Ok(Some((view_model, Some(workspace_model)))) => (view_model, workspace_model),
+        Ok(_) => return Err(not_found_err_response),
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    // View filter is evaluated against task groups and labels which are not in trash
+    let related_task_groups_res = found_workspace
+        .find_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .order_by_asc(task_group::Column::Id)
+        .all(&db)
+        .await;
+    let related_labels_res = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .order_by_asc(label::Column::Id)
+        .all(&db)
+        .await;
+    let related_git_integration_res = found_workspace.find_related(GitIntegration).one(&db).await;
+    let (task_groups, labels, git_integration) = match (
+        related_task_groups_res,
+        related_labels_res,
+        related_git_integration_res,
+    ) {
+        (Ok(task_groups), Ok(labels), Ok(git_integration)) => {
+            (task_groups, labels, git_integration)
+        }
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+            return Err(server_err_response.with_source(err))
+        }
+    };
+
+    // Filter was valid when it was saved, but labels or task groups it refers to may be gone
+    let filter_condition = Filter::parse(&view_model.filter).and_then(|filter| {
+        filter.to_condition(&FilterContext {
+            task_groups: &task_groups,
+            labels: &labels,
+            done_task_group_id: git_integration
+                .as_ref()
+                .and_then(|integration| integration.done_task_group_id),
+        })
+    });
+    let filter_condition = match filter_condition {
+        Ok(condition) => condition,
+        Err(err) => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Filter of the view is no longer valid at position {} ({}): {}",
+                    err.position, err.token, err.message
+                )),
+                Status::Conflict,
+            ))
+        }
+    };
+
+    let mut tasks_models: Vec<task::Model> = vec![];
+    if !task_groups.is_empty() {
+        let found_tasks_res = Task::find()
+            .filter(task::Column::TaskGroupId.is_in(task_groups.iter().map(|tg| tg.id)))
+            .filter(filter_condition)
+            .order_by(
+                sort_column(&view_model.sort_by),
+                sort_order(&view_model.sort_order),
+            )
+            .order_by_asc(task::Column::Id)
+            .all(&db)
+            .await;
+        match found_tasks_res {
+            Ok(found_tasks) => tasks_models = found_tasks,
+            Err(err) => return Err(server_err_response.with_source(err)),
+        }
+    }
+
+    // Ids of trashed labels are kept on tasks until the labels are purged
+    for task_model in tasks_models.iter_mut() {
+        if let Some(labels_ids) = task_model.labels_ids.as_mut() {
+            labels_ids.retain(|label_id| labels.iter().any(|l| &l.id == label_id));
+        }
+    }
+
+    let to_view_task = |task_model: &task::Model| -> ViewTask {
+        let is_visible = |field: &str| view_model.visible_fields.iter().any(|f| f == field);
+        ViewTask {
+            id: task_model.id,
+            key: task_key(&found_workspace.key_prefix, task_model.number),
+            task_group_id: is_visible("task_group_id").then_some(task_model.task_group_id),
+            title: is_visible("title").then(|| task_model.title.clone()),
+            description: is_visible("description").then(|| task_model.description.clone()),
+            labels_ids: is_visible("labels_ids")
+                .then(|| task_model.labels_ids.clone().unwrap_or_default()),
+            due_at: is_visible("due_at").then_some(task_model.due_at).flatten(),
+        }
+    };
+
+    let groups = match view_model.group_by.as_deref() {
+        Some("task_group") => task_groups
+            .iter()
+            .map(|task_group_model| ViewTasksGroup {
+                id: Some(task_group_model.id),
+                title: Some(task_group_model.title.clone()),
+                tasks: tasks_models
+                    .iter()
+                    .filter(|task_model| task_model.task_group_id == task_group_model.id)
+                    .map(to_view_task)
+                    .collect(),
+            })
+            .collect(),
+        Some("label") => {
+            let has_label = |task_model: &task::Model, label_id: i32| {
+                task_model
+                    .labels_ids
+                    .as_ref()
+                    .is_some_and(|labels_ids| labels_ids.contains(&label_id))
+            };
+
+            let mut label_groups: Vec<ViewTasksGroup> = labels
+                .iter()
+                .map(|label_model| ViewTasksGroup {
+                    id: Some(label_model.id),
+                    title: label_model.description.clone(),
+                    tasks: tasks_models
+                        .iter()
+                        .filter(|task_model| has_label(task_model, label_model.id))
+                        .map(to_view_task)
+                        .collect(),
+                })
+                .collect();
+            label_groups.push(ViewTasksGroup {
+                id: None,
+                title: None,
+                tasks: tasks_models
+                    .iter()
+                    .filter(|task_model| {
+                        task_model
+                            .labels_ids
+                            .as_ref()
+                            .is_none_or(|labels_ids| labels_ids.is_empty())
+                    })
+                    .map(to_view_task)
+                    .collect(),
+            });
+            label_groups
+        }
+        _ => vec![ViewTasksGroup {
+            id: None,
+            title: None,
+            tasks: tasks_models.iter().map(to_view_task).collect(),
+        }],
+    };
+
+    Ok(Json(SuccessResponse::new(ViewTasks {
+        view: SavedView::new(view_model, user.user_id),
+        groups,
+    })))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ViewTasks {
+    pub view: SavedView,
+    pub groups: Vec<ViewTasksGroup>,
+}
+
+/// Tasks of a task group or a label depending on grouping of the view,
+/// tasks which do not belong to any group are in a group without id
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ViewTasksGroup {
+    pub id: Option<i32>,
+    pub title: Option<String>,
+    pub tasks: Vec<ViewTask>,
+}
+
+/// Task with only the fields visible in the view, id and key are always present
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ViewTask {
+    pub id: i32,
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_group_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels_ids: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+}
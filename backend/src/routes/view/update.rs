@@ -0,0 +1,143 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{prelude::SavedView as SavedViewEntity, saved_view},
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::{
+    validate_filter, validate_option, validate_title, validate_visible_fields, SavedView,
+    GROUP_BY_OPTIONS, SORT_BY_OPTIONS, SORT_ORDER_OPTIONS,
+};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "view",
+    request_body = ModifiedViewData,
+    responses(
+        (status = 200, description = "Updated view", body = SavedViewResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[patch("/workspace/<workspace_id>/views/<view_id>", data = "<data>")]
+pub async fn handler(
+    workspace_id: i32,
+    view_id: i32,
+    data: Json<ModifiedViewData>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<SavedView>>, ErrorResponse> {
+    if data.title.is_none()
+        && data.filter.is_none()
+        && data.sort_by.is_none()
+        && data.sort_order.is_none()
+        && data.group_by.is_none()
+        && data.visible_fields.is_none()
+        && data.shared.is_none()
+    {
+        return Err(ErrorResponse::new(
+            Some("At least one property of the view has to be provided".into()),
+            Status::BadRequest,
+        ));
+    }
+
+    // Validate provided properties, empty group by removes grouping
+    let validation_errs = [
+        data.title.as_ref().and_then(validate_title),
+        data.filter.as_ref().and_then(validate_filter),
+        data.sort_by
+            .as_ref()
+            .and_then(|sort_by| validate_option(sort_by, &SORT_BY_OPTIONS, "Sort by")),
+        data.sort_order
+            .as_ref()
+            .and_then(|sort_order| validate_option(sort_order, &SORT_ORDER_OPTIONS, "Sort order")),
+        data.group_by
+            .as_ref()
+            .filter(|group_by| !group_by.is_empty())
+            .and_then(|group_by| validate_option(group_by, &GROUP_BY_OPTIONS, "Group by")),
+        data.visible_fields
+            .as_ref()
+            .and_then(|visible_fields| validate_visible_fields(visible_fields)),
+    ];
+    if let Some(err) = validation_errs.into_iter().flatten().next() {
+        return Err(err);
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Only owner of the view can modify it
+    let select_res = SavedViewEntity::find_by_id(view_id)
+        .filter(saved_view::Column::WorkspaceId.eq(workspace_id))
+        .filter(saved_view::Column::UserId.eq(user.user_id))
+        .one(&db)
+        .await;
+
+    let view_model = match select_res {
+        Ok(Some(view_model)) => view_model,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("View with id {} does not exist", view_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let mut view_to_update: saved_view::ActiveModel = view_model.into();
+    if let Some(title) = &data.title {
+        view_to_update.title = ActiveValue::Set(title.clone());
+    }
+    if let Some(filter) = &data.filter {
+        view_to_update.filter = ActiveValue::Set(filter.clone());
+    }
+    if let Some(sort_by) = &data.sort_by {
+        view_to_update.sort_by = ActiveValue::Set(sort_by.clone());
+    }
+    if let Some(sort_order) = &data.sort_order {
+        view_to_update.sort_order = ActiveValue::Set(sort_order.clone());
+    }
+    if let Some(group_by) = &data.group_by {
+        view_to_update.group_by = match group_by.is_empty() {
+            true => ActiveValue::Set(None),
+            false => ActiveValue::Set(Some(group_by.clone())),
+        };
+    }
+    if let Some(visible_fields) = &data.visible_fields {
+        view_to_update.visible_fields = ActiveValue::Set(visible_fields.clone());
+    }
+    if let Some(shared) = data.shared {
+        view_to_update.shared = ActiveValue::Set(shared);
+    }
+
+    match view_to_update.update(&db).await {
+        Ok(updated_view) => Ok(Json(SuccessResponse::new(SavedView::new(
+            updated_view,
+            user.user_id,
+        )))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ModifiedViewData {
+    pub title: Option<String>,
+    pub filter: Option<String>,
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,
+    pub group_by: Option<String>,
+    pub visible_fields: Option<Vec<String>>,
+    pub shared: Option<bool>,
+}
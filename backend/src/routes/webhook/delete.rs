@@ -0,0 +1,38 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::ModelTrait;
+
+use backend::establish_db_connection;
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::find_webhook;
+
+/// Deletes webhook together with its deliveries
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "webhook",
+    responses(
+        (status = 200, description = "Deleted webhook", body = EmptyResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/webhook/<webhook_id>")]
+pub async fn handler(
+    webhook_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let webhook_model = find_webhook(&db, webhook_id, user.user_id).await?;
+
+    match webhook_model.delete(&db).await {
+        Ok(_) => Ok(Json(SuccessResponse::new(()))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
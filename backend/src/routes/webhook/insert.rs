@@ -0,0 +1,102 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize, Serialize},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{prelude::Workspace, webhook, workspace},
+    establish_db_connection,
+};
+
+use crate::routes::lib::{
+    AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, SuccessResponse,
+};
+
+use super::lib::{validate_event_types, validate_secret, validate_url, Webhook};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "webhook",
+    request_body = NewWebhook,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Created webhook", body = WebhookResponse),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[post("/workspace/<workspace_id>/webhooks", data = "<data>")]
+pub async fn handler(
+    workspace_id: i32,
+    data: Json<NewWebhook>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey<'_>,
+) -> Result<Idempotent<Json<SuccessResponse<Webhook>>>, ErrorResponse> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    // Validate webhook
+    let validation_errs = [
+        validate_url(&data.url).await,
+        validate_secret(&data.secret),
+        validate_event_types(&data.event_types),
+    ];
+    if let Some(err) = validation_errs.into_iter().flatten().next() {
+        return Err(err);
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Validate workspace_id
+    let get_workspace_res = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    match get_workspace_res {
+        Ok(Some(_)) => (),
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    }
+
+    let insert_res = webhook::ActiveModel {
+        workspace_id: ActiveValue::Set(workspace_id),
+        user_id: ActiveValue::Set(user.user_id),
+        url: ActiveValue::Set(data.url.clone()),
+        secret: ActiveValue::Set(data.secret.clone()),
+        event_types: ActiveValue::Set(data.event_types.clone()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await;
+
+    match insert_res {
+        Ok(inserted_webhook) => Ok(Idempotent::Created(Json(SuccessResponse::new(
+            Webhook::new(inserted_webhook),
+        )))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewWebhook {
+    pub url: String,
+    /// Key of HMAC-SHA256 signature of deliveries
+    pub secret: String,
+    pub event_types: Vec<String>,
+}
@@ -0,0 +1,110 @@
+use reqwest::Url;
+use rocket::{http::Status, serde::Serialize};
+use sea_orm::{ColumnTrait, DbConn, EntityTrait, QueryFilter};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{
+        prelude::{Webhook as WebhookEntity, Workspace},
+        webhook, workspace,
+    },
+    services::webhooks::check_destination,
+};
+
+use crate::{
+    events::bus::EVENT_TYPES,
+    routes::lib::{validate_len, ErrorResponse},
+};
+
+/// Validates URL of a webhook, its host has to resolve to public addresses only
+pub async fn validate_url(url: &String) -> Option<ErrorResponse> {
+    if let Some(err) = validate_len(url, 1, 255, "Url") {
+        return Some(err);
+    }
+
+    match Url::parse(url) {
+        Ok(parsed_url) if ["http", "https"].contains(&parsed_url.scheme()) => (),
+        _ => {
+            return Some(ErrorResponse::new(
+                Some(format!("Url {} is not a valid http or https URL", url)),
+                Status::UnprocessableEntity,
+            ))
+        }
+    }
+
+    match check_destination(url).await {
+        Ok(_) => None,
+        Err(err) => Some(ErrorResponse::new(Some(err), Status::UnprocessableEntity)),
+    }
+}
+
+pub fn validate_secret(secret: &String) -> Option<ErrorResponse> {
+    validate_len(secret, 16, 255, "Secret")
+}
+
+pub fn validate_event_types(event_types: &[String]) -> Option<ErrorResponse> {
+    if event_types.is_empty() {
+        return Some(ErrorResponse::new(
+            Some("At least one event type has to be provided".into()),
+            Status::BadRequest,
+        ));
+    }
+
+    for event_type in event_types.iter() {
+        if !EVENT_TYPES.contains(&event_type.as_str()) {
+            return Some(ErrorResponse::new(
+                Some(format!(
+                    "Event type must be one of {}",
+                    EVENT_TYPES.join(", ")
+                )),
+                Status::UnprocessableEntity,
+            ));
+        }
+    }
+
+    None
+}
+
+/// Finds webhook of the user, webhooks of workspaces in trash can not be accessed
+pub async fn find_webhook(
+    db: &DbConn,
+    webhook_id: i32,
+    user_id: i32,
+) -> Result<webhook::Model, ErrorResponse> {
+    let select_webhook_res = WebhookEntity::find_by_id(webhook_id)
+        .filter(webhook::Column::UserId.eq(user_id))
+        .find_also_related(Workspace)
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(db)
+        .await;
+
+    match select_webhook_res {
+        Ok(Some((webhook_model, Some(_)))) => Ok(webhook_model),
+        Ok(_) => Err(ErrorResponse::new(
+            Some(format!("Webhook with id {} does not exist", webhook_id)),
+            Status::NotFound,
+        )),
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
+
+/// Webhook without its secret, the secret can only be replaced
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Webhook {
+    pub id: i32,
+    pub workspace_id: i32,
+    pub url: String,
+    pub event_types: Vec<String>,
+}
+
+impl Webhook {
+    pub fn new(webhook_model: webhook::Model) -> Webhook {
+        Webhook {
+            id: webhook_model.id,
+            workspace_id: webhook_model.workspace_id,
+            url: webhook_model.url,
+            event_types: webhook_model.event_types,
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod delete;
+pub mod insert;
+pub mod lib;
+pub mod select_all;
+pub mod select_deliveries;
+pub mod update;
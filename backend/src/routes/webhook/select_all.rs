@@ -0,0 +1,66 @@
+use rocket::{http::Status, serde::json::Json};
+use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder};
+
+use backend::{
+    entities::{
+        prelude::{Webhook as WebhookEntity, Workspace},
+        webhook, workspace,
+    },
+    establish_db_connection,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::Webhook;
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "webhook",
+    responses(
+        (status = 200, description = "Webhooks of the workspace", body = WebhooksResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>/webhooks")]
+pub async fn handler(
+    workspace_id: i32,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Vec<Webhook>>>, ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_workspace_res = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    let found_workspace = match select_workspace_res {
+        Ok(Some(workspace_model)) => workspace_model,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let found_webhooks_res = found_workspace
+        .find_related(WebhookEntity)
+        .filter(webhook::Column::UserId.eq(user.user_id))
+        .order_by_asc(webhook::Column::Id)
+        .all(&db)
+        .await;
+
+    match found_webhooks_res {
+        Ok(webhooks_models) => Ok(Json(SuccessResponse::new(
+            webhooks_models.into_iter().map(Webhook::new).collect(),
+        ))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
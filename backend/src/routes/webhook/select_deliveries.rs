@@ -0,0 +1,104 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{prelude::WebhookDelivery, webhook_delivery},
+    establish_db_connection,
+    services::webhooks::DELIVERY_PENDING,
+};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, Pagination, SuccessResponse};
+
+use super::lib::find_webhook;
+
+/// Delivery log of the webhook, oldest deliveries first
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "webhook",
+    responses(
+        (status = 200, description = "One page of deliveries", body = FoundDeliveriesResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/webhook/<webhook_id>/deliveries?<limit>&<cursor>")]
+pub async fn handler(
+    webhook_id: i32,
+    limit: Option<u64>,
+    cursor: Option<String>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Vec<FoundDelivery>>>, ErrorResponse> {
+    let pagination = Pagination::new(limit, cursor)?;
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let webhook_model = find_webhook(&db, webhook_id, user.user_id).await?;
+
+    let found_deliveries_res = webhook_model
+        .find_related(WebhookDelivery)
+        .filter(webhook_delivery::Column::Id.gt(pagination.after_id))
+        .order_by_asc(webhook_delivery::Column::Id)
+        .limit(pagination.select_limit())
+        .all(&db)
+        .await;
+
+    match found_deliveries_res {
+        Ok(deliveries_models) => {
+            let (deliveries_models, next_cursor) =
+                pagination.page(deliveries_models, |delivery_model| delivery_model.id);
+            let deliveries = deliveries_models
+                .into_iter()
+                .map(|delivery_model| FoundDelivery {
+                    id: delivery_model.id,
+                    event_id: delivery_model.event_id,
+                    event_type: delivery_model.event_type,
+                    next_attempt_at: (delivery_model.status == DELIVERY_PENDING)
+                        .then_some(delivery_model.next_attempt_at),
+                    status: delivery_model.status,
+                    attempts: delivery_model.attempts,
+                    response_status: delivery_model.response_status,
+                    response_time_ms: delivery_model.response_time_ms,
+                    error: delivery_model.error,
+                    created_at: delivery_model.created_at,
+                    delivered_at: delivery_model.delivered_at,
+                })
+                .collect();
+            Ok(Json(SuccessResponse::paginated(deliveries, next_cursor)))
+        }
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+/// Delivery with the result of its last attempt
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct FoundDelivery {
+    pub id: i32,
+    pub event_id: i64,
+    pub event_type: String,
+    /// pending, succeeded or failed
+    pub status: String,
+    pub attempts: i32,
+    /// Status code the endpoint responded with
+    pub response_status: Option<i32>,
+    pub response_time_ms: Option<i32>,
+    pub error: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTimeWithTimeZone,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub delivered_at: Option<DateTimeWithTimeZone>,
+    /// Only pending deliveries are attempted again
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub next_attempt_at: Option<DateTimeWithTimeZone>,
+}
@@ -0,0 +1,85 @@
+use rocket::{
+    http::Status,
+    serde::{json::Json, Deserialize},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue};
+use utoipa::ToSchema;
+
+use backend::{entities::webhook, establish_db_connection};
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+
+use super::lib::{find_webhook, validate_event_types, validate_secret, validate_url, Webhook};
+
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "webhook",
+    request_body = WebhookDataToUpdate,
+    responses(
+        (status = 200, description = "Updated webhook", body = WebhookResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[patch("/webhook/<webhook_id>", data = "<data>")]
+pub async fn handler(
+    webhook_id: i32,
+    data: Json<WebhookDataToUpdate>,
+    user: AuthenticatedUser,
+) -> Result<Json<SuccessResponse<Webhook>>, ErrorResponse> {
+    if data.url.is_none() && data.secret.is_none() && data.event_types.is_none() {
+        return Err(ErrorResponse::new(
+            Some("Either url, secret or event_types has to be provided".into()),
+            Status::BadRequest,
+        ));
+    }
+
+    // Validate provided properties
+    let url_err = match &data.url {
+        Some(url) => validate_url(url).await,
+        None => None,
+    };
+    let validation_errs = [
+        url_err,
+        data.secret.as_ref().and_then(validate_secret),
+        data.event_types
+            .as_ref()
+            .and_then(|event_types| validate_event_types(event_types)),
+    ];
+    if let Some(err) = validation_errs.into_iter().flatten().next() {
+        return Err(err);
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let webhook_model = find_webhook(&db, webhook_id, user.user_id).await?;
+
+    let mut webhook_to_update: webhook::ActiveModel = webhook_model.into();
+    if let Some(url) = &data.url {
+        webhook_to_update.url = ActiveValue::Set(url.clone());
+    }
+    if let Some(secret) = &data.secret {
+        webhook_to_update.secret = ActiveValue::Set(secret.clone());
+    }
+    if let Some(event_types) = &data.event_types {
+        webhook_to_update.event_types = ActiveValue::Set(event_types.clone());
+    }
+
+    match webhook_to_update.update(&db).await {
+        Ok(updated_webhook) => Ok(Json(SuccessResponse::new(Webhook::new(updated_webhook)))),
+        Err(err) => Err(server_err_response.with_source(err)),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct WebhookDataToUpdate {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub event_types: Option<Vec<String>>,
+}
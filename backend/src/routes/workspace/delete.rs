@@ -1,93 +1,101 @@
-use rocket::{http::Status, serde::json::Json};
-use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, TransactionError, TransactionTrait, Condition};
+use async_graphql::SimpleObject;
+use rocket::{
+    http::Status,
+    serde::{json::Json, Serialize},
+    State,
+};
+use sea_orm::TransactionTrait;
+use utoipa::ToSchema;
+
+use backend::establish_db_connection;
 
-use backend::{
-    entities::{
-        label,
-        prelude::{Label, Task, TaskGroup, Workspace},
-        task, task_group, workspace,
+use crate::{
+    events::bus::EventBus,
+    routes::{
+        lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, SuccessResponse},
+        workspace::{
+            lib::delete_workspace,
+            update::{find_workspace, SavedModifiedWorkspace},
+        },
     },
-    establish_db_connection,
 };
 
-use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
-
-#[delete("/workspace/<workspace_id>")]
+/// Moves workspace to trash, requests with `If-Match` header or `version` are rejected
+/// when the workspace was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    params(
+        ("If-Match" = Option<String>, Header, description = "ETag of the deleted version"),
+        ("version" = Option<i32>, Query, description = "Version of the deleted workspace, `If-Match` header takes precedence")
+    ),
+    responses(
+        (status = 200, description = "Workspace moved to trash", body = DeletedWorkspaceResponse),
+        (status = 409, description = "Workspace was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Workspace was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[delete("/workspace/<workspace_id>?<version>")]
 pub async fn handler(
     workspace_id: i32,
+    version: Option<i32>,
+    if_match: IfMatch,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<()>>, ErrorResponse> {
-    let server_error_response = ErrorResponse::new(None, Status::InternalServerError);
-    let db = establish_db_connection().await;
-
-    if let Err(_) = db {
-        return Err(server_error_response);
+    event_bus: &State<EventBus>,
+) -> Result<Json<SuccessResponse<DeletedWorkspace>>, ErrorResponse> {
+    let expected_version = ExpectedVersion::new(if_match, version);
+    let not_found_response = ErrorResponse::new(
+        format!("Workspace with id {} does not exist", workspace_id).into(),
+        Status::NotFound,
+    );
+    let db = match establish_db_connection().await {
+        Ok(db) => db,
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    let workspace_model = match find_workspace(&db, workspace_id, user.user_id).await {
+        Ok(Some(workspace_model)) => workspace_model,
+        Ok(None) => return Err(not_found_response),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    if !expected_version.matches(workspace_model.version) {
+        return Err(expected_version.conflict_response(
+            &SavedModifiedWorkspace::new(workspace_model.clone()),
+            workspace_model.version,
+        ));
     }
 
-    let delete_result = db
-        .unwrap()
-        .transaction::<_, (), DbErr>(|tx| {
-            Box::pin(async move {
-                // Delete workspace
-                let workspace_to_delete = workspace::ActiveModel {
-                    id: sea_orm::ActiveValue::Set(workspace_id),
-                    user_id: sea_orm::ActiveValue::Set(user.user_id),
-                    ..Default::default()
-                };
-                let delete_workspace_res = Workspace::delete(workspace_to_delete).exec(tx).await?;
-
-                if delete_workspace_res.rows_affected == 0 {
-                    return Err(DbErr::Custom(format!(
-                        "Workspace with id {} does not exist",
-                        workspace_id
-                    )));
-                }
-
-                // Find all related task groups
-                let task_groups = TaskGroup::find()
-                    .filter(task_group::Column::UserId.eq(user.user_id))
-                    .filter(task_group::Column::WorkspaceId.eq(workspace_id))
-                    .all(tx)
-                    .await?;
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let deleted_counts =
+        delete_workspace(&tx, event_bus, &workspace_model, &expected_version).await?;
+    let deleted = match deleted_counts {
+        Some(deleted) => deleted,
+        // Workspace was changed or moved to trash since it was selected
+        None => {
+            return match find_workspace(&db, workspace_id, user.user_id).await {
+                Ok(Some(workspace_model)) => Err(expected_version.conflict_response(
+                    &SavedModifiedWorkspace::new(workspace_model.clone()),
+                    workspace_model.version,
+                )),
+                Ok(None) => Err(not_found_response),
+                Err(err) => Err(ErrorResponse::server_error(err)),
+            };
+        }
+    };
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
 
-                // Delete related tasks
-                if task_groups.len() > 0 {
-                    let delete_tasks_stmt =
-                        Task::delete_many().filter(task::Column::UserId.eq(user.user_id));
-                    let mut delete_tasks_condition = Condition::any();
-                    for task_group in task_groups.iter() {
-                        let task_group_id = task_group.id;
-                        delete_tasks_condition = delete_tasks_condition.add(task::Column::Id.eq(task_group_id));
-                    }
-                    delete_tasks_stmt.filter(delete_tasks_condition).exec(tx).await?;
-                }
-
-                // Delete task groups
-                TaskGroup::delete_many()
-                    .filter(task_group::Column::WorkspaceId.eq(workspace_id))
-                    .filter(task_group::Column::UserId.eq(user.user_id))
-                    .exec(tx)
-                    .await?;
-
-                // Delete labels
-                Label::delete_many()
-                    .filter(label::Column::WorkspaceId.eq(workspace_id))
-                    .filter(label::Column::UserId.eq(user.user_id))
-                    .exec(tx)
-                    .await?;
-
-                Ok(())
-            })
-        })
-        .await;
+    Ok(Json(SuccessResponse::new(DeletedWorkspace {
+        task_groups: deleted.task_groups,
+        tasks: deleted.tasks,
+        labels: deleted.labels,
+    })))
+}
 
-    match delete_result {
-        Ok(_) => Ok(Json(SuccessResponse::new(()))),
-        Err(delete_err) => match delete_err {
-            TransactionError::Transaction(DbErr::Custom(err)) => {
-                Err(ErrorResponse::new(Some(err), Status::NotFound))
-            }
-            _ => Err(ErrorResponse::new(None, Status::InternalServerError)),
-        },
-    }
+#[derive(Serialize, ToSchema, SimpleObject)]
+#[serde(crate = "rocket::serde")]
+pub struct DeletedWorkspace {
+    pub task_groups: u64,
+    pub tasks: u64,
+    pub labels: u64,
 }
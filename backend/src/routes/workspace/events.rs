@@ -0,0 +1,130 @@
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    response::stream::{Event, EventStream},
+    serde::json::json,
+    tokio::{select, sync::broadcast::error::RecvError},
+    Request, Shutdown, State,
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+use backend::{
+    entities::{prelude::Workspace, workspace},
+    establish_db_connection,
+};
+
+use crate::{
+    events::bus::{BusMessage, EventBus, WorkspaceEvent, WorkspaceEventKind},
+    routes::lib::{AuthenticatedUser, ErrorResponse},
+};
+
+/// Stream of changes made in the workspace, see `WorkspaceEventKind` for the event types.
+///
+/// Client which reconnects with `Last-Event-ID` header receives the events it has missed,
+/// if they are no longer available it receives a `resync` event and has to reload the workspace.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    params(
+        ("Last-Event-ID" = Option<String>, Header, description = "Id of the last received event")
+    ),
+    responses(
+        (status = 200, description = "Stream of workspace events", body = String, content_type = "text/event-stream"),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>/events")]
+pub async fn handler(
+    workspace_id: i32,
+    user: AuthenticatedUser,
+    last_event_id: LastEventId,
+    event_bus: &State<EventBus>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], ErrorResponse> {
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let select_workspace_res = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(&db)
+        .await;
+    match select_workspace_res {
+        Ok(Some(_)) => (),
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    }
+
+    let mut subscription = event_bus.subscribe(workspace_id, last_event_id.0);
+
+    Ok(EventStream! {
+        if subscription.resync_required {
+            yield resync_event(subscription.last_event_id);
+        }
+        for missed_event in subscription.missed_events.iter() {
+            yield to_sse_event(missed_event);
+        }
+
+        loop {
+            let received = select! {
+                received = subscription.receiver.recv() => received,
+                _ = &mut shutdown => break,
+            };
+
+            match received {
+                Ok(BusMessage::Event(event)) => {
+                    if event.workspace_id != workspace_id {
+                        continue;
+                    }
+
+                    yield to_sse_event(&event);
+                    if let WorkspaceEventKind::WorkspaceDeleted = event.kind {
+                        break;
+                    }
+                }
+                // Events were lost before reaching the instance or the stream fell behind
+                Ok(BusMessage::Resync) | Err(RecvError::Lagged(_)) => yield resync_event(None),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+fn to_sse_event(event: &WorkspaceEvent) -> Event {
+    Event::json(event).id(event.id.to_string())
+}
+
+fn resync_event(last_event_id: Option<u64>) -> Event {
+    let event = Event::json(&json!({ "type": "resync" }));
+    match last_event_id {
+        Some(id) => event.id(id.to_string()),
+        None => event,
+    }
+}
+
+/// Id of the last event received by a reconnecting client
+pub struct LastEventId(Option<u64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let last_event_id = request
+            .headers()
+            .get_one("Last-Event-ID")
+            .and_then(|id| id.trim().parse::<u64>().ok());
+
+        Outcome::Success(LastEventId(last_event_id))
+    }
+}
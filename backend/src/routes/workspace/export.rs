@@ -0,0 +1,157 @@
+use rocket::{
+    http::{ContentType, Header, Status},
+    serde::json::serde_json,
+};
+
+use backend::establish_db_connection;
+
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse};
+
+use super::lib::{build_export_document, ExportDocument};
+
+/// Exports the workspace as a versioned JSON document, which can be imported back,
+/// or as a Markdown file for reading
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    responses(
+        (status = 200, description = "Export document, Markdown file with `format=markdown`", body = ExportDocument),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>/export?<format>")]
+pub async fn handler(
+    workspace_id: i32,
+    format: Option<String>,
+    user: AuthenticatedUser,
+) -> Result<ExportFile, ErrorResponse> {
+    let format = format.unwrap_or_else(|| "json".into());
+    if format != "json" && format != "markdown" {
+        return Err(ErrorResponse::new(
+            Some("Format must be either json or markdown".into()),
+            Status::BadRequest,
+        ));
+    }
+
+    let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    let document = match build_export_document(&db, user.user_id, workspace_id).await {
+        Ok(Some(document)) => document,
+        Ok(None) => {
+            return Err(ErrorResponse::new(
+                Some(format!("Workspace with id {} does not exist", workspace_id)),
+                Status::NotFound,
+            ))
+        }
+        Err(err) => return Err(server_err_response.with_source(err)),
+    };
+
+    let (content, content_type, extension) = match format.as_str() {
+        "markdown" => (to_markdown(&document), ContentType::Markdown, "md"),
+        _ => match serde_json::to_string_pretty(&document) {
+            Ok(content) => (content, ContentType::JSON, "json"),
+            Err(err) => return Err(server_err_response.with_source(err)),
+        },
+    };
+    Ok(ExportFile::new(
+        content_type,
+        content,
+        format!("workspace-{}.{}", workspace_id, extension),
+    ))
+}
+
+#[derive(Responder)]
+pub struct ExportFile {
+    content: (ContentType, String),
+    disposition: Header<'static>,
+}
+
+impl ExportFile {
+    /// File which browsers download instead of displaying
+    pub fn new(content_type: ContentType, content: String, filename: String) -> ExportFile {
+        ExportFile {
+            content: (content_type, content),
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        }
+    }
+}
+
+/// One section per task group with a checkbox list of its tasks,
+/// tasks of the done task group of the git integration are checked
+fn to_markdown(document: &ExportDocument) -> String {
+    let mut markdown = format!("# {}\n\n", escape_markdown(&document.workspace.title));
+    if let Some(description) = &document.workspace.description {
+        markdown.push_str(&format!("{}\n\n", escape_markdown(description)));
+    }
+
+    if !document.labels.is_empty() {
+        markdown.push_str("## Labels\n\n");
+        for label in document.labels.iter() {
+            markdown.push_str(&format!(
+                "- `{}` {}\n",
+                label.color,
+                escape_markdown(label.description.as_deref().unwrap_or_default())
+            ));
+        }
+        markdown.push('\n');
+    }
+
+    for task_group in document.task_groups.iter() {
+        markdown.push_str(&format!("## {}\n\n", escape_markdown(&task_group.title)));
+        if task_group.tasks.is_empty() {
+            markdown.push_str("_No tasks_\n\n");
+            continue;
+        }
+
+        let checkbox = if task_group.done { "[x]" } else { "[ ]" };
+        for task in task_group.tasks.iter() {
+            markdown.push_str(&format!(
+                "- {} **{}** {}\n",
+                checkbox,
+                task.key,
+                escape_markdown(&task.title)
+            ));
+            // Description is indented so it stays part of the list item
+            for line in task
+                .description
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+            {
+                markdown.push_str(&format!("  {}\n", escape_markdown(line.trim())));
+            }
+
+            let labels_descriptions: Vec<String> = task
+                .labels_ids
+                .iter()
+                .filter_map(|label_id| document.labels.iter().find(|l| &l.id == label_id))
+                .map(|label| escape_markdown(label.description.as_deref().unwrap_or(&label.color)))
+                .collect();
+            if !labels_descriptions.is_empty() {
+                markdown.push_str(&format!("  Labels: {}\n", labels_descriptions.join(", ")));
+            }
+        }
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]#<>|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
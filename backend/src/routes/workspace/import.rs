@@ -0,0 +1,249 @@
+use rocket::{
+    data::{Data, ToByteUnit},
+    http::Status,
+    serde::{
+        json::{serde_json, Json, Value},
+        Serialize,
+    },
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, DbErr, EntityTrait, TransactionError, TransactionTrait,
+};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use backend::{
+    entities::{label, prelude::Task, task, task_group, workspace},
+    establish_db_connection,
+    services::task_keys::{available_key_prefix, is_key_prefix_conflict, is_key_prefix_taken},
+};
+
+use crate::routes::lib::{
+    AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, SuccessResponse,
+};
+
+use super::lib::{
+    key_prefix_conflict_response, prepare_import, ExportDocument, SkippedItem, TrelloBoard,
+    EXPORT_VERSION,
+};
+
+const IMPORT_LIMIT_MIB: usize = 10;
+/// Every task binds a parameter per column and Postgres allows at most 65535 per statement
+const TASKS_PER_INSERT: usize = 500;
+
+/// Creates a new workspace from a document of `GET /workspace/<id>/export?format=json`
+/// or from a Trello board export, everything is created in a single transaction.
+///
+/// Items which can not be imported are skipped and listed in the response.
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    request_body(content = ExportDocument, description = "Export document or Trello board JSON"),
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Imported workspace", body = ImportedWorkspaceResponse),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[post("/workspace/import", data = "<data>")]
+pub async fn handler(
+    data: Data<'_>,
+    user: AuthenticatedUser,
+    idempotency_key: IdempotencyKey<'_>,
+) -> Result<Idempotent<Json<SuccessResponse<ImportedWorkspace>>>, ErrorResponse> {
+    let read_res = data.open(IMPORT_LIMIT_MIB.mebibytes()).into_string().await;
+    let content = match read_res {
+        Ok(content) if content.is_complete() => content.into_inner(),
+        Ok(_) => {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Imported document can not be larger than {} MiB",
+                    IMPORT_LIMIT_MIB
+                )),
+                Status::PayloadTooLarge,
+            ))
+        }
+        Err(err) => return Err(ErrorResponse::body_read_error(err)),
+    };
+
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &content).await? {
+        return Ok(Idempotent::Replayed(stored_response));
+    }
+
+    let mut skipped: Vec<SkippedItem> = vec![];
+    let document = parse_document(&content, &mut skipped)?;
+    let document = prepare_import(document, &mut skipped);
+
+    let db_res = establish_db_connection().await;
+    if let Err(err) = &db_res {
+        return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err));
+    }
+    let db = db_res.unwrap();
+
+    // Prefix of the exported workspace is kept unless it is invalid or the user already uses it
+    let key_prefix_res = match document.workspace.key_prefix.as_str() {
+        "" => Ok(true),
+        key_prefix => is_key_prefix_taken(&db, user.user_id, key_prefix, None).await,
+    };
+    let key_prefix_res = match key_prefix_res {
+        Ok(false) => Ok(document.workspace.key_prefix.clone()),
+        Ok(true) => available_key_prefix(&db, user.user_id, &document.workspace.title).await,
+        Err(err) => Err(err),
+    };
+    let key_prefix = match key_prefix_res {
+        Ok(key_prefix) => key_prefix,
+        Err(err) => {
+            return Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err))
+        }
+    };
+
+    let user_id = user.user_id;
+    let inserted_key_prefix = key_prefix.clone();
+    let tx_res = db
+        .transaction::<_, ImportedWorkspace, DbErr>(|tx| {
+            Box::pin(async move {
+                let tasks_count: usize = document
+                    .task_groups
+                    .iter()
+                    .map(|task_group| task_group.tasks.len())
+                    .sum();
+
+                // Workspace is new, so its tasks are numbered from 1
+                let inserted_workspace = workspace::ActiveModel {
+                    user_id: ActiveValue::Set(user_id),
+                    title: ActiveValue::Set(document.workspace.title),
+                    description: ActiveValue::Set(document.workspace.description),
+                    key_prefix: ActiveValue::Set(inserted_key_prefix),
+                    next_task_number: ActiveValue::Set(tasks_count as i32 + 1),
+                    ..Default::default()
+                }
+                .insert(tx)
+                .await?;
+
+                let mut labels_ids: HashMap<i32, i32> = HashMap::new();
+                for label in document.labels.iter() {
+                    let inserted_label = label::ActiveModel {
+                        user_id: ActiveValue::Set(user_id),
+                        workspace_id: ActiveValue::Set(inserted_workspace.id),
+                        color: ActiveValue::Set(label.color.clone()),
+                        description: ActiveValue::Set(label.description.clone()),
+                        ..Default::default()
+                    }
+                    .insert(tx)
+                    .await?;
+                    labels_ids.insert(label.id, inserted_label.id);
+                }
+
+                let task_groups_count = document.task_groups.len();
+                let mut tasks_to_insert: Vec<task::ActiveModel> = vec![];
+                for task_group in document.task_groups.into_iter() {
+                    let inserted_task_group = task_group::ActiveModel {
+                        user_id: ActiveValue::Set(user_id),
+                        workspace_id: ActiveValue::Set(inserted_workspace.id),
+                        title: ActiveValue::Set(task_group.title),
+                        ..Default::default()
+                    }
+                    .insert(tx)
+                    .await?;
+
+                    for task in task_group.tasks.into_iter() {
+                        let task_labels_ids: Vec<i32> = task
+                            .labels_ids
+                            .iter()
+                            .filter_map(|label_id| labels_ids.get(label_id).copied())
+                            .collect();
+                        tasks_to_insert.push(task::ActiveModel {
+                            number: ActiveValue::Set(tasks_to_insert.len() as i32 + 1),
+                            user_id: ActiveValue::Set(user_id),
+                            task_group_id: ActiveValue::Set(inserted_task_group.id),
+                            title: ActiveValue::Set(task.title),
+                            description: ActiveValue::Set(task.description),
+                            due_at: ActiveValue::Set(task.due_at),
+                            labels_ids: ActiveValue::Set(
+                                (!task_labels_ids.is_empty()).then_some(task_labels_ids),
+                            ),
+                            ..Default::default()
+                        });
+                    }
+                }
+                for tasks_chunk in tasks_to_insert.chunks(TASKS_PER_INSERT) {
+                    Task::insert_many(tasks_chunk.to_vec()).exec(tx).await?;
+                }
+
+                Ok(ImportedWorkspace {
+                    id: inserted_workspace.id,
+                    title: inserted_workspace.title,
+                    key_prefix: inserted_workspace.key_prefix,
+                    labels_count: labels_ids.len(),
+                    task_groups_count,
+                    tasks_count,
+                    skipped: vec![],
+                })
+            })
+        })
+        .await;
+
+    match tx_res {
+        Ok(mut imported_workspace) => {
+            imported_workspace.skipped = skipped;
+            Ok(Idempotent::Created(Json(SuccessResponse::new(
+                imported_workspace,
+            ))))
+        }
+        Err(TransactionError::Transaction(err)) if is_key_prefix_conflict(&err) => {
+            Err(key_prefix_conflict_response(&key_prefix))
+        }
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
+    }
+}
+
+/// Export documents are recognized by `version`, Trello boards by `lists` and `cards`
+fn parse_document(
+    content: &str,
+    skipped: &mut Vec<SkippedItem>,
+) -> Result<ExportDocument, ErrorResponse> {
+    let invalid_document_response = |err: serde_json::Error| {
+        ErrorResponse::new(
+            Some(format!("Imported document is not valid: {}", err)),
+            Status::UnprocessableEntity,
+        )
+    };
+
+    let value: Value = serde_json::from_str(content).map_err(invalid_document_response)?;
+    if let Some(version) = value.get("version") {
+        if version.as_u64() != Some(EXPORT_VERSION as u64) {
+            return Err(ErrorResponse::new(
+                Some(format!(
+                    "Export version {} is not supported, supported version is {}",
+                    version, EXPORT_VERSION
+                )),
+                Status::UnprocessableEntity,
+            ));
+        }
+        return serde_json::from_value(value).map_err(invalid_document_response);
+    }
+    if value.get("lists").is_some() && value.get("cards").is_some() {
+        let board: TrelloBoard =
+            serde_json::from_value(value).map_err(invalid_document_response)?;
+        return Ok(board.into_export_document(skipped));
+    }
+
+    Err(ErrorResponse::new(
+        Some("Imported document is neither a workspace export nor a Trello board".into()),
+        Status::UnprocessableEntity,
+    ))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportedWorkspace {
+    pub id: i32,
+    pub title: String,
+    pub key_prefix: String,
+    pub labels_count: usize,
+    pub task_groups_count: usize,
+    pub tasks_count: usize,
+    pub skipped: Vec<SkippedItem>,
+}
@@ -1,74 +1,77 @@
-use backend::{entities::workspace, establish_db_connection};
-use rocket::{
-    http::Status,
-    serde::{json::Json, Deserialize, Serialize},
-};
-use sea_orm::{ActiveModelTrait, ActiveValue};
+use async_graphql::InputObject;
+use backend::establish_db_connection;
+use rocket::serde::{json::Json, Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+use crate::routes::{
+    lib::{AuthenticatedUser, ErrorResponse, IdempotencyKey, Idempotent, VersionedResponse},
+    workspace::lib::insert_workspace,
+};
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    request_body = NewWorkspace,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key of the request, retries with the same key get the response of the first request")),
+    responses(
+        (status = 200, description = "Created workspace", body = InsertedWorkspaceResponse,
+            headers(("ETag" = String, description = "Version of the workspace"))),
+        (status = 409, description = "Request with the same `Idempotency-Key` is in progress", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[post("/workspace", data = "<data>")]
 pub async fn handler(
     data: Json<NewWorkspace>,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<InsertedWorkspace>>, ErrorResponse> {
-    if data.title.len() > 50 && data.title.len() < 1 {
-        return Err(ErrorResponse::new(
-            Some("Title length must be between 50 and 1 characters".into()),
-            Status::BadRequest,
-        ));
-    }
-
-    match &data.description {
-        Some(desc) => {
-            if desc.len() > 255 || desc.len() < 1 {
-                return Err(ErrorResponse::new(
-                    Some("Description length must be between 255 and 1 characters".into()),
-                    Status::BadRequest,
-                ));
-            }
-        }
-        None => (),
-    }
-
-    let db_res = establish_db_connection().await;
-    if let Err(_) = db_res {
-        return Err(ErrorResponse::new(None, Status::InternalServerError));
+    idempotency_key: IdempotencyKey<'_>,
+) -> Result<Idempotent<VersionedResponse<InsertedWorkspace>>, ErrorResponse> {
+    if let Some(stored_response) = idempotency_key.claim(user.user_id, &*data).await? {
+        return Ok(Idempotent::Replayed(stored_response));
     }
-    let db = db_res.unwrap();
 
-    let inserted_workspace_res = workspace::ActiveModel {
-        user_id: ActiveValue::Set(user.user_id),
-        title: ActiveValue::Set(data.title.clone()),
-        description: ActiveValue::Set(data.description.clone()),
-        ..Default::default()
-    }
-    .save(&db)
-    .await;
+    create(data, user).await.map(Idempotent::Created)
+}
 
-    if let Err(_) = inserted_workspace_res {
-        return Err(ErrorResponse::new(None, Status::InternalServerError));
-    }
+/// Creates the workspace, also run by the `createWorkspace` mutation
+pub async fn create(
+    data: Json<NewWorkspace>,
+    user: AuthenticatedUser,
+) -> Result<VersionedResponse<InsertedWorkspace>, ErrorResponse> {
+    let db = match establish_db_connection().await {
+        Ok(db) => db,
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    let inserted_workspace = insert_workspace(&db, user.user_id, &data).await?;
 
-    let inserted_workspace = inserted_workspace_res.unwrap();
-    Ok(Json(SuccessResponse::new(InsertedWorkspace {
-        id: inserted_workspace.id.unwrap(),
-        title: inserted_workspace.title.unwrap(),
-        description: inserted_workspace.description.unwrap(),
-    })))
+    let version = inserted_workspace.version;
+    Ok(VersionedResponse::new(
+        InsertedWorkspace {
+            id: inserted_workspace.id,
+            title: inserted_workspace.title,
+            description: inserted_workspace.description,
+            key_prefix: inserted_workspace.key_prefix,
+            version,
+        },
+        version,
+    ))
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct NewWorkspace {
     pub title: String,
     pub description: Option<String>,
+    pub key_prefix: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct InsertedWorkspace {
     pub id: i32,
     pub title: String,
     pub description: Option<String>,
+    pub key_prefix: String,
+    pub version: i32,
 }
@@ -0,0 +1,665 @@
+use rocket::{
+    http::Status,
+    serde::{Deserialize, Serialize},
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait,
+    DatabaseTransaction, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use backend::{
+    current_timestamp,
+    entities::{
+        label,
+        prelude::{GitIntegration, Label, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+    services::deletion::{trash_workspace_in, DeletedCounts},
+    services::task_keys::default_key_prefix,
+    services::task_keys::{
+        available_key_prefix, is_key_prefix_conflict, is_key_prefix_taken, is_valid_key_prefix,
+        task_key,
+    },
+};
+
+use crate::{
+    events::bus::{EventBus, WorkspaceEventKind},
+    routes::{
+        label::lib::validate_label_color,
+        lib::{validate_len, ErrorResponse, ExpectedVersion},
+        workspace::{insert::NewWorkspace, update::ModifiedWorkspaceData},
+    },
+};
+
+/// Key prefixes are stored uppercase, returns the normalized prefix
+pub fn validate_key_prefix(key_prefix: &str) -> Result<String, ErrorResponse> {
+    let key_prefix = key_prefix.to_uppercase();
+    if !is_valid_key_prefix(&key_prefix) {
+        return Err(ErrorResponse::new(
+            Some("Key prefix must have 2 to 10 letters or digits and start with a letter".into()),
+            Status::BadRequest,
+        ));
+    }
+    Ok(key_prefix)
+}
+
+pub fn key_prefix_conflict_response(key_prefix: &str) -> ErrorResponse {
+    ErrorResponse::new(
+        Some(format!(
+            "Key prefix {} is already used by another workspace",
+            key_prefix
+        )),
+        Status::Conflict,
+    )
+}
+
+/// Inserts the workspace, its key prefix is derived from the title when it is not provided.
+/// Used by `POST /workspace` and batches.
+pub async fn insert_workspace<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    data: &NewWorkspace,
+) -> Result<workspace::Model, ErrorResponse> {
+    if let Some(err) = validate_len(&data.title, 1, 50, "Title") {
+        return Err(err);
+    }
+    if let Some(desc) = &data.description {
+        if let Some(err) = validate_len(desc, 1, 255, "Description") {
+            return Err(err);
+        }
+    }
+
+    let key_prefix = match &data.key_prefix {
+        Some(key_prefix) => {
+            let key_prefix = validate_key_prefix(key_prefix)?;
+            let is_taken = is_key_prefix_taken(db, user_id, &key_prefix, None)
+                .await
+                .map_err(ErrorResponse::server_error)?;
+            if is_taken {
+                return Err(key_prefix_conflict_response(&key_prefix));
+            }
+            key_prefix
+        }
+        None => available_key_prefix(db, user_id, &data.title)
+            .await
+            .map_err(ErrorResponse::server_error)?,
+    };
+
+    workspace::ActiveModel {
+        user_id: ActiveValue::Set(user_id),
+        title: ActiveValue::Set(data.title.clone()),
+        description: ActiveValue::Set(data.description.clone()),
+        key_prefix: ActiveValue::Set(key_prefix.clone()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|err| match is_key_prefix_conflict(&err) {
+        true => key_prefix_conflict_response(&key_prefix),
+        false => ErrorResponse::server_error(err),
+    })
+}
+
+/// Updates the workspace selected as `workspace_model` and publishes the change.
+///
+/// Returns `None` when the workspace was changed or moved to trash since it was selected,
+/// callers tell the two apart. Used by `PATCH /workspace/<id>` and batches.
+pub async fn update_workspace<C: ConnectionTrait>(
+    db: &C,
+    event_bus: &EventBus,
+    workspace_model: workspace::Model,
+    data: &ModifiedWorkspaceData,
+    expected_version: &ExpectedVersion,
+) -> Result<Option<workspace::Model>, ErrorResponse> {
+    if data.title.is_none() && data.description.is_none() && data.key_prefix.is_none() {
+        return Err(ErrorResponse::new(
+            Some("Either title, description or key prefix has to be provided".into()),
+            Status::BadRequest,
+        ));
+    }
+
+    let workspace_id = workspace_model.id;
+    let user_id = workspace_model.user_id;
+    let version = workspace_model.version;
+    let mut workspace_to_update: workspace::ActiveModel = workspace_model.into();
+    if let Some(title) = &data.title {
+        if let Some(err) = validate_len(title, 1, 50, "Title") {
+            return Err(err);
+        }
+        workspace_to_update.title = ActiveValue::Set(title.clone());
+    }
+    if let Some(desc) = &data.description {
+        if let Some(err) = validate_len(desc, 0, 255, "Description") {
+            return Err(err);
+        }
+        // Empty description removes it
+        workspace_to_update.description =
+            ActiveValue::Set((!desc.is_empty()).then(|| desc.clone()));
+    }
+
+    // Changing the prefix changes keys of all tasks in the workspace, numbers are kept
+    let key_prefix = match &data.key_prefix {
+        Some(key_prefix) => Some(validate_key_prefix(key_prefix)?),
+        None => None,
+    };
+    if let Some(key_prefix) = &key_prefix {
+        let is_taken = is_key_prefix_taken(db, user_id, key_prefix, Some(workspace_id))
+            .await
+            .map_err(ErrorResponse::server_error)?;
+        if is_taken {
+            return Err(key_prefix_conflict_response(key_prefix));
+        }
+        workspace_to_update.key_prefix = ActiveValue::Set(key_prefix.clone());
+    }
+
+    let update_res = Workspace::update(workspace_to_update)
+        .filter(workspace::Column::DeletedAt.is_null())
+        .filter(expected_version.condition(workspace::Column::Version, version))
+        .exec(db)
+        .await;
+    let updated_workspace = match update_res {
+        Ok(updated_workspace) => updated_workspace,
+        Err(DbErr::RecordNotFound(_)) => return Ok(None),
+        Err(err) if is_key_prefix_conflict(&err) => {
+            return Err(key_prefix_conflict_response(
+                key_prefix.as_deref().unwrap_or_default(),
+            ))
+        }
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+
+    // Event carries the whole workspace, description may not have been modified
+    event_bus
+        .publish(
+            db,
+            workspace_id,
+            WorkspaceEventKind::WorkspaceUpdated {
+                title: updated_workspace.title.clone(),
+                description: updated_workspace.description.clone(),
+                key_prefix: updated_workspace.key_prefix.clone(),
+            },
+        )
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(Some(updated_workspace))
+}
+
+/// Moves the workspace to trash and ends its event streams.
+/// Used by `DELETE /workspace/<id>` and batches.
+///
+/// Returns `None` when the workspace was changed or trashed since `workspace_model`
+/// was selected, the transaction has to be rolled back then.
+pub async fn delete_workspace(
+    tx: &DatabaseTransaction,
+    event_bus: &EventBus,
+    workspace_model: &workspace::Model,
+    expected_version: &ExpectedVersion,
+) -> Result<Option<DeletedCounts>, ErrorResponse> {
+    let trash_res = trash_workspace_in(
+        tx,
+        workspace_model.id,
+        workspace_model.user_id,
+        expected_version.required_version(workspace_model.version),
+    )
+    .await;
+    let deleted = match trash_res {
+        Ok(deleted) => deleted,
+        Err(DbErr::RecordNotFound(_)) => return Ok(None),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    event_bus
+        .publish(tx, workspace_model.id, WorkspaceEventKind::WorkspaceDeleted)
+        .await
+        .map_err(ErrorResponse::server_error)?;
+    Ok(Some(deleted))
+}
+
+/// Version of `ExportDocument`, incremented on changes which older importers can not read
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Workspace with its labels, task groups and tasks, items in trash are not exported.
+///
+/// Ids are the ids at the time of the export, they are only used to refer
+/// to labels from tasks and are not kept on import.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportDocument {
+    pub version: u32,
+    #[schema(value_type = String, format = DateTime)]
+    pub exported_at: DateTimeWithTimeZone,
+    pub workspace: ExportWorkspace,
+    pub labels: Vec<ExportLabel>,
+    pub task_groups: Vec<ExportTaskGroup>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportWorkspace {
+    pub title: String,
+    pub description: Option<String>,
+    pub key_prefix: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportLabel {
+    pub id: i32,
+    pub color: String,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportTaskGroup {
+    pub id: i32,
+    pub title: String,
+    /// Task group which the git integration moves fixed tasks to
+    #[serde(default)]
+    pub done: bool,
+    pub tasks: Vec<ExportTask>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ExportTask {
+    /// Not kept on import, imported tasks are numbered from 1 in the new workspace
+    #[serde(default)]
+    pub key: String,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub labels_ids: Vec<i32>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+}
+
+/// Builds the same tree as `GET /workspace/<id>`, returns `None` if the user
+/// has no such workspace outside of trash
+pub async fn build_export_document<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    workspace_id: i32,
+) -> Result<Option<ExportDocument>, DbErr> {
+    let found_workspace = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(db)
+        .await?;
+    let found_workspace = match found_workspace {
+        Some(workspace_model) => workspace_model,
+        None => return Ok(None),
+    };
+
+    let labels = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .order_by_asc(label::Column::Id)
+        .all(db)
+        .await?;
+    let task_groups = found_workspace
+        .find_related(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .order_by_asc(task_group::Column::Id)
+        .all(db)
+        .await?;
+    let done_task_group_id = found_workspace
+        .find_related(GitIntegration)
+        .one(db)
+        .await?
+        .and_then(|integration| integration.done_task_group_id);
+
+    let mut tasks_models: Vec<task::Model> = vec![];
+    if !task_groups.is_empty() {
+        tasks_models = Task::find()
+            .filter(task::Column::UserId.eq(user_id))
+            .filter(task::Column::TaskGroupId.is_in(task_groups.iter().map(|tg| tg.id)))
+            .order_by_asc(task::Column::Id)
+            .all(db)
+            .await?;
+    }
+
+    let mut tasks_by_task_group: HashMap<i32, Vec<ExportTask>> = HashMap::new();
+    for task_model in tasks_models.into_iter() {
+        tasks_by_task_group
+            .entry(task_model.task_group_id)
+            .or_default()
+            .push(ExportTask {
+                key: task_key(&found_workspace.key_prefix, task_model.number),
+                title: task_model.title,
+                description: task_model.description,
+                due_at: task_model.due_at,
+                // Ids of trashed labels are kept on tasks until the labels are purged
+                labels_ids: task_model
+                    .labels_ids
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|label_id| labels.iter().any(|l| &l.id == label_id))
+                    .collect(),
+            });
+    }
+
+    Ok(Some(ExportDocument {
+        version: EXPORT_VERSION,
+        exported_at: current_timestamp(),
+        workspace: ExportWorkspace {
+            title: found_workspace.title,
+            description: found_workspace.description,
+            key_prefix: found_workspace.key_prefix,
+        },
+        labels: labels
+            .into_iter()
+            .map(|label_model| ExportLabel {
+                id: label_model.id,
+                color: label_model.color,
+                description: label_model.description,
+            })
+            .collect(),
+        task_groups: task_groups
+            .into_iter()
+            .map(|task_group_model| ExportTaskGroup {
+                id: task_group_model.id,
+                done: done_task_group_id == Some(task_group_model.id),
+                tasks: tasks_by_task_group
+                    .remove(&task_group_model.id)
+                    .unwrap_or_default(),
+                title: task_group_model.title,
+            })
+            .collect(),
+    }))
+}
+
+/// Item of the imported document which was not imported
+#[derive(Serialize, ToSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SkippedItem {
+    /// Either `label`, `task_group` or `task`
+    pub item: &'static str,
+    pub title: String,
+    pub reason: String,
+}
+
+impl SkippedItem {
+    fn new(item: &'static str, title: &str, reason: &str) -> SkippedItem {
+        SkippedItem {
+            item,
+            title: title.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Makes the document satisfy the same constraints as inserting items one by one.
+///
+/// Too long texts are truncated, labels with invalid colors and items without title
+/// are skipped, tasks lose the labels which were skipped. Tasks without description get
+/// their title as description. Invalid key prefix is left empty, so that another one is picked.
+pub fn prepare_import(document: ExportDocument, skipped: &mut Vec<SkippedItem>) -> ExportDocument {
+    let mut labels: Vec<ExportLabel> = vec![];
+    for label in document.labels.into_iter() {
+        let label_title = label.description.clone().unwrap_or_default();
+        let color = label.color.to_lowercase();
+        if validate_label_color(&color).is_some() {
+            skipped.push(SkippedItem::new(
+                "label",
+                &label_title,
+                &format!("Color {} is not a valid hex color", label.color),
+            ));
+            continue;
+        }
+        labels.push(ExportLabel {
+            id: label.id,
+            color,
+            description: non_empty(truncate(&label_title, 30)),
+        });
+    }
+
+    let mut task_groups: Vec<ExportTaskGroup> = vec![];
+    for task_group in document.task_groups.into_iter() {
+        if task_group.title.trim().is_empty() {
+            skipped.push(SkippedItem::new(
+                "task_group",
+                &task_group.title,
+                &format!(
+                    "Title can not be empty, {} tasks of the task group were skipped with it",
+                    task_group.tasks.len()
+                ),
+            ));
+            continue;
+        }
+
+        let mut tasks: Vec<ExportTask> = vec![];
+        for task in task_group.tasks.into_iter() {
+            if task.title.trim().is_empty() {
+                skipped.push(SkippedItem::new(
+                    "task",
+                    &task.title,
+                    "Title can not be empty",
+                ));
+                continue;
+            }
+            let title = truncate(task.title.trim(), 50);
+            let description = match task.description.trim().is_empty() {
+                true => title.clone(),
+                false => truncate(&task.description, 255),
+            };
+            tasks.push(ExportTask {
+                key: task.key,
+                title,
+                description,
+                due_at: task.due_at,
+                labels_ids: task
+                    .labels_ids
+                    .into_iter()
+                    .filter(|label_id| labels.iter().any(|l| &l.id == label_id))
+                    .collect(),
+            });
+        }
+        task_groups.push(ExportTaskGroup {
+            id: task_group.id,
+            title: truncate(task_group.title.trim(), 50),
+            done: task_group.done,
+            tasks,
+        });
+    }
+
+    let title = match document.workspace.title.trim() {
+        "" => "Imported workspace".into(),
+        title => truncate(title, 50),
+    };
+    ExportDocument {
+        version: document.version,
+        exported_at: document.exported_at,
+        workspace: ExportWorkspace {
+            title,
+            description: document
+                .workspace
+                .description
+                .and_then(|desc| non_empty(truncate(&desc, 255))),
+            key_prefix: validate_key_prefix(&document.workspace.key_prefix).unwrap_or_default(),
+        },
+        labels,
+        task_groups,
+    }
+}
+
+/// Truncates the text to at most `max_len` bytes, as `validate_len` counts bytes
+fn truncate(text: &str, max_len: usize) -> String {
+    let mut end = text.len().min(max_len);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].into()
+}
+
+fn non_empty(text: String) -> Option<String> {
+    match text.trim().is_empty() {
+        true => None,
+        false => Some(text),
+    }
+}
+
+/// Board exported from Trello (Menu -> Print, export and share -> Export as JSON),
+/// only the fields which are imported
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "camelCase")]
+pub struct TrelloBoard {
+    pub name: String,
+    #[serde(default)]
+    pub desc: String,
+    #[serde(default)]
+    pub labels: Vec<TrelloLabel>,
+    pub lists: Vec<TrelloList>,
+    pub cards: Vec<TrelloCard>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "camelCase")]
+pub struct TrelloLabel {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "camelCase")]
+pub struct TrelloList {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "camelCase")]
+pub struct TrelloCard {
+    pub name: String,
+    #[serde(default)]
+    pub desc: String,
+    pub id_list: String,
+    #[serde(default)]
+    pub id_labels: Vec<String>,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub pos: f64,
+    pub due: Option<DateTimeWithTimeZone>,
+}
+
+/// Hex value of a Trello label color, shades (`green_dark`, `red_light`) use the base color
+pub fn trello_label_color(color: &str) -> Option<&'static str> {
+    let base_color = color.trim_end_matches("_dark").trim_end_matches("_light");
+    let hex = match base_color {
+        "green" => "#61bd4f",
+        "yellow" => "#f2d600",
+        "orange" => "#ff9f1a",
+        "red" => "#eb5a46",
+        "purple" => "#c377e0",
+        "blue" => "#0079bf",
+        "sky" => "#00c2e0",
+        "lime" => "#51e898",
+        "pink" => "#ff78cb",
+        "black" => "#344563",
+        _ => return None,
+    };
+    Some(hex)
+}
+
+impl TrelloBoard {
+    /// Maps lists to task groups, cards to tasks and labels to labels,
+    /// archived lists and cards are skipped
+    pub fn into_export_document(self, skipped: &mut Vec<SkippedItem>) -> ExportDocument {
+        let mut labels: Vec<ExportLabel> = vec![];
+        let mut labels_ids: HashMap<String, i32> = HashMap::new();
+        for trello_label in self.labels.into_iter() {
+            let color = match trello_label.color.as_deref() {
+                Some(color) => trello_label_color(color)
+                    .ok_or_else(|| format!("Color {} does not have a matching hex color", color)),
+                None => Err("Label does not have a color".into()),
+            };
+            match color {
+                Ok(color) => {
+                    let label_id = labels.len() as i32 + 1;
+                    labels_ids.insert(trello_label.id, label_id);
+                    labels.push(ExportLabel {
+                        id: label_id,
+                        color: color.into(),
+                        description: non_empty(trello_label.name),
+                    });
+                }
+                Err(reason) => skipped.push(SkippedItem::new("label", &trello_label.name, &reason)),
+            }
+        }
+
+        let mut lists = self.lists;
+        lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        let mut cards = self.cards;
+        cards.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+        let mut task_groups: Vec<ExportTaskGroup> = vec![];
+        let mut task_groups_ids: HashMap<String, usize> = HashMap::new();
+        for list in lists.into_iter() {
+            if list.closed {
+                skipped.push(SkippedItem::new(
+                    "task_group",
+                    &list.name,
+                    "List is archived",
+                ));
+                continue;
+            }
+            task_groups_ids.insert(list.id, task_groups.len());
+            task_groups.push(ExportTaskGroup {
+                id: task_groups.len() as i32 + 1,
+                title: list.name,
+                done: false,
+                tasks: vec![],
+            });
+        }
+
+        for card in cards.into_iter() {
+            if card.closed {
+                skipped.push(SkippedItem::new("task", &card.name, "Card is archived"));
+                continue;
+            }
+            let task_group_index = match task_groups_ids.get(&card.id_list) {
+                Some(index) => *index,
+                None => {
+                    skipped.push(SkippedItem::new(
+                        "task",
+                        &card.name,
+                        "List of the card is archived",
+                    ));
+                    continue;
+                }
+            };
+            task_groups[task_group_index].tasks.push(ExportTask {
+                key: String::new(),
+                labels_ids: card
+                    .id_labels
+                    .iter()
+                    .filter_map(|label_id| labels_ids.get(label_id).copied())
+                    .collect(),
+                title: card.name,
+                description: card.desc,
+                due_at: card.due,
+            });
+        }
+
+        ExportDocument {
+            version: EXPORT_VERSION,
+            exported_at: current_timestamp(),
+            workspace: ExportWorkspace {
+                key_prefix: default_key_prefix(&self.name),
+                title: self.name,
+                description: non_empty(self.desc),
+            },
+            labels,
+            task_groups,
+        }
+    }
+}
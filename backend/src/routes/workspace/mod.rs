@@ -1,5 +1,9 @@
 pub mod delete;
+pub mod events;
+pub mod export;
+pub mod import;
 pub mod insert;
+pub mod lib;
 pub mod select_all;
 pub mod select_one;
 pub mod update;
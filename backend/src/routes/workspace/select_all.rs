@@ -2,23 +2,41 @@ use rocket::{
     http::Status,
     serde::{json::Json, Serialize},
 };
-use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter};
+use sea_orm::{ColumnTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use utoipa::ToSchema;
 
 use backend::{
     entities::{prelude::Workspace, workspace},
     establish_db_connection,
 };
 
-use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
+use crate::routes::lib::{AuthenticatedUser, ErrorResponse, Pagination, SuccessResponse};
 
-#[get("/workspace")]
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    responses(
+        (status = 200, description = "One page of workspaces", body = ListedWorkspacesResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace?<limit>&<cursor>")]
 pub async fn handler(
+    limit: Option<u64>,
+    cursor: Option<String>,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<Vec<FoundWorkspace>>>, ErrorResponse> {
-    let select_res: Result<Vec<workspace::Model>, DbErr> = async move {
+) -> Result<Json<SuccessResponse<Vec<ListedWorkspace>>>, ErrorResponse> {
+    let pagination = Pagination::new(limit, cursor)?;
+
+    let select_res: Result<Vec<workspace::Model>, DbErr> = async {
         let db = establish_db_connection().await?;
         let select_workspaces = Workspace::find()
             .filter(workspace::Column::UserId.eq(user.user_id))
+            .filter(workspace::Column::DeletedAt.is_null())
+            .filter(workspace::Column::Id.gt(pagination.after_id))
+            .order_by_asc(workspace::Column::Id)
+            .limit(pagination.select_limit())
             .all(&db)
             .await?;
         Ok(select_workspaces)
@@ -27,24 +45,30 @@ pub async fn handler(
 
     match select_res {
         Ok(workspaces_models) => {
-            let mut workspaces: Vec<FoundWorkspace> = vec![];
+            let (workspaces_models, next_cursor) =
+                pagination.page(workspaces_models, |workspace_model| workspace_model.id);
+            let mut workspaces: Vec<ListedWorkspace> = vec![];
             for workspace_model in workspaces_models.iter() {
-                workspaces.push(FoundWorkspace {
+                workspaces.push(ListedWorkspace {
                     id: workspace_model.id,
                     title: workspace_model.title.clone(),
                     description: workspace_model.description.clone(),
+                    key_prefix: workspace_model.key_prefix.clone(),
+                    version: workspace_model.version,
                 })
             }
-            Ok(Json(SuccessResponse::new(workspaces)))
+            Ok(Json(SuccessResponse::paginated(workspaces, next_cursor)))
         }
-        Err(_) => Err(ErrorResponse::new(None, Status::InternalServerError)),
+        Err(err) => Err(ErrorResponse::new(None, Status::InternalServerError).with_source(err)),
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
-pub struct FoundWorkspace {
+pub struct ListedWorkspace {
     id: i32,
     title: String,
     description: Option<String>,
+    key_prefix: String,
+    version: i32,
 }
@@ -2,28 +2,45 @@ use rocket::{
     http::Status,
     serde::{json::Json, Serialize},
 };
-use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter, Condition};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, EntityTrait, FromQueryResult, ModelTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+use std::collections::HashMap;
+use utoipa::ToSchema;
 
 use backend::{
     entities::{
+        label,
         prelude::{Label, Task, TaskGroup, Workspace},
-        task, workspace,
+        task, task_group, workspace,
     },
     establish_db_connection,
+    services::task_keys::task_key,
 };
 
 use crate::routes::lib::{AuthenticatedUser, ErrorResponse, SuccessResponse};
 
-#[get("/workspace/<workspace_id>")]
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    responses(
+        (status = 200, description = "Workspace with its labels and task groups", body = FoundWorkspaceResponse),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
+#[get("/workspace/<workspace_id>?<summary>")]
 pub async fn handler(
     workspace_id: i32,
+    summary: Option<bool>,
     user: AuthenticatedUser,
 ) -> Result<Json<SuccessResponse<FoundWorkspace>>, ErrorResponse> {
     let server_err_response = ErrorResponse::new(None, Status::InternalServerError);
     let db_res = establish_db_connection().await;
 
-    if let Err(_) = db_res {
-        return Err(server_err_response);
+    if let Err(err) = &db_res {
+        return Err(server_err_response.with_source(err));
     }
 
     let db = db_res.unwrap();
@@ -31,12 +48,13 @@ pub async fn handler(
     // Find workspace
     let select_workspace_res = Workspace::find_by_id(workspace_id)
         .filter(workspace::Column::UserId.eq(user.user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
         .find_with_related(TaskGroup)
         .all(&db)
         .await;
 
-    if let Err(_) = select_workspace_res {
-        return Err(server_err_response);
+    if let Err(err) = &select_workspace_res {
+        return Err(server_err_response.with_source(err));
     }
 
     let found_models = select_workspace_res.unwrap();
@@ -47,12 +65,20 @@ pub async fn handler(
         ));
     }
 
-    let (found_workspace, found_related_task_groups) = &found_models[0];
+    let (found_workspace, found_task_groups) = &found_models[0];
+    let found_related_task_groups: Vec<&task_group::Model> = found_task_groups
+        .iter()
+        .filter(|task_group_model| task_group_model.deleted_at.is_none())
+        .collect();
 
     // Find related labels
-    let related_labels = found_workspace.find_related(Label).all(&db).await;
-    if let Err(_) = related_labels {
-        return Err(server_err_response);
+    let related_labels = found_workspace
+        .find_related(Label)
+        .filter(label::Column::DeletedAt.is_null())
+        .all(&db)
+        .await;
+    if let Err(err) = &related_labels {
+        return Err(server_err_response.with_source(err));
     }
     let labels: Vec<FoundLabel> = related_labels
         .unwrap()
@@ -61,23 +87,72 @@ pub async fn handler(
             id: label_model.id,
             color: label_model.color.clone(),
             description: label_model.description.clone(),
+            version: label_model.version,
         })
         .collect();
 
+    // Summary contains only number of tasks in each task group
+    if summary.unwrap_or(false) {
+        let mut tasks_counts: HashMap<i32, i64> = HashMap::new();
+        if !found_related_task_groups.is_empty() {
+            let count_tasks_res = Task::find()
+                .select_only()
+                .column(task::Column::TaskGroupId)
+                .column_as(task::Column::Id.count(), "task_count")
+                .filter(task::Column::UserId.eq(user.user_id))
+                .filter(
+                    task::Column::TaskGroupId
+                        .is_in(found_related_task_groups.iter().map(|tg| tg.id)),
+                )
+                .group_by(task::Column::TaskGroupId)
+                .into_model::<TaskGroupTaskCount>()
+                .all(&db)
+                .await;
+            match count_tasks_res {
+                Err(err) => return Err(server_err_response.with_source(err)),
+                Ok(counts) => {
+                    for count in counts {
+                        tasks_counts.insert(count.task_group_id, count.task_count);
+                    }
+                }
+            }
+        }
+
+        let task_groups = found_related_task_groups
+            .iter()
+            .map(|task_group_model| FoundTaskGroup {
+                id: task_group_model.id,
+                title: task_group_model.title.clone(),
+                task_count: *tasks_counts.get(&task_group_model.id).unwrap_or(&0),
+                tasks: None,
+                version: task_group_model.version,
+            })
+            .collect();
+
+        return Ok(Json(SuccessResponse::new(FoundWorkspace {
+            title: found_workspace.title.clone(),
+            description: found_workspace.description.clone(),
+            key_prefix: found_workspace.key_prefix.clone(),
+            version: found_workspace.version,
+            labels,
+            task_groups,
+        })));
+    }
+
     // Find tasks related tasks to related task groups
     let mut related_tasks: Vec<task::Model> = vec![];
-    if found_related_task_groups.len() > 0 {
-        let find_related_tasks_stmt =
-            Task::find().filter(task::Column::UserId.eq(user.user_id));
-        let mut find_related_tasks_cond = Condition::any();
-        for task_group_model in found_related_task_groups.clone().iter() {
-            find_related_tasks_cond =
-                find_related_tasks_cond.add(task::Column::TaskGroupId.eq(task_group_model.id));
-        }
-        let found_related_tasks_models = find_related_tasks_stmt.filter(find_related_tasks_cond).all(&db).await;
+    if !found_related_task_groups.is_empty() {
+        let found_related_tasks_models = Task::find()
+            .filter(task::Column::UserId.eq(user.user_id))
+            .filter(
+                task::Column::TaskGroupId.is_in(found_related_task_groups.iter().map(|tg| tg.id)),
+            )
+            .order_by_asc(task::Column::Id)
+            .all(&db)
+            .await;
         match found_related_tasks_models {
-            Err(_) => {
-                return Err(server_err_response);
+            Err(err) => {
+                return Err(server_err_response.with_source(err));
             }
             Ok(tasks_models) => {
                 related_tasks = tasks_models;
@@ -86,65 +161,100 @@ pub async fn handler(
     }
 
     // Merge task groups with related tasks
+    let mut tasks_by_task_group: HashMap<i32, Vec<GroupTask>> = HashMap::new();
+    for task_model in related_tasks.into_iter() {
+        tasks_by_task_group
+            .entry(task_model.task_group_id)
+            .or_default()
+            .push(GroupTask {
+                id: task_model.id,
+                key: task_key(&found_workspace.key_prefix, task_model.number),
+                title: task_model.title,
+                description: task_model.description,
+                due_at: task_model.due_at,
+                version: task_model.version,
+                // Ids of trashed labels are kept on tasks until the labels are purged
+                labels_ids: task_model.labels_ids.map(|labels_ids| {
+                    labels_ids
+                        .into_iter()
+                        .filter(|label_id| labels.iter().any(|l| &l.id == label_id))
+                        .collect()
+                }),
+            });
+    }
+
     let mut task_groups: Vec<FoundTaskGroup> = vec![];
     for task_group_model in found_related_task_groups.iter() {
-        let mut current_related_tasks: Vec<FoundTask> = vec![];
-        for task_model in related_tasks.iter() {
-            if task_model.task_group_id == task_group_model.id {
-                current_related_tasks.push(FoundTask {
-                    id: task_model.id,
-                    title: task_model.title.clone(),
-                    description: task_model.description.clone(),
-                    labels_ids: task_model.labels_ids.clone(),
-                })
-            }
-        }
+        let current_related_tasks = tasks_by_task_group
+            .remove(&task_group_model.id)
+            .unwrap_or_default();
 
         task_groups.push(FoundTaskGroup {
             id: task_group_model.id,
             title: task_group_model.title.clone(),
-            tasks: current_related_tasks,
+            task_count: current_related_tasks.len() as i64,
+            tasks: Some(current_related_tasks),
+            version: task_group_model.version,
         })
     }
 
     Ok(Json(SuccessResponse::new(FoundWorkspace {
         title: found_workspace.title.clone(),
         description: found_workspace.description.clone(),
+        key_prefix: found_workspace.key_prefix.clone(),
+        version: found_workspace.version,
         labels,
         task_groups,
     })))
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct FoundWorkspace {
     pub title: String,
     pub description: Option<String>,
+    pub key_prefix: String,
+    pub version: i32,
     pub labels: Vec<FoundLabel>,
     pub task_groups: Vec<FoundTaskGroup>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct FoundLabel {
     pub id: i32,
     pub color: String,
     pub description: Option<String>,
+    pub version: i32,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct FoundTaskGroup {
     pub id: i32,
     pub title: String,
-    pub tasks: Vec<FoundTask>,
+    pub task_count: i64,
+    /// Not present in summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<Vec<GroupTask>>,
+    pub version: i32,
+}
+
+#[derive(FromQueryResult)]
+struct TaskGroupTaskCount {
+    task_group_id: i32,
+    task_count: i64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
-pub struct FoundTask {
+pub struct GroupTask {
     pub id: i32,
+    pub key: String,
     pub title: String,
     pub description: String,
     pub labels_ids: Option<Vec<i32>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub due_at: Option<DateTimeWithTimeZone>,
+    pub version: i32,
 }
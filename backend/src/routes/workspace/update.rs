@@ -1,95 +1,136 @@
+use async_graphql::InputObject;
 use rocket::{
     http::Status,
     serde::{json::Json, Deserialize, Serialize},
+    State,
 };
-use sea_orm::{sea_query::Expr, ColumnTrait, DbErr, EntityTrait, QueryFilter, UpdateResult, Value};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, TransactionTrait};
+use utoipa::ToSchema;
 
 use backend::{
     entities::{prelude::Workspace, workspace},
     establish_db_connection,
 };
 
-use crate::routes::lib::{validate_len, AuthenticatedUser, ErrorResponse, SuccessResponse};
+use crate::{
+    events::bus::EventBus,
+    routes::{
+        lib::{AuthenticatedUser, ErrorResponse, ExpectedVersion, IfMatch, VersionedResponse},
+        workspace::lib::update_workspace,
+    },
+};
 
+/// Updates workspace, requests with `If-Match` header or `version` are rejected
+/// when the workspace was changed since that version
+#[utoipa::path(
+    context_path = "/api/v1",
+    tag = "workspace",
+    request_body = ModifiedWorkspaceData,
+    params(("If-Match" = Option<String>, Header, description = "ETag of the changed version")),
+    responses(
+        (status = 200, description = "Updated workspace", body = SavedModifiedWorkspaceResponse,
+            headers(("ETag" = String, description = "Version of the workspace"))),
+        (status = 409, description = "Workspace was changed since `version`, body has the current one", body = ErrorResponseBody),
+        (status = 412, description = "Workspace was changed since `If-Match`, body has the current one", body = ErrorResponseBody),
+        (status = "4XX", description = "Invalid request", body = ErrorResponseBody),
+        (status = 500, description = "Internal server error", body = ErrorResponseBody)
+    )
+)]
 #[patch("/workspace/<workspace_id>", data = "<data>")]
 pub async fn handler(
     workspace_id: i32,
     data: Json<ModifiedWorkspaceData>,
+    if_match: IfMatch,
     user: AuthenticatedUser,
-) -> Result<Json<SuccessResponse<SavedModifiedWorkspace>>, ErrorResponse> {
-    if None == data.title && None == data.description {
-        return Err(ErrorResponse::new(
-            Some("Either title or description has to be provided".into()),
-            Status::BadRequest,
+    event_bus: &State<EventBus>,
+) -> Result<VersionedResponse<SavedModifiedWorkspace>, ErrorResponse> {
+    let expected_version = ExpectedVersion::new(if_match, data.version);
+    let not_found_response = ErrorResponse::new(
+        format!("Workspace with id {} does not exist", workspace_id).into(),
+        Status::NotFound,
+    );
+    let db = match establish_db_connection().await {
+        Ok(db) => db,
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    let current_workspace = match find_workspace(&db, workspace_id, user.user_id).await {
+        Ok(Some(workspace_model)) => workspace_model,
+        Ok(None) => return Err(not_found_response),
+        Err(err) => return Err(ErrorResponse::server_error(err)),
+    };
+    if !expected_version.matches(current_workspace.version) {
+        return Err(expected_version.conflict_response(
+            &SavedModifiedWorkspace::new(current_workspace.clone()),
+            current_workspace.version,
         ));
     }
 
-    let mut updated_workspace_stmt = Workspace::update_many()
-        .filter(workspace::Column::Id.eq(workspace_id))
-        .filter(workspace::Column::UserId.eq(user.user_id));
-
-    let title = data.title.as_ref().unwrap();
-    let title_err = validate_len(title, 1, 50, "Title");
-    if let Some(err) = title_err {
-        return Err(err);
-    }
-    updated_workspace_stmt =
-        updated_workspace_stmt.col_expr(workspace::Column::Title, Expr::value(title.clone()));
-
-    if let Some(desc) = &data.description {
-        let desc_err = validate_len(desc, 0, 255, "Description");
-        if let Some(err) = desc_err {
-            return Err(err);
+    let tx = db.begin().await.map_err(ErrorResponse::server_error)?;
+    let updated_workspace =
+        update_workspace(&tx, event_bus, current_workspace, &data, &expected_version).await?;
+    let workspace_model = match updated_workspace {
+        Some(workspace_model) => workspace_model,
+        // Workspace was changed or moved to trash since it was selected
+        None => {
+            return match find_workspace(&db, workspace_id, user.user_id).await {
+                Ok(Some(workspace_model)) => Err(expected_version.conflict_response(
+                    &SavedModifiedWorkspace::new(workspace_model.clone()),
+                    workspace_model.version,
+                )),
+                Ok(None) => Err(not_found_response),
+                Err(err) => Err(ErrorResponse::server_error(err)),
+            };
         }
-        updated_workspace_stmt = match desc.len() > 0 {
-            true => updated_workspace_stmt.col_expr(
-                workspace::Column::Description,
-                Expr::value(Some(desc.clone())),
-            ),
-            false => updated_workspace_stmt.col_expr(
-                workspace::Column::Description,
-                Expr::value(Value::String(None)),
-            ),
-        };
-    }
+    };
+    tx.commit().await.map_err(ErrorResponse::server_error)?;
 
-    let updated_workspace_res: Result<UpdateResult, DbErr> = async move {
-        let db = establish_db_connection().await?;
-        let updated_workspace = updated_workspace_stmt.exec(&db).await?;
-        Ok(updated_workspace)
-    }
-    .await;
-
-    match updated_workspace_res {
-        Ok(update_result) => {
-            if update_result.rows_affected == 0 {
-                return Err(ErrorResponse::new(
-                    format!("Workspace with id {} does not exist", workspace_id).into(),
-                    Status::NotFound,
-                ));
-            }
-
-            Ok(Json(SuccessResponse::new(SavedModifiedWorkspace {
-                id: workspace_id,
-                title: title.clone(),
-                description: data.description.clone(),
-            })))
-        }
-        Err(_) => Err(ErrorResponse::new(None, Status::InternalServerError)),
-    }
+    let version = workspace_model.version;
+    Ok(VersionedResponse::new(
+        SavedModifiedWorkspace::new(workspace_model),
+        version,
+    ))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, ToSchema, InputObject)]
 #[serde(crate = "rocket::serde")]
 pub struct ModifiedWorkspaceData {
     pub title: Option<String>,
     pub description: Option<String>,
+    pub key_prefix: Option<String>,
+    /// Version of the workspace which is changed, `If-Match` header takes precedence
+    pub version: Option<i32>,
+}
+
+pub async fn find_workspace(
+    db: &DatabaseConnection,
+    workspace_id: i32,
+    user_id: i32,
+) -> Result<Option<workspace::Model>, DbErr> {
+    Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(db)
+        .await
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct SavedModifiedWorkspace {
     pub id: i32,
     pub title: String,
     pub description: Option<String>,
+    pub key_prefix: String,
+    pub version: i32,
+}
+
+impl SavedModifiedWorkspace {
+    pub fn new(workspace_model: workspace::Model) -> SavedModifiedWorkspace {
+        SavedModifiedWorkspace {
+            id: workspace_model.id,
+            title: workspace_model.title,
+            description: workspace_model.description,
+            key_prefix: workspace_model.key_prefix,
+            version: workspace_model.version,
+        }
+    }
 }
@@ -0,0 +1,58 @@
+//! Change sequence of workspaces, task groups, tasks and labels for incremental sync.
+//!
+//! Triggers give every inserted or updated row the next value of `change_seq`
+//! and record deleted rows as tombstones, so every route and job maintains it.
+//! Values are taken before the writing transaction commits, writers hold a shared
+//! advisory lock of the user until then and `last_change_seq` waits for them.
+
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement, TransactionError,
+    TransactionTrait,
+};
+
+/// Namespace of the advisory locks of users' changes, the other key is the user id
+pub const CHANGES_LOCK_NAMESPACE: i32 = 1_937_337_955;
+
+/// Entity names of tombstones, names of the tables
+pub const ENTITY_WORKSPACE: &str = "workspace";
+pub const ENTITY_TASK_GROUP: &str = "task_group";
+pub const ENTITY_TASK: &str = "task";
+pub const ENTITY_LABEL: &str = "label";
+
+/// Value of the sequence which all committed changes of the user are at or below,
+/// changes which are not committed yet get greater values
+pub async fn last_change_seq(db: &DatabaseConnection, user_id: i32) -> Result<i64, DbErr> {
+    let tx_res = db
+        .transaction::<_, i64, DbErr>(|tx| {
+            Box::pin(async move {
+                // Waits for the user's writers which already took a value, released on commit
+                tx.execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    r#"SELECT pg_advisory_xact_lock($1, $2)"#,
+                    vec![CHANGES_LOCK_NAMESPACE.into(), user_id.into()],
+                ))
+                .await?;
+
+                let last_value = tx
+                    .query_one(Statement::from_string(
+                        DbBackend::Postgres,
+                        r#"SELECT CASE WHEN "is_called" THEN "last_value" ELSE "last_value" - 1 END AS "last_change_seq" FROM "change_seq""#.to_owned(),
+                    ))
+                    .await?;
+                match last_value {
+                    Some(row) => row.try_get("", "last_change_seq"),
+                    None => Err(DbErr::RecordNotFound(
+                        "Sequence change_seq does not exist".into(),
+                    )),
+                }
+            })
+        })
+        .await;
+
+    match tx_res {
+        Ok(last_change_seq) => Ok(last_change_seq),
+        Err(TransactionError::Connection(err)) | Err(TransactionError::Transaction(err)) => {
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,573 @@
+//! Deletion of workspaces and task groups together with everything that depends on them.
+//!
+//! Deleting moves the subtree to trash, purging removes trashed subtrees permanently.
+//! Dependent rows are always handled before the rows they depend on
+//! (tasks -> task groups -> labels -> workspace) and all of it happens in a single transaction.
+
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, sea_query::Expr, ColumnTrait, ConnectionTrait,
+    DatabaseConnection, DatabaseTransaction, DbBackend, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QuerySelect, QueryTrait, Select, Statement, TransactionError, TransactionTrait,
+};
+
+use crate::{
+    current_timestamp,
+    entities::{
+        label,
+        prelude::{Label, Task, TaskGroup, Workspace},
+        task, task_group, workspace,
+    },
+};
+
+/// Number of rows removed by a deletion
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeletedCounts {
+    pub workspaces: u64,
+    pub task_groups: u64,
+    pub tasks: u64,
+    pub labels: u64,
+}
+
+/// Moves workspace with its task groups and labels to trash.
+///
+/// Task groups and labels share the deletion time of the workspace so that they can be
+/// restored with it, tasks stay attached to their task groups.
+/// Returns `DbErr::RecordNotFound` if the user has no such workspace.
+pub async fn trash_workspace(
+    db: &DatabaseConnection,
+    workspace_id: i32,
+    user_id: i32,
+) -> Result<DeletedCounts, DbErr> {
+    let tx_res = db
+        .transaction::<_, DeletedCounts, DbErr>(|tx| {
+            Box::pin(async move { trash_workspace_in(tx, workspace_id, user_id, None).await })
+        })
+        .await;
+
+    flatten_tx_err(tx_res)
+}
+
+/// Same as `trash_workspace` within a transaction of the caller.
+///
+/// With `version` the workspace is only trashed if it still has that version, otherwise
+/// `DbErr::RecordNotFound` is returned after its task groups and labels were trashed,
+/// so the caller has to roll back.
+pub async fn trash_workspace_in(
+    tx: &DatabaseTransaction,
+    workspace_id: i32,
+    user_id: i32,
+    version: Option<i32>,
+) -> Result<DeletedCounts, DbErr> {
+    let deleted_at = current_timestamp();
+
+    let existing_workspace = Workspace::find_by_id(workspace_id)
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::DeletedAt.is_null())
+        .one(tx)
+        .await?;
+
+    if existing_workspace.is_none() {
+        return Err(DbErr::RecordNotFound(format!(
+            "Workspace with id {} does not exist",
+            workspace_id
+        )));
+    }
+
+    let live_task_groups = TaskGroup::find()
+        .filter(task_group::Column::WorkspaceId.eq(workspace_id))
+        .filter(task_group::Column::DeletedAt.is_null());
+
+    let tasks = Task::find()
+        .filter(task::Column::TaskGroupId.in_subquery(task_groups_ids(live_task_groups.clone())))
+        .count(tx)
+        .await?;
+
+    let task_groups = TaskGroup::update_many()
+        .col_expr(task_group::Column::DeletedAt, Expr::value(deleted_at))
+        .filter(task_group::Column::WorkspaceId.eq(workspace_id))
+        .filter(task_group::Column::DeletedAt.is_null())
+        .exec(tx)
+        .await?
+        .rows_affected;
+
+    let labels = Label::update_many()
+        .col_expr(label::Column::DeletedAt, Expr::value(deleted_at))
+        .filter(label::Column::WorkspaceId.eq(workspace_id))
+        .filter(label::Column::DeletedAt.is_null())
+        .exec(tx)
+        .await?
+        .rows_affected;
+
+    let mut trash_workspace_stmt = Workspace::update_many()
+        .col_expr(workspace::Column::DeletedAt, Expr::value(deleted_at))
+        .filter(workspace::Column::Id.eq(workspace_id))
+        .filter(workspace::Column::DeletedAt.is_null());
+    if let Some(version) = version {
+        trash_workspace_stmt = trash_workspace_stmt.filter(workspace::Column::Version.eq(version));
+    }
+    // Concurrent change of the workspace since it was selected
+    if trash_workspace_stmt.exec(tx).await?.rows_affected == 0 {
+        return Err(DbErr::RecordNotFound(format!(
+            "Workspace with id {} was changed by another request",
+            workspace_id
+        )));
+    }
+
+    Ok(DeletedCounts {
+        workspaces: 1,
+        task_groups,
+        tasks,
+        labels,
+    })
+}
+
+/// Moves task group to trash, its tasks stay attached to it and are restored with it.
+///
+/// Returns `DbErr::RecordNotFound` if the user has no such task group.
+pub async fn trash_task_group(
+    db: &DatabaseConnection,
+    task_group_id: i32,
+    user_id: i32,
+) -> Result<DeletedCounts, DbErr> {
+    let tx_res = db
+        .transaction::<_, DeletedCounts, DbErr>(|tx| {
+            Box::pin(async move { trash_task_group_in(tx, task_group_id, user_id, None).await })
+        })
+        .await;
+
+    flatten_tx_err(tx_res)
+}
+
+/// Same as `trash_task_group` within a transaction of the caller.
+///
+/// With `version` the task group is only trashed if it still has that version,
+/// otherwise `DbErr::RecordNotFound` is returned.
+pub async fn trash_task_group_in(
+    tx: &DatabaseTransaction,
+    task_group_id: i32,
+    user_id: i32,
+    version: Option<i32>,
+) -> Result<DeletedCounts, DbErr> {
+    let deleted_at = current_timestamp();
+
+    let existing_task_group = TaskGroup::find_by_id(task_group_id)
+        .filter(task_group::Column::UserId.eq(user_id))
+        .filter(task_group::Column::DeletedAt.is_null())
+        .one(tx)
+        .await?;
+
+    if existing_task_group.is_none() {
+        return Err(DbErr::RecordNotFound(format!(
+            "Task group with id {} does not exist",
+            task_group_id
+        )));
+    }
+
+    let tasks = Task::find()
+        .filter(task::Column::TaskGroupId.eq(task_group_id))
+        .count(tx)
+        .await?;
+
+    let mut trash_task_group_stmt = TaskGroup::update_many()
+        .col_expr(task_group::Column::DeletedAt, Expr::value(deleted_at))
+        .filter(task_group::Column::Id.eq(task_group_id))
+        .filter(task_group::Column::DeletedAt.is_null());
+    if let Some(version) = version {
+        trash_task_group_stmt =
+            trash_task_group_stmt.filter(task_group::Column::Version.eq(version));
+    }
+    // Concurrent change of the task group since it was selected
+    if trash_task_group_stmt.exec(tx).await?.rows_affected == 0 {
+        return Err(DbErr::RecordNotFound(format!(
+            "Task group with id {} was changed by another request",
+            task_group_id
+        )));
+    }
+
+    Ok(DeletedCounts {
+        task_groups: 1,
+        tasks,
+        ..Default::default()
+    })
+}
+
+/// Permanently removes everything which was moved to trash before `purge_before`
+pub async fn purge_trash(
+    db: &DatabaseConnection,
+    purge_before: DateTimeWithTimeZone,
+) -> Result<DeletedCounts, DbErr> {
+    let tx_res = db
+        .transaction::<_, DeletedCounts, DbErr>(|tx| {
+            Box::pin(async move {
+                let mut purged = DeletedCounts::default();
+
+                // Whole subtrees of expired workspaces
+                let expired_workspaces_ids = Workspace::find()
+                    .select_only()
+                    .column(workspace::Column::Id)
+                    .filter(workspace::Column::DeletedAt.lt(purge_before))
+                    .into_query();
+                let workspaces_task_groups = TaskGroup::find().filter(
+                    task_group::Column::WorkspaceId.in_subquery(expired_workspaces_ids.clone()),
+                );
+                let workspaces_labels = Label::find()
+                    .filter(label::Column::WorkspaceId.in_subquery(expired_workspaces_ids.clone()));
+
+                purged.tasks += delete_tasks_of(tx, workspaces_task_groups.clone()).await?;
+                purged.task_groups += TaskGroup::delete_many()
+                    .filter(
+                        task_group::Column::WorkspaceId.in_subquery(expired_workspaces_ids.clone()),
+                    )
+                    .exec(tx)
+                    .await?
+                    .rows_affected;
+                purged.labels += delete_labels(tx, workspaces_labels).await?;
+                purged.workspaces += Workspace::delete_many()
+                    .filter(workspace::Column::DeletedAt.lt(purge_before))
+                    .exec(tx)
+                    .await?
+                    .rows_affected;
+
+                // Task groups trashed on their own
+                let expired_task_groups =
+                    TaskGroup::find().filter(task_group::Column::DeletedAt.lt(purge_before));
+                purged.tasks += delete_tasks_of(tx, expired_task_groups).await?;
+                purged.task_groups += TaskGroup::delete_many()
+                    .filter(task_group::Column::DeletedAt.lt(purge_before))
+                    .exec(tx)
+                    .await?
+                    .rows_affected;
+
+                // Labels trashed on their own
+                let expired_labels =
+                    Label::find().filter(label::Column::DeletedAt.lt(purge_before));
+                purged.labels += delete_labels(tx, expired_labels).await?;
+
+                Ok(purged)
+            })
+        })
+        .await;
+
+    flatten_tx_err(tx_res)
+}
+
+fn task_groups_ids(task_groups: Select<TaskGroup>) -> sea_orm::sea_query::SelectStatement {
+    task_groups
+        .select_only()
+        .column(task_group::Column::Id)
+        .into_query()
+}
+
+async fn delete_tasks_of(
+    tx: &DatabaseTransaction,
+    task_groups: Select<TaskGroup>,
+) -> Result<u64, DbErr> {
+    let delete_res = Task::delete_many()
+        .filter(task::Column::TaskGroupId.in_subquery(task_groups_ids(task_groups)))
+        .exec(tx)
+        .await?;
+    Ok(delete_res.rows_affected)
+}
+
+/// Removes labels and their ids from tasks which reference them
+async fn delete_labels(tx: &DatabaseTransaction, labels: Select<Label>) -> Result<u64, DbErr> {
+    let labels_ids: Vec<i32> = labels
+        .all(tx)
+        .await?
+        .iter()
+        .map(|label_model| label_model.id)
+        .collect();
+    if labels_ids.is_empty() {
+        return Ok(0);
+    }
+
+    // Ids are removed from all referencing tasks at once, other ids keep their order
+    tx.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"UPDATE "task" SET "labels_ids" = ARRAY(
+            SELECT "label_id" FROM unnest("task"."labels_ids") WITH ORDINALITY AS "labels"("label_id", "position")
+            WHERE NOT "label_id" = ANY($1) ORDER BY "position"
+        ) WHERE "task"."labels_ids" && $1"#,
+        vec![labels_ids.clone().into()],
+    ))
+    .await?;
+
+    let delete_res = Label::delete_many()
+        .filter(label::Column::Id.is_in(labels_ids))
+        .exec(tx)
+        .await?;
+    Ok(delete_res.rows_affected)
+}
+
+fn flatten_tx_err<T>(tx_res: Result<T, TransactionError<DbErr>>) -> Result<T, DbErr> {
+    match tx_res {
+        Ok(value) => Ok(value),
+        Err(TransactionError::Connection(err)) | Err(TransactionError::Transaction(err)) => {
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Value};
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn workspace_model(id: i32) -> workspace::Model {
+        workspace::Model {
+            id,
+            user_id: 1,
+            title: "Workspace".into(),
+            description: None,
+            deleted_at: None,
+            key_prefix: "WS".into(),
+            next_task_number: 1,
+            change_seq: 1,
+            version: 1,
+        }
+    }
+
+    fn task_group_model(id: i32) -> task_group::Model {
+        task_group::Model {
+            workspace_id: 1,
+            user_id: 1,
+            title: "Task group".into(),
+            id,
+            deleted_at: None,
+            change_seq: 1,
+            version: 1,
+        }
+    }
+
+    fn label_model(id: i32) -> label::Model {
+        label::Model {
+            workspace_id: 1,
+            user_id: 1,
+            color: "#ffffff".into(),
+            description: None,
+            id,
+            deleted_at: Some(current_timestamp()),
+            change_seq: 1,
+            version: 1,
+        }
+    }
+
+    fn count_row(count: i64) -> BTreeMap<&'static str, Value> {
+        BTreeMap::from([("num_items", count.into())])
+    }
+
+    fn rows_affected(rows_affected: u64) -> MockExecResult {
+        MockExecResult {
+            last_insert_id: 0,
+            rows_affected,
+        }
+    }
+
+    /// Statements of all transactions with their values, in the order they were executed.
+    /// The mock does not expose statements of a transaction, so they are read from its `Debug`
+    fn executed_statements(db: DatabaseConnection) -> Vec<String> {
+        format!("{:?}", db.into_transaction_log())
+            .split("Statement { ")
+            .skip(1)
+            .map(|statement| statement.replace("\\\"", "\""))
+            .collect()
+    }
+
+    fn position(statements: &[String], pattern: &str) -> usize {
+        statements
+            .iter()
+            .position(|statement| statement.contains(pattern))
+            .unwrap_or_else(|| panic!("No statement contains {}", pattern))
+    }
+
+    #[rocket::async_test]
+    async fn trash_workspace_trashes_task_groups_and_labels_of_the_workspace() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![workspace_model(7)]])
+            .append_query_results(vec![vec![count_row(5)]])
+            .append_exec_results(vec![rows_affected(2), rows_affected(3), rows_affected(1)])
+            .into_connection();
+
+        let deleted = trash_workspace(&db, 7, 1).await.unwrap();
+        assert_eq!(
+            deleted,
+            DeletedCounts {
+                workspaces: 1,
+                task_groups: 2,
+                tasks: 5,
+                labels: 3,
+            }
+        );
+
+        let statements = executed_statements(db);
+        // Tasks are counted in the live task groups of the workspace only
+        let count_tasks = position(
+            &statements,
+            r#"FROM "task" WHERE "task"."task_group_id" IN (SELECT "task_group"."id" FROM "task_group" WHERE "task_group"."workspace_id" = $1 AND "task_group"."deleted_at" IS NULL)"#,
+        );
+        assert!(statements[count_tasks].contains("Int(Some(7))"));
+
+        let trash_task_groups = position(&statements, r#"UPDATE "task_group""#);
+        let trash_labels = position(&statements, r#"UPDATE "label""#);
+        let trash_workspace = position(&statements, r#"UPDATE "workspace""#);
+        assert!(trash_task_groups < trash_workspace);
+        assert!(trash_labels < trash_workspace);
+        assert!(statements[trash_workspace].contains("Int(Some(7))"));
+        assert!(statements.last().unwrap().contains("COMMIT"));
+    }
+
+    #[rocket::async_test]
+    async fn trash_workspace_of_another_user_is_not_found() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![Vec::<workspace::Model>::new()])
+            .into_connection();
+
+        let trash_res = trash_workspace(&db, 7, 2).await;
+        assert!(matches!(trash_res, Err(DbErr::RecordNotFound(_))));
+
+        let statements = executed_statements(db);
+        assert!(!statements
+            .iter()
+            .any(|statement| statement.contains("UPDATE")));
+        assert!(statements.last().unwrap().contains("ROLLBACK"));
+    }
+
+    #[rocket::async_test]
+    async fn trash_workspace_is_rolled_back_when_a_step_fails() {
+        // Trashing the workspace itself has no result, so it fails after its task groups and labels
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![workspace_model(7)]])
+            .append_query_results(vec![vec![count_row(5)]])
+            .append_exec_results(vec![rows_affected(2), rows_affected(3)])
+            .into_connection();
+
+        assert!(trash_workspace(&db, 7, 1).await.is_err());
+
+        let statements = executed_statements(db);
+        assert!(statements.last().unwrap().contains("ROLLBACK"));
+        assert!(!statements
+            .iter()
+            .any(|statement| statement.contains("COMMIT")));
+    }
+
+    #[rocket::async_test]
+    async fn trash_workspace_in_requires_the_expected_version() {
+        // Workspace was changed by a concurrent request after it was selected
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![workspace_model(7)]])
+            .append_query_results(vec![vec![count_row(5)]])
+            .append_exec_results(vec![rows_affected(2), rows_affected(3), rows_affected(0)])
+            .into_connection();
+
+        let tx = db.begin().await.unwrap();
+        let trash_res = trash_workspace_in(&tx, 7, 1, Some(1)).await;
+        assert!(matches!(trash_res, Err(DbErr::RecordNotFound(_))));
+        tx.rollback().await.unwrap();
+
+        let statements = executed_statements(db);
+        let trash_workspace = position(&statements, r#"UPDATE "workspace""#);
+        assert!(statements[trash_workspace].contains(r#""workspace"."version" = $"#));
+        assert!(statements.last().unwrap().contains("ROLLBACK"));
+    }
+
+    #[rocket::async_test]
+    async fn trash_task_group_in_requires_the_expected_version() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![task_group_model(4)]])
+            .append_query_results(vec![vec![count_row(3)]])
+            .append_exec_results(vec![rows_affected(0)])
+            .into_connection();
+
+        let tx = db.begin().await.unwrap();
+        let trash_res = trash_task_group_in(&tx, 4, 1, Some(1)).await;
+        assert!(matches!(trash_res, Err(DbErr::RecordNotFound(_))));
+        tx.rollback().await.unwrap();
+
+        let statements = executed_statements(db);
+        let trash_task_group = position(&statements, r#"UPDATE "task_group""#);
+        assert!(statements[trash_task_group].contains(r#""task_group"."version" = $"#));
+    }
+
+    #[rocket::async_test]
+    async fn trash_task_group_counts_tasks_of_the_task_group() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![task_group_model(4)]])
+            .append_query_results(vec![vec![count_row(3)]])
+            .append_exec_results(vec![rows_affected(1)])
+            .into_connection();
+
+        let deleted = trash_task_group(&db, 4, 1).await.unwrap();
+        assert_eq!(
+            deleted,
+            DeletedCounts {
+                task_groups: 1,
+                tasks: 3,
+                ..Default::default()
+            }
+        );
+
+        let statements = executed_statements(db);
+        // Tasks are selected by their task group, not by their own id
+        let count_tasks = position(&statements, r#"WHERE "task"."task_group_id" = $1"#);
+        assert!(statements[count_tasks].contains("Int(Some(4))"));
+        assert!(!statements
+            .iter()
+            .any(|statement| statement.contains(r#""task"."id" = $1"#)));
+
+        let trash_task_group = position(&statements, r#"UPDATE "task_group""#);
+        assert!(count_tasks < trash_task_group);
+        assert!(statements[trash_task_group].contains("Int(Some(4))"));
+    }
+
+    #[rocket::async_test]
+    async fn purge_trash_removes_children_before_their_parents() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // Labels of expired workspaces, then labels trashed on their own
+            .append_query_results(vec![vec![label_model(11), label_model(12)], vec![]])
+            .append_exec_results(vec![
+                // Tasks, task groups, label ids of tasks, labels and workspaces of expired workspaces
+                rows_affected(6),
+                rows_affected(2),
+                rows_affected(4),
+                rows_affected(2),
+                rows_affected(1),
+                // Tasks and task groups trashed on their own
+                rows_affected(3),
+                rows_affected(1),
+            ])
+            .into_connection();
+
+        let purged = purge_trash(&db, current_timestamp()).await.unwrap();
+        assert_eq!(
+            purged,
+            DeletedCounts {
+                workspaces: 1,
+                task_groups: 3,
+                tasks: 9,
+                labels: 2,
+            }
+        );
+
+        let statements = executed_statements(db);
+        let delete_tasks = position(&statements, r#"DELETE FROM "task""#);
+        let delete_task_groups = position(&statements, r#"DELETE FROM "task_group""#);
+        let remove_labels_ids = position(&statements, r#"UPDATE "task" SET "labels_ids""#);
+        let delete_labels = position(&statements, r#"DELETE FROM "label""#);
+        let delete_workspaces = position(&statements, r#"DELETE FROM "workspace""#);
+        assert!(delete_tasks < delete_task_groups);
+        assert!(delete_task_groups < delete_workspaces);
+        assert!(remove_labels_ids < delete_labels);
+        assert!(delete_labels < delete_workspaces);
+        // Ids of all labels are removed from tasks by a single statement
+        assert_eq!(
+            statements
+                .iter()
+                .filter(|statement| statement.contains(r#"UPDATE "task" SET "labels_ids""#))
+                .count(),
+            1
+        );
+        assert!(statements.last().unwrap().contains("COMMIT"));
+    }
+}
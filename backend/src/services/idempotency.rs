@@ -0,0 +1,160 @@
+//! Idempotency keys of creating requests.
+//!
+//! The first request with a key claims it together with the hash of the request, its response
+//! is stored when it has been handled. Retries with the same key get the stored response instead
+//! of creating the rows again. Keys can be reused after `KEY_TTL_HOURS`.
+//! A claim without a stored response, e.g. of a request whose instance crashed,
+//! can be taken over by a retry after `CLAIM_LEASE_MINUTES`.
+
+use hmac_sha256::Hash;
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, QueryFilter,
+    Statement,
+};
+
+use crate::{
+    current_timestamp,
+    entities::{idempotency_key, prelude::IdempotencyKey},
+};
+
+pub const KEY_TTL_HOURS: i64 = 24;
+/// Longer than any request takes, as the first request can not store its response after that
+pub const CLAIM_LEASE_MINUTES: i64 = 5;
+
+/// Response of the first request with a key
+pub struct StoredResponse {
+    pub status: u16,
+    pub etag: Option<String>,
+    pub body: String,
+}
+
+pub enum KeyClaim {
+    /// Key is new, expired or its claim lease ended, the request is handled
+    /// and its response is stored for the id
+    Claimed(i32),
+    /// Response of the first request with the key
+    Completed(StoredResponse),
+    /// First request with the key is still handled
+    InProgress,
+    /// Key was used for another request
+    Mismatch,
+}
+
+/// Hex encoded SHA-256 of the route and the body of a request
+pub fn request_hash(route: &str, body: &[u8]) -> String {
+    let mut hash = Hash::new();
+    hash.update(route.as_bytes());
+    hash.update(b"\n");
+    hash.update(body);
+    hex::encode(hash.finalize())
+}
+
+/// Claims the key for the request unless it was used within `KEY_TTL_HOURS`,
+/// or claimed within `CLAIM_LEASE_MINUTES` by a request which has not stored its response
+pub async fn claim_key(
+    db: &DatabaseConnection,
+    user_id: i32,
+    key: &str,
+    request_hash: &str,
+) -> Result<KeyClaim, DbErr> {
+    let now = current_timestamp();
+    let expired_before = now - chrono::Duration::hours(KEY_TTL_HOURS);
+    let lease_ended_before = now - chrono::Duration::minutes(CLAIM_LEASE_MINUTES);
+
+    // Concurrent requests with the same key are serialized by the unique index,
+    // only one of them inserts or takes over the expired key or the abandoned claim
+    let claimed_key = IdempotencyKey::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"INSERT INTO "idempotency_key" ("user_id", "key", "request_hash", "created_at")
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT ("user_id", "key") DO UPDATE SET
+                "request_hash" = EXCLUDED."request_hash",
+                "response_status" = NULL,
+                "response_etag" = NULL,
+                "response_body" = NULL,
+                "created_at" = EXCLUDED."created_at"
+            WHERE "idempotency_key"."created_at" < $5
+                OR ("idempotency_key"."response_status" IS NULL
+                    AND "idempotency_key"."created_at" < $6)
+            RETURNING *"#,
+            vec![
+                user_id.into(),
+                key.into(),
+                request_hash.into(),
+                now.into(),
+                expired_before.into(),
+                lease_ended_before.into(),
+            ],
+        ))
+        .one(db)
+        .await?;
+    if let Some(claimed_key) = claimed_key {
+        return Ok(KeyClaim::Claimed(claimed_key.id));
+    }
+
+    let existing_key = IdempotencyKey::find()
+        .filter(idempotency_key::Column::UserId.eq(user_id))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .one(db)
+        .await?;
+    match existing_key {
+        Some(existing_key) if existing_key.request_hash != request_hash => Ok(KeyClaim::Mismatch),
+        Some(idempotency_key::Model {
+            response_status: Some(status),
+            response_etag,
+            response_body,
+            ..
+        }) => Ok(KeyClaim::Completed(StoredResponse {
+            status: status as u16,
+            etag: response_etag,
+            body: response_body.unwrap_or_default(),
+        })),
+        // Response is not stored yet, or the key was just released by a failed request
+        _ => Ok(KeyClaim::InProgress),
+    }
+}
+
+/// Stores response of the request which claimed the key
+pub async fn complete_key(
+    db: &DatabaseConnection,
+    key_id: i32,
+    response: StoredResponse,
+) -> Result<(), DbErr> {
+    IdempotencyKey::update_many()
+        .col_expr(
+            idempotency_key::Column::ResponseStatus,
+            Expr::value(response.status as i16),
+        )
+        .col_expr(
+            idempotency_key::Column::ResponseEtag,
+            Expr::value(response.etag),
+        )
+        .col_expr(
+            idempotency_key::Column::ResponseBody,
+            Expr::value(response.body),
+        )
+        .filter(idempotency_key::Column::Id.eq(key_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Removes the key of a request which could not be handled, so that it can be retried
+pub async fn release_key(db: &DatabaseConnection, key_id: i32) -> Result<(), DbErr> {
+    IdempotencyKey::delete_by_id(key_id).exec(db).await?;
+
+    Ok(())
+}
+
+/// Removes keys older than `KEY_TTL_HOURS`, returns number of removed keys
+pub async fn purge_expired_keys(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let expired_before = current_timestamp() - chrono::Duration::hours(KEY_TTL_HOURS);
+    let delete_res = IdempotencyKey::delete_many()
+        .filter(idempotency_key::Column::CreatedAt.lt(expired_before))
+        .exec(db)
+        .await?;
+
+    Ok(delete_res.rows_affected)
+}
@@ -0,0 +1,130 @@
+//! Prometheus metrics, served in the text format by `GET /metrics`.
+//!
+//! Requests and database queries are recorded as they happen, gauges of the connection pool
+//! and of the number of users, workspaces and tasks are refreshed on every scrape.
+
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+};
+use sea_orm::{
+    metric::Info, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+};
+use std::time::Duration;
+
+use crate::entities::{
+    prelude::{Task, TaskGroup, User, Workspace},
+    task_group, workspace,
+};
+
+/// Queries take milliseconds, unlike whole requests
+const QUERY_DURATION_BUCKETS: [f64; 11] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+];
+
+lazy_static! {
+    static ref HTTP_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "http_requests_total",
+        "Handled requests by route and status",
+        &["method", "route", "status"]
+    )
+    .unwrap();
+    static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "http_request_duration_seconds",
+        "Time from receiving a request to its response by route and status",
+        &["method", "route", "status"]
+    )
+    .unwrap();
+    static ref DB_QUERY_DURATION: HistogramVec = register_histogram_vec!(
+        "db_query_duration_seconds",
+        "Duration of database queries by kind of statement",
+        &["operation"],
+        QUERY_DURATION_BUCKETS.to_vec()
+    )
+    .unwrap();
+    static ref DB_POOL_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "db_pool_connections",
+        "Open connections of the database pool by state",
+        &["state"]
+    )
+    .unwrap();
+    static ref OAUTH_CALLBACKS: IntCounterVec = register_int_counter_vec!(
+        "oauth_callbacks_total",
+        "Sign in callbacks of OAuth providers by result, failures by their reason",
+        &["provider", "result"]
+    )
+    .unwrap();
+    static ref USERS: IntGauge = register_int_gauge!("task_app_users", "Users").unwrap();
+    static ref WORKSPACES: IntGauge =
+        register_int_gauge!("task_app_workspaces", "Workspaces which are not in trash").unwrap();
+    static ref TASKS: IntGauge =
+        register_int_gauge!("task_app_tasks", "Tasks which are not in trash").unwrap();
+}
+
+/// `route` is the URI of the matched route, so that ids in paths do not create new series
+pub fn observe_request(method: &str, route: &str, status: u16, duration: Duration) {
+    let status = status.to_string();
+    let labels = [method, route, status.as_str()];
+    HTTP_REQUESTS.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&labels)
+        .observe(duration.as_secs_f64());
+}
+
+/// Metric callback of the database connection
+pub fn observe_query(info: &Info<'_>) {
+    let operation = match info.statement.sql.trim_start().get(..6) {
+        Some(keyword) if keyword.eq_ignore_ascii_case("select") => "select",
+        Some(keyword) if keyword.eq_ignore_ascii_case("insert") => "insert",
+        Some(keyword) if keyword.eq_ignore_ascii_case("update") => "update",
+        Some(keyword) if keyword.eq_ignore_ascii_case("delete") => "delete",
+        _ => "other",
+    };
+    DB_QUERY_DURATION
+        .with_label_values(&[operation])
+        .observe(info.elapsed.as_secs_f64());
+}
+
+/// `result` is `success` or the reason of the failure
+pub fn observe_oauth_callback(provider: &str, result: &str) {
+    OAUTH_CALLBACKS.with_label_values(&[provider, result]).inc();
+}
+
+/// Updates gauges which are read from the database instead of being recorded
+pub async fn refresh_gauges(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let pool = db.get_postgres_connection_pool();
+    let idle_connections = pool.num_idle() as i64;
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(idle_connections);
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["in_use"])
+        .set(i64::from(pool.size()) - idle_connections);
+
+    let users_count = User::find().count(db).await?;
+    let workspaces_count = Workspace::find()
+        .filter(workspace::Column::DeletedAt.is_null())
+        .count(db)
+        .await?;
+    // Tasks are in trash with their task group, which is in trash with its workspace
+    let tasks_count = Task::find()
+        .inner_join(TaskGroup)
+        .filter(task_group::Column::DeletedAt.is_null())
+        .count(db)
+        .await?;
+    USERS.set(users_count as i64);
+    WORKSPACES.set(workspaces_count as i64);
+    TASKS.set(tasks_count as i64);
+
+    Ok(())
+}
+
+/// All metrics in the Prometheus text format
+pub fn encode() -> String {
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .unwrap();
+    String::from_utf8(buffer).unwrap()
+}
@@ -0,0 +1,6 @@
+pub mod changes;
+pub mod deletion;
+pub mod idempotency;
+pub mod metrics;
+pub mod task_keys;
+pub mod webhooks;
@@ -0,0 +1,134 @@
+//! Human friendly task keys, e.g. `WEB-42`.
+//!
+//! Key consists of the key prefix of the workspace and the number of the task in the workspace.
+//! Numbers are allocated from a counter of the workspace, so they are never reused,
+//! numbers allocated for tasks which failed to be created are skipped.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, QueryFilter, Statement,
+};
+
+use crate::entities::{prelude::Workspace, workspace};
+
+const KEY_PREFIX_INDEX: &str = "idx_workspace_user_id_key_prefix";
+
+lazy_static! {
+    static ref KEY_PREFIX_REGEX: Regex = Regex::new(r"^[A-Z][A-Z0-9]{1,9}$").unwrap();
+}
+
+pub fn task_key(key_prefix: &str, number: i32) -> String {
+    format!("{}-{}", key_prefix, number)
+}
+
+/// Splits key into workspace key prefix and task number, prefix is matched case insensitively
+pub fn parse_task_key(key: &str) -> Option<(String, i32)> {
+    let (key_prefix, number) = key.trim().rsplit_once('-')?;
+    let key_prefix = key_prefix.to_uppercase();
+    if !is_valid_key_prefix(&key_prefix) {
+        return None;
+    }
+
+    match number.parse::<i32>() {
+        Ok(number) if number > 0 => Some((key_prefix, number)),
+        _ => None,
+    }
+}
+
+/// Prefix has 2 to 10 uppercase letters or digits and starts with a letter
+pub fn is_valid_key_prefix(key_prefix: &str) -> bool {
+    KEY_PREFIX_REGEX.is_match(key_prefix)
+}
+
+/// Prefix suggested for a workspace without one, made of the first letters of its title
+pub fn default_key_prefix(title: &str) -> String {
+    let letters: String = title
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .take(4)
+        .collect::<String>()
+        .to_uppercase();
+
+    match letters.len() {
+        0 | 1 => "WS".into(),
+        _ => letters,
+    }
+}
+
+/// Checks whether the user has another workspace with the prefix, trashed workspaces included
+pub async fn is_key_prefix_taken<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    key_prefix: &str,
+    except_workspace_id: Option<i32>,
+) -> Result<bool, DbErr> {
+    let mut select_workspace = Workspace::find()
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::KeyPrefix.eq(key_prefix));
+    if let Some(workspace_id) = except_workspace_id {
+        select_workspace = select_workspace.filter(workspace::Column::Id.ne(workspace_id));
+    }
+
+    Ok(select_workspace.one(db).await?.is_some())
+}
+
+/// Default prefix for the title, suffixed with a number when the user already uses it
+pub async fn available_key_prefix<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    title: &str,
+) -> Result<String, DbErr> {
+    let base_prefix = default_key_prefix(title);
+    let used_prefixes: Vec<String> = Workspace::find()
+        .filter(workspace::Column::UserId.eq(user_id))
+        .filter(workspace::Column::KeyPrefix.starts_with(&base_prefix))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|workspace_model| workspace_model.key_prefix)
+        .collect();
+
+    if !used_prefixes.contains(&base_prefix) {
+        return Ok(base_prefix);
+    }
+    // Base prefix has at most 4 letters, so the suffixed one always fits in 10 characters
+    let key_prefix = (2..)
+        .map(|suffix| format!("{}{}", base_prefix, suffix))
+        .find(|key_prefix| !used_prefixes.contains(key_prefix))
+        .unwrap();
+    Ok(key_prefix)
+}
+
+/// Whether the error is a violation of the unique prefix of the user's workspaces,
+/// raised when two requests claim the same prefix at once
+pub fn is_key_prefix_conflict(err: &DbErr) -> bool {
+    err.to_string().contains(KEY_PREFIX_INDEX)
+}
+
+/// Allocates `count` consecutive task numbers in the workspace, returns the first one.
+///
+/// The counter is incremented in a single statement, concurrent allocations wait
+/// for each other on the row lock of the workspace.
+pub async fn allocate_task_numbers<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    count: i32,
+) -> Result<i32, DbErr> {
+    let allocated = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"UPDATE "workspace" SET "next_task_number" = "next_task_number" + $1
+            WHERE "id" = $2 RETURNING "next_task_number" - $1 AS "first_number""#,
+            vec![count.into(), workspace_id.into()],
+        ))
+        .await?;
+
+    match allocated {
+        Some(row) => row.try_get("", "first_number"),
+        None => Err(DbErr::RecordNotFound(format!(
+            "Workspace with id {} does not exist",
+            workspace_id
+        ))),
+    }
+}
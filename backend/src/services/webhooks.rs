@@ -0,0 +1,373 @@
+//! Outgoing webhooks of workspaces.
+//!
+//! Changes are queued as deliveries, one per subscribed webhook, and sent by a background job.
+//! Each request is signed with HMAC-SHA256 of the body keyed with the webhook secret
+//! (`X-Webhook-Signature: sha256=<hex>`). Failed deliveries are retried with exponential backoff
+//! until `MAX_ATTEMPTS` is reached. Webhooks can only be sent to public addresses, the host
+//! is resolved and checked when a webhook is saved and again before every delivery,
+//! which is then sent to the checked address without resolving the host again.
+
+use hmac_sha256::HMAC;
+use reqwest::{header::CONTENT_TYPE, redirect::Policy, Client, Url};
+use rocket::tokio::net::lookup_host;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend,
+    DbErr, EntityTrait, QueryFilter, Statement,
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    current_timestamp,
+    entities::{
+        prelude::{Webhook, WebhookDelivery},
+        webhook, webhook_delivery,
+    },
+};
+
+pub const DELIVERY_PENDING: &str = "pending";
+pub const DELIVERY_SUCCEEDED: &str = "succeeded";
+pub const DELIVERY_FAILED: &str = "failed";
+
+pub const MAX_ATTEMPTS: i32 = 8;
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+/// Claimed deliveries are not picked up by other instances for this long
+const CLAIM_DURATION_SECS: i64 = 5 * 60;
+pub const BATCH_SIZE: usize = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Queues delivery of the event to webhooks of the workspace which subscribe to its type,
+/// `db` is the transaction of the change so that deliveries are stored only with it
+pub async fn enqueue_deliveries<C: ConnectionTrait>(
+    db: &C,
+    workspace_id: i32,
+    event_id: i64,
+    event_type: &str,
+    payload: &str,
+) -> Result<u64, DbErr> {
+    let subscribed_webhooks: Vec<webhook::Model> = Webhook::find()
+        .filter(webhook::Column::WorkspaceId.eq(workspace_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|webhook_model| webhook_model.event_types.iter().any(|t| t == event_type))
+        .collect();
+    if subscribed_webhooks.is_empty() {
+        return Ok(0);
+    }
+
+    let now = current_timestamp();
+    let deliveries =
+        subscribed_webhooks
+            .iter()
+            .map(|webhook_model| webhook_delivery::ActiveModel {
+                webhook_id: ActiveValue::Set(webhook_model.id),
+                event_id: ActiveValue::Set(event_id),
+                event_type: ActiveValue::Set(event_type.into()),
+                payload: ActiveValue::Set(payload.into()),
+                status: ActiveValue::Set(DELIVERY_PENDING.into()),
+                attempts: ActiveValue::Set(0),
+                next_attempt_at: ActiveValue::Set(now),
+                created_at: ActiveValue::Set(now),
+                ..Default::default()
+            });
+    WebhookDelivery::insert_many(deliveries).exec(db).await?;
+
+    Ok(subscribed_webhooks.len() as u64)
+}
+
+/// Sends a batch of pending deliveries which are due, returns number of attempted deliveries.
+///
+/// Deliveries are claimed with `FOR UPDATE SKIP LOCKED` so that instances
+/// do not send the same delivery at the same time.
+pub async fn deliver_due(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    let now = current_timestamp();
+    let claimed_until = now + chrono::Duration::seconds(CLAIM_DURATION_SECS);
+
+    let claimed_deliveries = WebhookDelivery::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"UPDATE "webhook_delivery" SET "next_attempt_at" = $1 WHERE "id" IN (
+                SELECT "id" FROM "webhook_delivery"
+                WHERE "status" = $2 AND "next_attempt_at" <= $3
+                ORDER BY "next_attempt_at" LIMIT $4
+                FOR UPDATE SKIP LOCKED
+            ) RETURNING *"#,
+            vec![
+                claimed_until.into(),
+                DELIVERY_PENDING.into(),
+                now.into(),
+                (BATCH_SIZE as i64).into(),
+            ],
+        ))
+        .all(db)
+        .await?;
+
+    let claimed_count = claimed_deliveries.len();
+    for delivery in claimed_deliveries {
+        deliver(db, delivery).await?;
+    }
+
+    Ok(claimed_count)
+}
+
+async fn deliver(db: &DatabaseConnection, delivery: webhook_delivery::Model) -> Result<(), DbErr> {
+    let found_webhook = Webhook::find_by_id(delivery.webhook_id).one(db).await?;
+    let webhook_model = match found_webhook {
+        Some(webhook_model) => webhook_model,
+        // Webhook has been removed together with its deliveries
+        None => return Ok(()),
+    };
+
+    // Host may resolve to a different address than when the webhook was saved
+    let client = match check_destination(&webhook_model.url).await {
+        Ok((host, address)) => delivery_client(&host, address).map_err(|err| err.to_string()),
+        Err(err) => Err(err),
+    };
+    let client = match client {
+        Ok(client) => client,
+        Err(err) => {
+            let error = err.chars().take(255).collect();
+            return record_attempt(db, delivery, None, None, Some(error)).await;
+        }
+    };
+
+    let started_at = Instant::now();
+    let send_res = client
+        .post(&webhook_model.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header("X-Webhook-Event", &delivery.event_type)
+        .header("X-Webhook-Delivery", delivery.id.to_string())
+        .header(
+            "X-Webhook-Signature",
+            sign_payload(&webhook_model.secret, &delivery.payload),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+    let response_time_ms = i32::try_from(started_at.elapsed().as_millis()).unwrap_or(i32::MAX);
+
+    let (response_status, error) = match send_res {
+        Ok(response) => {
+            let status = response.status();
+            let error = (!status.is_success())
+                .then(|| format!("Endpoint responded with status {}", status));
+            (Some(i32::from(status.as_u16())), error)
+        }
+        Err(err) => (None, Some(err.to_string().chars().take(255).collect())),
+    };
+
+    record_attempt(db, delivery, response_status, Some(response_time_ms), error).await
+}
+
+/// Stores result of an attempt, failed deliveries are retried until `MAX_ATTEMPTS` is reached
+async fn record_attempt(
+    db: &DatabaseConnection,
+    delivery: webhook_delivery::Model,
+    response_status: Option<i32>,
+    response_time_ms: Option<i32>,
+    error: Option<String>,
+) -> Result<(), DbErr> {
+    let attempts = delivery.attempts + 1;
+    let now = current_timestamp();
+    let mut delivery_to_update: webhook_delivery::ActiveModel = delivery.into();
+    delivery_to_update.attempts = ActiveValue::Set(attempts);
+    delivery_to_update.response_status = ActiveValue::Set(response_status);
+    delivery_to_update.response_time_ms = ActiveValue::Set(response_time_ms);
+    match error {
+        None => {
+            delivery_to_update.status = ActiveValue::Set(DELIVERY_SUCCEEDED.into());
+            delivery_to_update.delivered_at = ActiveValue::Set(Some(now));
+        }
+        Some(_) if attempts >= MAX_ATTEMPTS => {
+            delivery_to_update.status = ActiveValue::Set(DELIVERY_FAILED.into());
+        }
+        Some(_) => {
+            delivery_to_update.next_attempt_at = ActiveValue::Set(now + retry_delay(attempts));
+        }
+    }
+    delivery_to_update.error = ActiveValue::Set(error);
+    delivery_to_update.update(db).await?;
+
+    Ok(())
+}
+
+/// Client which connects to `address` instead of resolving `host` again, so that the host
+/// can not resolve to another address between the check and the request.
+/// Redirects are not followed, their targets are not checked like webhook URLs.
+fn delivery_client(host: &str, address: SocketAddr) -> Result<Client, reqwest::Error> {
+    Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(Policy::none())
+        .resolve(host, address)
+        .build()
+}
+
+/// Resolves host of the URL, returns error if it does not resolve or any of its addresses
+/// is not public, so that webhooks can not reach the internal network or cloud metadata.
+/// Otherwise returns the host with one of its checked addresses.
+pub async fn check_destination(url: &str) -> Result<(String, SocketAddr), String> {
+    let parsed_url = Url::parse(url).map_err(|_| format!("Url {} is not valid", url))?;
+    let (host, port) = match (parsed_url.host_str(), parsed_url.port_or_known_default()) {
+        (Some(host), Some(port)) => (host.trim_start_matches('[').trim_end_matches(']'), port),
+        _ => return Err(format!("Url {} has no host", url)),
+    };
+
+    let addresses: Vec<SocketAddr> = match lookup_host((host, port)).await {
+        Ok(socket_addresses) => socket_addresses.collect(),
+        Err(_) => vec![],
+    };
+    let first_address = match addresses.first() {
+        Some(address) => *address,
+        None => return Err(format!("Host {} could not be resolved", host)),
+    };
+    match addresses
+        .into_iter()
+        .find(|address| !is_public_address(address.ip()))
+    {
+        Some(address) => Err(format!(
+            "Host {} resolves to address {} which is not public",
+            host,
+            address.ip()
+        )),
+        None => Ok((host.to_string(), first_address)),
+    }
+}
+
+/// Loopback, private, link-local (including metadata at 169.254.169.254), shared, unspecified,
+/// broadcast, multicast, reserved and documentation addresses are not public.
+/// IPv6 addresses which embed an IPv4 address are checked by the embedded one.
+fn is_public_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let octets = address.octets();
+            !(address.is_loopback()
+                || address.is_private()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+                || address.is_documentation()
+                || address.is_multicast()
+                || octets[0] == 0
+                // Reserved 240.0.0.0/4
+                || octets[0] >= 240
+                // Shared address space of carrier-grade NAT, 100.64.0.0/10
+                || (octets[0] == 100 && octets[1] & 0b1100_0000 == 64))
+        }
+        IpAddr::V6(address) => {
+            let segments = address.segments();
+            let embedded_address = match segments {
+                // NAT64 64:ff9b::/96 ends with the IPv4 address
+                [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some((high, low)),
+                // 6to4 2002::/16 has the IPv4 address after the prefix
+                [0x2002, high, low, ..] => Some((high, low)),
+                _ => None,
+            };
+            if let Some((high, low)) = embedded_address {
+                let embedded_address = Ipv4Addr::from((u32::from(high) << 16) | u32::from(low));
+                return is_public_address(IpAddr::V4(embedded_address));
+            }
+            match address.to_ipv4_mapped() {
+                Some(mapped_address) => is_public_address(IpAddr::V4(mapped_address)),
+                None => {
+                    !(address.is_loopback()
+                        || address.is_unspecified()
+                        || address.is_multicast()
+                        // Unique local fc00::/7 and link-local fe80::/10
+                        || segments[0] & 0xfe00 == 0xfc00
+                        || segments[0] & 0xffc0 == 0xfe80
+                        // Documentation 2001:db8::/32
+                        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+                }
+            }
+        }
+    }
+}
+
+/// 30s after the first attempt, doubled after every following one
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    chrono::Duration::seconds(RETRY_BASE_DELAY_SECS << (attempts - 1))
+}
+
+/// Value of `X-Webhook-Signature` header
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    format!(
+        "sha256={}",
+        hex::encode(HMAC::mac(payload.as_bytes(), secret.as_bytes()))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_public(address: &str) -> bool {
+        is_public_address(address.parse().unwrap())
+    }
+
+    #[test]
+    fn public_ipv4_addresses_are_public() {
+        assert!(is_public("93.184.216.34"));
+        assert!(is_public("8.8.8.8"));
+        assert!(is_public("100.128.0.1"));
+    }
+
+    #[test]
+    fn internal_ipv4_addresses_are_not_public() {
+        for address in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "0.1.2.3",
+            "255.255.255.255",
+            "192.0.2.1",
+            "224.0.0.1",
+            "239.255.255.250",
+            "240.0.0.1",
+        ] {
+            assert!(!is_public(address), "{} is public", address);
+        }
+    }
+
+    #[test]
+    fn public_ipv6_addresses_are_public() {
+        assert!(is_public("2606:4700:4700::1111"));
+        assert!(is_public("::ffff:93.184.216.34"));
+        assert!(is_public("64:ff9b::5db8:d822"));
+        assert!(is_public("2002:5db8:d822::1"));
+    }
+
+    #[test]
+    fn internal_ipv6_addresses_are_not_public() {
+        for address in [
+            "::1",
+            "::",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "ff02::1",
+            "ff0e::1",
+            "2001:db8::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(address), "{} is public", address);
+        }
+    }
+
+    #[test]
+    fn ipv6_addresses_are_checked_by_their_embedded_ipv4_address() {
+        // NAT64 of 127.0.0.1 and 169.254.169.254
+        assert!(!is_public("64:ff9b::7f00:1"));
+        assert!(!is_public("64:ff9b::a9fe:a9fe"));
+        // 6to4 of 10.0.0.1 and 192.168.0.1
+        assert!(!is_public("2002:a00:1::1"));
+        assert!(!is_public("2002:c0a8:1::"));
+    }
+}
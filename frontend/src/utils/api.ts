@@ -21,6 +21,15 @@ type ErrorResponse = {
 
 type FetchResponse<D> = SuccessResponse<D> | ErrorResponse
 
+const CSRF_COOKIE = 'csrf_token'
+
+// Set by the API for signed in clients, requests which are not GET send it back
+const getCsrfToken = () =>
+	document.cookie
+		.split('; ')
+		.find((cookie) => cookie.startsWith(`${CSRF_COOKIE}=`))
+		?.slice(CSRF_COOKIE.length + 1)
+
 export const apiFetch = async <R, B = Record<string, unknown>>({
 	method = 'GET',
 	path,
@@ -28,10 +37,12 @@ export const apiFetch = async <R, B = Record<string, unknown>>({
 	body,
 }: Config<B>): Promise<FetchResponse<R>> => {
 	try {
+		const csrfToken = getCsrfToken()
 		const headers =
 			method !== 'GET'
 				? {
 						'content-type': 'application/json',
+						...(csrfToken ? { 'x-csrf-token': csrfToken } : {}),
 				  }
 				: undefined
 		const res = (await (